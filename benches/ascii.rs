@@ -0,0 +1,46 @@
+//! Benchmark dumping a large ASCII text file.
+//!
+//! Run with `cargo bench`. The dump is written to the null device, so this measures grouping and
+//! formatting throughput.
+
+use std::io::Write as _;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// Size of the generated input.
+const SIZE: usize = 16 * 1024 * 1024;
+
+fn main() {
+    let line = b"2024-01-01 12:34:56 INFO request id=4711 path=/index.html status=200 bytes=5120\n";
+    let input: Vec<u8> = line.iter().copied().cycle().take(SIZE).collect();
+
+    // A single run is enough when built as a test.
+    let runs = if std::env::args().any(|arg| arg == "--bench") {
+        5
+    } else {
+        1
+    };
+
+    for (name, color) in [("plain", "NO_COLOR"), ("color", "ALWAYS_COLOR")] {
+        let best = (0..runs).map(|_| dump(&input, color)).min().unwrap();
+        let throughput = SIZE as f64 / best.as_secs_f64() / (1024.0 * 1024.0);
+        println!("ascii/{name}: {best:.2?} ({throughput:.1} MiB/s)");
+    }
+}
+
+/// Dump the input with an environment variable set to choose colors, returning the elapsed time.
+fn dump(input: &[u8], color: &str) -> Duration {
+    let start = Instant::now();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_hd"))
+        .env_remove("NO_COLOR")
+        .env_remove("ALWAYS_COLOR")
+        .env(color, "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    assert!(child.wait().unwrap().success());
+
+    start.elapsed()
+}
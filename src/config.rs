@@ -0,0 +1,340 @@
+use crate::Args;
+use onlyargs::OnlyArgs as _;
+use onlyerror::Error;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+/// Errors that can occur while parsing a config file.
+#[derive(Debug, Error, PartialEq)]
+pub(crate) enum ConfigError {
+    /// Line is not a `key = value` pair
+    #[error("Expected `key = value` on line {0}")]
+    Syntax(usize),
+
+    /// Tables are not supported
+    #[error("Tables are not supported, on line {0}")]
+    Table(usize),
+
+    /// Value is not a string, integer, boolean, or array
+    #[error("Expected a string, integer, boolean, or array value on line {0}")]
+    Value(usize),
+
+    /// Key is not an option or flag
+    #[error("Unknown option `{0}` on line {1}")]
+    UnknownKey(String, usize),
+
+    /// Flag value is not a boolean
+    #[error("Expected `true` or `false` for flag `{0}` on line {1}")]
+    FlagValue(String, usize),
+
+    /// Option value is a boolean, or an array for an option that takes one value
+    #[error("Expected a string or integer for option `{0}` on line {1}")]
+    OptionValue(String, usize),
+}
+
+/// Options that can be given more than once, which take an array of values in a config file.
+const MULTIVALUE: &[&str] = &["mark"];
+
+/// Arguments that are not read from a config file.
+const IGNORED: &[&str] = &["help", "version", "config"];
+
+/// Whether an argument is a flag or takes a value.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Kind {
+    Flag,
+    Option,
+}
+
+/// Find the kind of a long argument name in the help text of [`Args`], which lists flags and
+/// options under their own headings.
+fn kind_of(name: &str) -> Option<Kind> {
+    if IGNORED.contains(&name) {
+        return None;
+    }
+
+    let mut kind = None;
+    for line in Args::HELP.lines() {
+        match line {
+            "Flags:" => kind = Some(Kind::Flag),
+            "Options:" => kind = Some(Kind::Option),
+            _ if !line.starts_with(' ') => kind = None,
+            _ => {
+                let mut words = line.split_whitespace();
+                let long = words.find(|word| word.starts_with("--"));
+                if long.map(|long| &long[2..]) == Some(name) {
+                    return kind;
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Get the path of the default config file, if there is a config directory.
+///
+/// This is `$XDG_CONFIG_HOME/hd/config.toml` or `~/.config/hd/config.toml`, and
+/// `%APPDATA%\hd\config.toml` on Windows.
+pub(crate) fn default_path() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    let dir = if cfg!(windows) {
+        var("APPDATA").map(PathBuf::from)
+    } else {
+        var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| Path::new(&home).join(".config")))
+    }?;
+
+    Some(dir.join("hd").join("config.toml"))
+}
+
+/// Parse a config file into command line arguments.
+///
+/// The config file is a flat TOML document with one `key = value` pair for each option or flag
+/// in [`Args`], e.g. `width = 32` or `uppercase = true`. Keys may use `_` or `-`. Values are
+/// strings, integers, or booleans, and a `false` flag is left unset. Options that can be
+/// repeated, like `mark`, also take an array of values.
+///
+/// Keys are checked against [`Args`], so an unknown key is an error that names its line. A flag
+/// set to `true` cannot be unset by the command line, which only adds arguments.
+///
+/// [`Args`]: crate::Args
+pub(crate) fn parse(config: &str) -> Result<Vec<OsString>, ConfigError> {
+    let mut args = Vec::new();
+
+    for (i, line) in config.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('[') {
+            return Err(ConfigError::Table(number));
+        }
+
+        let (key, value) = line.split_once('=').ok_or(ConfigError::Syntax(number))?;
+        let key = key.trim().trim_matches('"');
+        if key.is_empty()
+            || !key
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"_-".contains(&b))
+        {
+            return Err(ConfigError::Syntax(number));
+        }
+        let name = key.replace('_', "-");
+        let kind =
+            kind_of(&name).ok_or_else(|| ConfigError::UnknownKey(key.to_string(), number))?;
+        let arg = format!("--{name}");
+
+        match (
+            kind,
+            parse_value(value.trim()).ok_or(ConfigError::Value(number))?,
+        ) {
+            (Kind::Flag, Value::Bool(true)) => args.push(arg.into()),
+            (Kind::Flag, Value::Bool(false)) => (),
+            (Kind::Flag, _) => return Err(ConfigError::FlagValue(key.to_string(), number)),
+            (Kind::Option, Value::String(value)) => args.extend([arg.into(), value.into()]),
+            (Kind::Option, Value::Array(values)) if MULTIVALUE.contains(&name.as_str()) => {
+                for value in values {
+                    args.extend([arg.clone().into(), value.into()]);
+                }
+            }
+            (Kind::Option, _) => {
+                return Err(ConfigError::OptionValue(key.to_string(), number));
+            }
+        }
+    }
+
+    Ok(args)
+}
+
+/// A config value.
+enum Value {
+    /// A flag.
+    Bool(bool),
+
+    /// An option value; integers are converted to decimal.
+    String(String),
+
+    /// Values of an option that can be repeated.
+    Array(Vec<String>),
+}
+
+/// Parse a value, with an optional trailing comment.
+fn parse_value(value: &str) -> Option<Value> {
+    let (value, rest) = match value.strip_prefix('[') {
+        Some(rest) => parse_array(rest)?,
+        None => parse_scalar(value)?,
+    };
+
+    let rest = rest.trim();
+    (rest.is_empty() || rest.starts_with('#')).then_some(value)
+}
+
+/// Parse the rest of a single-line array of strings and integers, returning it and the text after
+/// the closing bracket. A trailing comma is allowed.
+fn parse_array(mut s: &str) -> Option<(Value, &str)> {
+    let mut values = Vec::new();
+    loop {
+        s = s.trim_start();
+        if let Some(rest) = s.strip_prefix(']') {
+            return Some((Value::Array(values), rest));
+        }
+
+        let (Value::String(value), rest) = parse_scalar(s)? else {
+            return None;
+        };
+        values.push(value);
+        s = rest.trim_start();
+        match s.strip_prefix(',') {
+            Some(rest) => s = rest,
+            None => return Some((Value::Array(values), s.strip_prefix(']')?)),
+        }
+    }
+}
+
+/// Parse a string, integer, or boolean, returning it and the text after it.
+fn parse_scalar(s: &str) -> Option<(Value, &str)> {
+    match s.chars().next()? {
+        '"' => {
+            let (value, rest) = parse_basic_string(&s[1..])?;
+            Some((Value::String(value), rest))
+        }
+        '\'' => {
+            let (value, rest) = s[1..].split_once('\'')?;
+            Some((Value::String(value.to_string()), rest))
+        }
+        _ => {
+            let end = s
+                .find(|ch: char| ch.is_whitespace() || ",]#".contains(ch))
+                .unwrap_or(s.len());
+            let value = match &s[..end] {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                value => Value::String(parse_integer(value)?),
+            };
+
+            Some((value, &s[end..]))
+        }
+    }
+}
+
+/// Parse the rest of a double-quoted string, returning it and the text after the closing quote.
+fn parse_basic_string(s: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = s.char_indices();
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '"' => return Some((value, &s[i + 1..])),
+            '\\' => value.push(match chars.next()?.1 {
+                'n' => '\n',
+                't' => '\t',
+                ch @ ('"' | '\\') => ch,
+                _ => return None,
+            }),
+            ch => value.push(ch),
+        }
+    }
+
+    None
+}
+
+/// Parse a decimal or `0x` hex integer, with optional `_` separators, into a decimal string.
+fn parse_integer(s: &str) -> Option<String> {
+    let digits = s.replace('_', "");
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => i64::from_str_radix(hex, 16).ok()?,
+        None => digits.parse().ok()?,
+    };
+
+    Some(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(config: &str) -> Vec<String> {
+        parse(config)
+            .unwrap()
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let config = r#"
+            # Defaults for hd
+            width = 32
+            numeric = "hex"  # Classify a-f
+            uppercase = true
+            no_addr = false
+            group-gap = 0x2
+            stdin_name = 'in "quotes"'
+            addr_from_name = "_(0x[[:xdigit:]]+)\\."
+            mark = ["0x10:0x20", '0x30:0x40',]  # Two ranges
+        "#;
+
+        assert_eq!(
+            args(config),
+            [
+                "--width",
+                "32",
+                "--numeric",
+                "hex",
+                "--uppercase",
+                "--group-gap",
+                "2",
+                "--stdin-name",
+                "in \"quotes\"",
+                "--addr-from-name",
+                "_(0x[[:xdigit:]]+)\\.",
+                "--mark",
+                "0x10:0x20",
+                "--mark",
+                "0x30:0x40",
+            ]
+        );
+        assert!(args("").is_empty());
+        assert!(args("mark = []").is_empty());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse("width"), Err(ConfigError::Syntax(1)));
+        assert_eq!(parse("\n= 1"), Err(ConfigError::Syntax(2)));
+        assert_eq!(parse("[hd]"), Err(ConfigError::Table(1)));
+        assert_eq!(parse("width = sixteen"), Err(ConfigError::Value(1)));
+        assert_eq!(parse("numeric = \"hex"), Err(ConfigError::Value(1)));
+        assert_eq!(parse("numeric = \"hex\" x"), Err(ConfigError::Value(1)));
+        assert_eq!(parse("mark = [\"0x10:0x20\""), Err(ConfigError::Value(1)));
+        assert_eq!(parse("mark = [true]"), Err(ConfigError::Value(1)));
+
+        // Keys are checked against the command line arguments, with values of the right kind.
+        let key = |key: &str| key.to_string();
+        assert_eq!(
+            parse("\nbogus = 1"),
+            Err(ConfigError::UnknownKey(key("bogus"), 2))
+        );
+        assert_eq!(
+            parse("help = true"),
+            Err(ConfigError::UnknownKey(key("help"), 1))
+        );
+        assert_eq!(
+            parse("no_addr = 1"),
+            Err(ConfigError::FlagValue(key("no_addr"), 1))
+        );
+        assert_eq!(
+            parse("width = true"),
+            Err(ConfigError::OptionValue(key("width"), 1))
+        );
+        assert_eq!(
+            parse("width = [8, 16]"),
+            Err(ConfigError::OptionValue(key("width"), 1))
+        );
+        for name in MULTIVALUE {
+            assert_eq!(kind_of(name), Some(Kind::Option), "{name}");
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::group::{read_groups, Kind};
+use crate::Numeric;
+use std::io::{self, Read, Write};
+
+/// Counts bytes by [`Kind`] instead of printing a dump.
+///
+/// The counts are written by [`Counts::finish`] as a compact table with one line for each kind
+/// and a total. Bytes in grapheme clusters are counted individually.
+pub(crate) struct Counts<W: Write> {
+    /// The writer receiving the table.
+    writer: W,
+
+    /// Numeric classification for groups.
+    numeric: Numeric,
+
+    /// Number of bytes of each kind, in [`Kind::ALL`] order.
+    counts: [u64; 5],
+}
+
+impl<W: Write> Counts<W> {
+    /// Create a byte counter wrapping a writer.
+    pub(crate) fn new(writer: W, numeric: Numeric) -> Self {
+        Self {
+            writer,
+            numeric,
+            counts: [0; 5],
+        }
+    }
+
+    /// Count all bytes from a reader.
+    ///
+    /// Counts accumulate across multiple readers.
+    pub(crate) fn write<R>(&mut self, reader: &mut R) -> io::Result<()>
+    where
+        R: Read,
+    {
+        read_groups(reader, &mut self.numeric, |group| {
+            self.counts[group.kind.index()] += group.span.bytes.len() as u64;
+
+            Ok(())
+        })
+    }
+
+    /// Write the table of counts and flush the writer.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        let total = self.counts.iter().sum::<u64>();
+        let rows = Kind::ALL
+            .iter()
+            .map(|kind| kind.name())
+            .zip(self.counts)
+            .chain([("total", total)]);
+        let width = total.to_string().len();
+        for (name, count) in rows {
+            writeln!(self.writer, "{name:<9} {count:>width$}")?;
+        }
+
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts() {
+        let mut output = Vec::new();
+        let mut counts = Counts::new(&mut output, Numeric::Decimal);
+        counts.write(&mut &b"abc 123\n\x00\xff\xfe"[..]).unwrap();
+        counts.write(&mut "€".as_bytes()).unwrap();
+        counts.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "printable  4\n\
+             numeric    3\n\
+             control    2\n\
+             graphemes  3\n\
+             invalid    2\n\
+             total     14\n"
+        );
+    }
+
+    #[test]
+    fn test_counts_empty() {
+        let mut output = Vec::new();
+        Counts::new(&mut output, Numeric::Hexadecimal)
+            .finish()
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "printable 0\nnumeric   0\ncontrol   0\ngraphemes 0\ninvalid   0\ntotal     0\n"
+        );
+    }
+}
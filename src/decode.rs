@@ -0,0 +1,740 @@
+use onlyerror::Error;
+use std::io::{self, Read};
+use std::ops::Range;
+
+/// Errors that can occur while decoding text input into bytes.
+#[derive(Debug, Error)]
+pub(crate) enum DecodeError {
+    /// Invalid hex character
+    #[error("Invalid hex character `{0}` at offset {1}")]
+    InvalidHex(String, usize),
+
+    /// Hex input has an odd number of digits
+    OddHex,
+
+    /// Invalid base64 character
+    #[error("Invalid base64 character `{0}` at offset {1}")]
+    InvalidBase64(String, usize),
+
+    /// Invalid base64 padding
+    #[error("Invalid base64 padding at offset {0}")]
+    Base64Padding(usize),
+
+    /// Base64 character is from a different alphabet than earlier characters
+    #[error("Base64 character `{0}` at offset {1} mixes the standard and URL-safe alphabets")]
+    MixedBase64(String, usize),
+
+    /// Base64 input ends with an incomplete character group
+    #[error("Base64 input ends with an incomplete character group at offset {0}")]
+    TruncatedBase64(usize),
+
+    /// Dump row address is lower than the previous row
+    #[error("Dump row address goes backwards at offset {0}")]
+    DumpAddress(usize),
+}
+
+impl From<DecodeError> for io::Error {
+    fn from(value: DecodeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, value)
+    }
+}
+
+/// A text decoding state machine, fed one byte of text at a time.
+pub(crate) trait Decode {
+    /// Decode one byte of text at `offset`, appending any complete bytes to `output`.
+    fn push(&mut self, ch: u8, offset: usize, output: &mut Vec<u8>) -> Result<(), DecodeError>;
+
+    /// Flush any remaining state at the end of the text.
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), DecodeError>;
+
+    /// Append the next chunk of output that was held back, like a large gap between dump rows.
+    ///
+    /// Decoders that hold back output must append some of it in `push` or `finish`, so no more
+    /// text is decoded until the rest is drained.
+    fn drain(&mut self, _output: &mut Vec<u8>) {}
+}
+
+/// Maximum number of bytes of held back output appended at a time.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Streaming decoder that wraps a reader providing text.
+///
+/// Text is decoded in chunks, so large inputs are never buffered entirely.
+pub(crate) struct Decoder<R, D> {
+    /// The reader providing text.
+    inner: R,
+
+    /// Decoding state machine.
+    state: D,
+
+    /// Offset of the next byte read from `inner`.
+    offset: usize,
+
+    /// Text read from `inner`.
+    input: Vec<u8>,
+
+    /// Range of `input` that has not been decoded yet.
+    unread: Range<usize>,
+
+    /// Decoded bytes that have not been read yet.
+    output: Vec<u8>,
+
+    /// Position of the next unread byte in `output`.
+    pos: usize,
+
+    /// The end of the text has been reached.
+    eof: bool,
+}
+
+impl<R: Read, D: Decode> Decoder<R, D> {
+    /// Create a decoder wrapping a reader.
+    pub(crate) fn new(inner: R, state: D) -> Self {
+        Self {
+            inner,
+            state,
+            offset: 0,
+            input: vec![0; 4096],
+            unread: 0..0,
+            output: Vec::new(),
+            pos: 0,
+            eof: false,
+        }
+    }
+
+    /// Decode the next chunk of text into the output buffer.
+    fn fill(&mut self) -> io::Result<()> {
+        self.output.clear();
+        self.pos = 0;
+
+        while self.output.is_empty() {
+            // Held back output comes before any more text is decoded.
+            self.state.drain(&mut self.output);
+            if !self.output.is_empty() || self.eof {
+                break;
+            }
+
+            if self.unread.is_empty() {
+                let size = self.inner.read(&mut self.input)?;
+                if size == 0 {
+                    self.eof = true;
+                    self.state.finish(&mut self.output)?;
+                }
+                self.unread = 0..size;
+            }
+
+            // Stop at the first output, which may be followed by held back output.
+            for i in self.unread.by_ref() {
+                self.state
+                    .push(self.input[i], self.offset, &mut self.output)?;
+                self.offset += 1;
+                if !self.output.is_empty() {
+                    break;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<R: Read, D: Decode> Read for Decoder<R, D> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.output.len() {
+            self.fill()?;
+        }
+
+        let available = &self.output[self.pos..];
+        let size = available.len().min(buf.len());
+        buf[..size].copy_from_slice(&available[..size]);
+        self.pos += size;
+
+        Ok(size)
+    }
+}
+
+/// Decoder state for ASCII hex text.
+///
+/// Whitespace and `0x` prefixes are ignored.
+#[derive(Default)]
+pub(crate) struct Hex {
+    /// High nibble waiting for its low nibble.
+    nibble: Option<u8>,
+
+    /// A `0` was seen on a byte boundary, and it might be the start of a `0x` prefix.
+    zero: bool,
+}
+
+impl Decode for Hex {
+    fn push(&mut self, ch: u8, offset: usize, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        if self.zero {
+            self.zero = false;
+            if matches!(ch, b'x' | b'X') {
+                return Ok(());
+            }
+            self.nibble = Some(0);
+        } else if ch == b'0' && self.nibble.is_none() {
+            self.zero = true;
+            return Ok(());
+        }
+
+        if ch.is_ascii_whitespace() {
+            return Ok(());
+        }
+
+        let digit = match ch {
+            b'0'..=b'9' => ch - b'0',
+            b'a'..=b'f' => ch - b'a' + 10,
+            b'A'..=b'F' => ch - b'A' + 10,
+            _ => return Err(DecodeError::InvalidHex(escape(ch), offset)),
+        };
+
+        match self.nibble.take() {
+            Some(high) => output.push(high << 4 | digit),
+            None => self.nibble = Some(digit),
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, _output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        if self.zero || self.nibble.is_some() {
+            Err(DecodeError::OddHex)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Decoder state for base64 text.
+///
+/// Both the standard and URL-safe alphabets are accepted, but not mixed in one input. Padding is
+/// optional, and whitespace is ignored.
+#[derive(Default)]
+pub(crate) struct Base64 {
+    /// Alphabet of the input, chosen by its first character that is only in one alphabet.
+    alphabet: Option<Alphabet>,
+
+    /// Accumulated bits of the current group of four characters.
+    bits: u32,
+
+    /// Number of characters accumulated in the current group.
+    count: usize,
+
+    /// Offset of the first character in the current group.
+    start: usize,
+
+    /// Number of padding characters seen in the current group.
+    padding: usize,
+
+    /// Padding has terminated the input.
+    done: bool,
+}
+
+/// A base64 alphabet, which differs in the characters for 62 and 63.
+#[derive(Copy, Clone, PartialEq)]
+enum Alphabet {
+    /// `+` and `/`.
+    Standard,
+
+    /// `-` and `_`.
+    UrlSafe,
+}
+
+impl Base64 {
+    /// Write the decoded bytes from the current group.
+    fn flush(&mut self, output: &mut Vec<u8>) {
+        let bytes = (self.bits << (6 * (4 - self.count) + 8)).to_be_bytes();
+        output.extend_from_slice(&bytes[..self.count - 1]);
+        self.bits = 0;
+        self.count = 0;
+        self.padding = 0;
+    }
+}
+
+impl Decode for Base64 {
+    fn push(&mut self, ch: u8, offset: usize, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        if ch.is_ascii_whitespace() {
+            return Ok(());
+        }
+
+        if ch == b'=' {
+            if self.done || self.count < 2 {
+                return Err(DecodeError::Base64Padding(offset));
+            }
+            self.padding += 1;
+            if self.count + self.padding == 4 {
+                self.flush(output);
+                self.done = true;
+            }
+
+            return Ok(());
+        }
+
+        if self.done || self.padding > 0 {
+            return Err(DecodeError::Base64Padding(offset));
+        }
+
+        let (sextet, alphabet) = match ch {
+            b'A'..=b'Z' => (ch - b'A', None),
+            b'a'..=b'z' => (ch - b'a' + 26, None),
+            b'0'..=b'9' => (ch - b'0' + 52, None),
+            b'+' => (62, Some(Alphabet::Standard)),
+            b'/' => (63, Some(Alphabet::Standard)),
+            b'-' => (62, Some(Alphabet::UrlSafe)),
+            b'_' => (63, Some(Alphabet::UrlSafe)),
+            _ => return Err(DecodeError::InvalidBase64(escape(ch), offset)),
+        };
+        if let Some(alphabet) = alphabet {
+            if *self.alphabet.get_or_insert(alphabet) != alphabet {
+                return Err(DecodeError::MixedBase64(escape(ch), offset));
+            }
+        }
+
+        if self.count == 0 {
+            self.start = offset;
+        }
+        self.bits = self.bits << 6 | u32::from(sextet);
+        self.count += 1;
+        if self.count == 4 {
+            self.flush(output);
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        match (self.count, self.padding) {
+            (0, _) => Ok(()),
+            (2 | 3, 0) => {
+                self.flush(output);
+                Ok(())
+            }
+            _ => Err(DecodeError::TruncatedBase64(self.start)),
+        }
+    }
+}
+
+/// Custom separators in dump rows, which the dump decoder must know to parse them.
+#[derive(Clone, Default)]
+pub(crate) struct DumpLayout {
+    /// Separator after row addresses, when it is not `:`.
+    pub(crate) separator: Option<String>,
+
+    /// Delimiter before the character table, when it is not `|`. It must contain a character that
+    /// is neither whitespace nor a hex digit.
+    pub(crate) delimiter: Option<String>,
+}
+
+/// Decoder state for `hd` dump text, reversing a dump back into bytes.
+///
+/// Each row is parsed from its address and hex column, and the character table is ignored. Colors,
+/// file headers, and row prefixes like file names and line numbers are skipped. Gaps between row
+/// addresses after a squeezed `*` line repeat the previous row, and other gaps like sparse holes
+/// are filled with zeros. Gaps are written in chunks, so a large one does not fill memory.
+///
+/// Addresses have at least 8 hex digits, like `hd` prints them, so shorter numbers like line
+/// numbers in front of rows without addresses are skipped.
+#[derive(Default)]
+pub(crate) struct Dump {
+    /// Text of the current line.
+    line: Vec<u8>,
+
+    /// Offset of the start of the current line.
+    start: usize,
+
+    /// Address of the next expected byte, after the first row.
+    addr: Option<usize>,
+
+    /// Bytes of the previous row.
+    row: Vec<u8>,
+
+    /// The previous line was a squeezed `*` line.
+    squeezed: bool,
+
+    /// Number of gap bytes that have not been written yet.
+    gap: usize,
+
+    /// Bytes repeated to fill the gap, or empty for zeros.
+    fill: Vec<u8>,
+
+    /// Position in `fill` of the next gap byte.
+    fill_pos: usize,
+
+    /// The row after the gap has not been written yet.
+    held: bool,
+
+    /// Custom separators in rows.
+    layout: DumpLayout,
+}
+
+impl Dump {
+    /// Create a dump decoder for rows with custom separators.
+    pub(crate) fn new(layout: DumpLayout) -> Self {
+        Self {
+            layout,
+            ..Self::default()
+        }
+    }
+
+    /// Parse one line of dump text.
+    fn parse_line(&mut self, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let line = strip_escapes(&self.line);
+        self.line.clear();
+        if line.trim() == "*" {
+            self.squeezed = true;
+            return Ok(());
+        }
+
+        // Rows start with an address followed by a `:`, and any other line is skipped.
+        let separator = self.layout.separator.as_deref().unwrap_or(":");
+        let Some((addr, rest)) = split_addr(&line, separator) else {
+            return Ok(());
+        };
+        let next = self.addr.unwrap_or(addr);
+        if addr < next {
+            return Err(DecodeError::DumpAddress(self.start));
+        }
+        self.gap = addr - next;
+        self.fill = if std::mem::take(&mut self.squeezed) {
+            std::mem::take(&mut self.row)
+        } else {
+            Vec::new()
+        };
+        self.fill_pos = 0;
+        self.addr = Some(addr);
+
+        // The hex column ends at the table separator, or anything else that is not hex bytes.
+        let hex = match &self.layout.delimiter {
+            Some(delimiter) => rest.split(delimiter.as_str()).next().unwrap_or_default(),
+            None => rest,
+        };
+        self.row.clear();
+        let tokens = hex.split_ascii_whitespace();
+        for token in tokens.take_while(|token| is_hex_bytes(token)) {
+            for pair in token.as_bytes().chunks(2) {
+                let pair = std::str::from_utf8(pair).unwrap();
+                self.row.push(u8::from_str_radix(pair, 16).unwrap());
+            }
+        }
+        self.addr = Some(addr + self.row.len());
+        self.held = true;
+        self.drain(output);
+
+        Ok(())
+    }
+}
+
+impl Decode for Dump {
+    fn push(&mut self, ch: u8, offset: usize, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        if self.line.is_empty() {
+            self.start = offset;
+        }
+        if ch == b'\n' {
+            self.parse_line(output)
+        } else {
+            self.line.push(ch);
+            Ok(())
+        }
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        self.parse_line(output)
+    }
+
+    fn drain(&mut self, output: &mut Vec<u8>) {
+        if self.gap > 0 {
+            let size = self.gap.min(CHUNK_SIZE);
+            if self.fill.is_empty() {
+                output.resize(output.len() + size, 0);
+            } else {
+                let fill = self.fill.iter().cycle().skip(self.fill_pos).take(size);
+                output.extend(fill);
+                self.fill_pos = (self.fill_pos + size) % self.fill.len();
+            }
+            self.gap -= size;
+        } else if std::mem::take(&mut self.held) {
+            output.extend_from_slice(&self.row);
+        }
+    }
+}
+
+/// Find the row address in a line, returning it and the rest of the line after it.
+fn split_addr<'a>(line: &'a str, separator: &str) -> Option<(usize, &'a str)> {
+    let mut rest = line;
+    loop {
+        let token = rest.split_ascii_whitespace().next()?;
+        // The first match is the token itself, since only whitespace comes before it.
+        rest = &rest[rest.find(token)? + token.len()..];
+        if let Some(addr) = parse_addr(token, separator) {
+            return Some((addr, rest));
+        }
+
+        // Hex bytes may touch the separator, like `0000_0000_0000_0000:6869`.
+        if let Some((addr, hex)) = token
+            .split_once(separator)
+            .filter(|(_, hex)| is_hex_bytes(hex))
+        {
+            if let Some(addr) = parse_addr(addr, "") {
+                let start = line.len() - rest.len() - hex.len();
+                return Some((addr, &line[start..]));
+            }
+        }
+    }
+}
+
+/// Parse a row address token, like `0000_0000_0000_0010:`.
+fn parse_addr(token: &str, separator: &str) -> Option<usize> {
+    // Whitespace separators are already split from the token.
+    let addr = if separator.trim().is_empty() {
+        token
+    } else {
+        token.strip_suffix(separator)?
+    };
+    let digits = addr.replace('_', "");
+    if digits.len() < 8 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    usize::from_str_radix(&digits, 16).ok()
+}
+
+/// Check if a token is a group of hex bytes, like `a1b2`.
+fn is_hex_bytes(token: &str) -> bool {
+    token.len() % 2 == 0 && token.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Remove ANSI escape sequences (colors and hyperlinks) from a line of text.
+fn strip_escapes(line: &[u8]) -> String {
+    let line = String::from_utf8_lossy(line);
+    let mut output = String::with_capacity(line.len());
+    let mut chars = line.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\x1b' {
+            output.push(ch);
+            continue;
+        }
+        match chars.next() {
+            // CSI: ends with a letter.
+            Some('[') => {
+                chars.by_ref().find(|ch| ch.is_ascii_alphabetic());
+            }
+            // OSC: ends with ST (`ESC \\`) or BEL.
+            Some(']') => {
+                chars.by_ref().find(|&ch| ch == '\\' || ch == '\x07');
+            }
+            _ => (),
+        }
+    }
+
+    output
+}
+
+/// Escape a byte for use in error messages.
+fn escape(ch: u8) -> String {
+    ch.escape_ascii().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode<D: Decode>(input: &str, state: D) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        Decoder::new(input.as_bytes(), state).read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+
+    #[test]
+    fn test_hex_decoder() {
+        let expected = [0xde, 0xad, 0xbe, 0xef, 0xca, 0xfe];
+        let hex = |input| decode(input, Hex::default()).unwrap();
+
+        assert_eq!(hex("deadbeefcafe"), expected);
+        assert_eq!(hex("de ad be ef\nca fe\n"), expected);
+        assert_eq!(hex("DeAdBEEF cAfE"), expected);
+        assert_eq!(hex("0xde 0xad 0XBE 0xef 0xca 0xfe"), expected);
+        assert_eq!(hex("00 0a 00"), [0x00, 0x0a, 0x00]);
+        assert_eq!(hex(""), []);
+    }
+
+    #[test]
+    fn test_hex_decoder_errors() {
+        let hex = |input| decode(input, Hex::default()).unwrap_err().to_string();
+
+        assert_eq!(hex("dead beeg"), "Invalid hex character `g` at offset 8");
+        assert_eq!(hex("abc"), "Hex input has an odd number of digits");
+        assert_eq!(hex("ab 0"), "Hex input has an odd number of digits");
+    }
+
+    #[test]
+    fn test_base64_decoder() {
+        let base64 = |input| decode(input, Base64::default()).unwrap();
+
+        assert_eq!(base64("aGVsbG8gd29ybGQ="), b"hello world");
+        assert_eq!(base64("aGVsbG8gd29ybGQ"), b"hello world");
+        assert_eq!(base64("aGVs\nbG8g\r\nd29y\nbGQ=\n"), b"hello world");
+        assert_eq!(base64("aGk="), b"hi");
+        assert_eq!(base64("aA=="), b"h");
+        assert_eq!(base64("+/+/"), [0xfb, 0xff, 0xbf]);
+        assert_eq!(base64("-_-_"), [0xfb, 0xff, 0xbf]);
+        assert_eq!(base64(""), []);
+
+        // Large inputs are decoded across multiple reads.
+        let input = "AAEC\n".repeat(4096);
+        assert_eq!(base64(&input), [0, 1, 2].repeat(4096));
+    }
+
+    #[test]
+    fn test_dump_decoder() {
+        let dump = |input| decode(input, Dump::default()).unwrap();
+
+        let input = "\
+            \n[example.bin]\n\
+            0000_0000_0000_0000: 6865 6c6c 6f20 776f 726c 640a 0001 0203 | hello world..... |\n\
+            0000_0000_0000_0010: ff                                      | .                |\n";
+        assert_eq!(dump(input), b"hello world\n\x00\x01\x02\x03\xff");
+
+        // Colors, prefixes, and sparse holes.
+        let input = "\
+            \x1b[90mfile:\x1b[0m \x1b[34m     1\x1b[0m \x1b[94m0000_0000_0000_0000\x1b[0m: \x1b[92m6869\x1b[0m | hi |\n\
+            0000_0000_0000_0002: [sparse hole: 0x4 bytes]\n\
+            0000_0000_0000_0006: 2121 | !! |";
+        assert_eq!(dump(input), b"hi\x00\x00\x00\x00!!");
+
+        // Squeezed rows repeat the previous row.
+        let input = "\
+            0000_0000_0000_0000: 6162 | ab |\n\
+            *\n\
+            0000_0000_0000_0006: 63 | c |\n";
+        assert_eq!(dump(input), b"abababc");
+
+        // Addresses do not need to start at zero.
+        assert_eq!(dump("0000_0000_0000_1000: 6869 | hi |\n"), b"hi");
+
+        // Hex bytes may touch the address, like `--no-leading-space`.
+        let input = "\
+            0000_0000_0000_0000:6869 2121 | hi!! |\n\
+            0000_0000_0000_0004:3f   | ? |\n";
+        assert_eq!(dump(input), b"hi!!?");
+
+        // Addresses may be plain hex, like `--raw-offsets`.
+        let input = "\
+            00000000: 6869 | hi |\n\
+            00000004: 2121 | !! |\n";
+        assert_eq!(dump(input), b"hi\x00\x00!!");
+    }
+
+    #[test]
+    fn test_dump_decoder_gaps() {
+        let dump = |input| decode(input, Dump::default()).unwrap();
+
+        // Gaps larger than a chunk are written across multiple reads.
+        let input = "\
+            0000_0000_0000_0000: 6162 | ab |\n\
+            *\n\
+            0000_0000_0002_0001: 63 | c |\n";
+        let output = dump(input);
+        assert_eq!(output.len(), 0x20002);
+        assert_eq!(&output[..4], b"abab");
+        assert_eq!(&output[0x1fffe..], b"abac");
+
+        // A huge gap is not held in memory.
+        let input = "\
+            0000_0000_0000_0000: 6869 | hi |\n\
+            0000_0100_0000_0000: 2121 | !! |\n";
+        let mut decoder = Decoder::new(input.as_bytes(), Dump::default());
+        let mut output = Vec::new();
+        (&mut decoder)
+            .take(0x30000)
+            .read_to_end(&mut output)
+            .unwrap();
+        assert_eq!(&output[..3], b"hi\0");
+        assert!(decoder.output.len() <= CHUNK_SIZE);
+
+        // Line numbers in front of rows without addresses are not addresses.
+        let input = "\
+                 1: 6869 | hi |\n\
+                 2: 2121 | !! |\n";
+        assert_eq!(dump(input), b"");
+    }
+
+    #[test]
+    fn test_dump_decoder_separator() {
+        let dump = |input, separator: &str| {
+            let layout = DumpLayout {
+                separator: Some(separator.to_string()),
+                ..Default::default()
+            };
+            decode(input, Dump::new(layout)).unwrap()
+        };
+
+        let input = "\
+            0000_0000_0000_0000\u{2502} 6869 | hi |\n\
+            0000_0000_0000_0002\u{2502} 21 | ! |\n";
+        assert_eq!(dump(input, "\u{2502}"), b"hi!");
+        assert_eq!(dump(input, ":"), b"");
+
+        let input = "0000_0000_0000_0000  6869 | hi |\n";
+        assert_eq!(dump(input, " "), b"hi");
+    }
+
+    #[test]
+    fn test_dump_decoder_delimiter() {
+        let dump = |input, delimiter: &str| {
+            let layout = DumpLayout {
+                delimiter: Some(delimiter.to_string()),
+                ..Default::default()
+            };
+            decode(input, Dump::new(layout)).unwrap()
+        };
+
+        // The last hex bytes of full rows touch the delimiter.
+        let input = "\
+            0000_0000_0000_0000: 6361 6665<<cafe>>\n\
+            0000_0000_0000_0004: 21       <<!   >>\n";
+        assert_eq!(dump(input, "<<"), b"cafe!");
+        assert_eq!(dump(input, "|"), b"ca\0\0!");
+    }
+
+    #[test]
+    fn test_dump_decoder_errors() {
+        let input = "\
+            0000_0000_0000_0010: 6869 | hi |\n\
+            0000_0000_0000_0000: 6869 | hi |\n";
+
+        assert_eq!(
+            decode(input, Dump::default()).unwrap_err().to_string(),
+            "Dump row address goes backwards at offset 33"
+        );
+    }
+
+    #[test]
+    fn test_base64_decoder_errors() {
+        let base64 = |input| decode(input, Base64::default()).unwrap_err().to_string();
+
+        assert_eq!(base64("aGk*"), "Invalid base64 character `*` at offset 3");
+        assert_eq!(base64("a==="), "Invalid base64 padding at offset 1");
+        assert_eq!(base64("aGk=aGk="), "Invalid base64 padding at offset 4");
+        assert_eq!(base64("aA=a"), "Invalid base64 padding at offset 3");
+        assert_eq!(
+            base64("aA="),
+            "Base64 input ends with an incomplete character group at offset 0"
+        );
+        assert_eq!(
+            base64("aGVs\nb"),
+            "Base64 input ends with an incomplete character group at offset 5"
+        );
+
+        // The first character from one alphabet rules out the other.
+        assert_eq!(
+            base64("aGVsbG8-/w"),
+            "Base64 character `/` at offset 8 mixes the standard and URL-safe alphabets"
+        );
+        assert_eq!(
+            base64("+/+_"),
+            "Base64 character `_` at offset 3 mixes the standard and URL-safe alphabets"
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use crate::Error;
+use std::io::{self, BufRead, BufReader, Read};
+use std::str::FromStr;
+
+/// Magic bytes at the start of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Magic bytes at the start of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Errors that can occur while decompressing.
+#[derive(Debug, onlyerror::Error)]
+pub(crate) enum DecompressError {
+    /// Compression format is not supported by this build
+    #[cfg(not(all(feature = "gzip", feature = "zstd")))]
+    #[error("{0} decompression is not supported by this build")]
+    Unsupported(&'static str),
+
+    /// Compressed stream is corrupt or truncated
+    #[cfg(any(feature = "gzip", feature = "zstd"))]
+    #[error("Corrupt or truncated {0} stream")]
+    Corrupt(&'static str, #[source] io::Error),
+}
+
+impl From<DecompressError> for io::Error {
+    fn from(value: DecompressError) -> Self {
+        let kind = match value {
+            #[cfg(not(all(feature = "gzip", feature = "zstd")))]
+            DecompressError::Unsupported(_) => io::ErrorKind::Unsupported,
+            #[cfg(any(feature = "gzip", feature = "zstd"))]
+            DecompressError::Corrupt(..) => io::ErrorKind::InvalidData,
+        };
+
+        io::Error::new(kind, value)
+    }
+}
+
+/// Compression format of the inputs.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Compression {
+    /// Detect gzip and zstd by their magic bytes, and read other inputs as-is.
+    Auto,
+
+    /// Decompress every input as gzip.
+    Gzip,
+
+    /// Decompress every input as zstd.
+    Zstd,
+}
+
+impl FromStr for Compression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "gz" | "gzip" => Ok(Self::Gzip),
+            "zst" | "zstd" => Ok(Self::Zstd),
+            _ => Err(Error::UnknownCompression(s.to_string())),
+        }
+    }
+}
+
+/// Wrap a reader with a decompressor for the compression format.
+///
+/// With [`Compression::Auto`], the format is chosen by the magic bytes at the start of the stream,
+/// and streams that are not compressed are passed through unchanged. Otherwise, streams in a
+/// different format fail to decompress.
+pub(crate) fn decompress<'a, R>(
+    reader: R,
+    compression: Compression,
+) -> io::Result<Box<dyn Read + 'a>>
+where
+    R: Read + 'a,
+{
+    let mut reader = BufReader::new(reader);
+    let compression = match compression {
+        Compression::Auto => {
+            let magic = reader.fill_buf()?;
+            if magic.starts_with(&GZIP_MAGIC) {
+                Compression::Gzip
+            } else if magic.starts_with(&ZSTD_MAGIC) {
+                Compression::Zstd
+            } else {
+                return Ok(Box::new(reader));
+            }
+        }
+        compression => compression,
+    };
+
+    match compression {
+        Compression::Gzip => gzip(reader),
+        _ => zstd(reader),
+    }
+}
+
+/// Decompress a gzip stream, including multiple concatenated members.
+#[cfg(feature = "gzip")]
+fn gzip<'a, R: BufRead + 'a>(reader: R) -> io::Result<Box<dyn Read + 'a>> {
+    let reader = flate2::bufread::MultiGzDecoder::new(reader);
+
+    Ok(Box::new(Decompressor {
+        reader,
+        format: "gzip",
+    }))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip<'a, R: BufRead + 'a>(_reader: R) -> io::Result<Box<dyn Read + 'a>> {
+    Err(DecompressError::Unsupported("gzip").into())
+}
+
+/// Decompress a zstd stream, including multiple concatenated frames.
+#[cfg(feature = "zstd")]
+fn zstd<'a, R: BufRead + 'a>(reader: R) -> io::Result<Box<dyn Read + 'a>> {
+    let reader = zstd::stream::read::Decoder::with_buffer(reader)?;
+
+    Ok(Box::new(Decompressor {
+        reader,
+        format: "zstd",
+    }))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd<'a, R: BufRead + 'a>(_reader: R) -> io::Result<Box<dyn Read + 'a>> {
+    Err(DecompressError::Unsupported("zstd").into())
+}
+
+/// A decompressing reader, which reports errors as a corrupt stream in its format.
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+struct Decompressor<R> {
+    /// The decompressing reader.
+    reader: R,
+
+    /// Name of the compression format.
+    format: &'static str,
+}
+
+#[cfg(any(feature = "gzip", feature = "zstd"))]
+impl<R: Read> Read for Decompressor<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf).map_err(|err| match err.kind() {
+            io::ErrorKind::Interrupted => err,
+            _ => DecompressError::Corrupt(self.format, err).into(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(bytes: &[u8], compression: Compression) -> io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        decompress(bytes, compression)?.read_to_end(&mut output)?;
+
+        Ok(output)
+    }
+
+    #[test]
+    fn test_decompress() {
+        // Uncompressed input is passed through.
+        assert_eq!(read(b"plain", Compression::Auto).unwrap(), b"plain");
+        assert_eq!(read(b"", Compression::Auto).unwrap(), b"");
+
+        // Forcing a format rejects other input.
+        assert!(read(b"plain", Compression::Gzip).is_err());
+        assert!(read(b"plain", Compression::Zstd).is_err());
+
+        assert_eq!("gz".parse::<Compression>().unwrap(), Compression::Gzip);
+        assert_eq!("GZIP".parse::<Compression>().unwrap(), Compression::Gzip);
+        assert_eq!("zst".parse::<Compression>().unwrap(), Compression::Zstd);
+        assert!("xz".parse::<Compression>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn test_gzip() {
+        use flate2::{write::GzEncoder, Compression as Level};
+        use std::io::Write as _;
+
+        let gzip = |bytes: &[u8]| {
+            let mut encoder = GzEncoder::new(Vec::new(), Level::default());
+            encoder.write_all(bytes).unwrap();
+            encoder.finish().unwrap()
+        };
+
+        // Concatenated members are decompressed as one stream.
+        let input = [gzip(b"hello, "), gzip(b"world")].concat();
+        assert_eq!(read(&input, Compression::Auto).unwrap(), b"hello, world");
+        assert_eq!(read(&input, Compression::Gzip).unwrap(), b"hello, world");
+
+        // Truncated streams are errors.
+        let input = gzip(b"hello, world");
+        let err = read(&input[..input.len() - 4], Compression::Auto).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "Corrupt or truncated gzip stream");
+    }
+
+    #[test]
+    #[cfg(feature = "zstd")]
+    fn test_zstd() {
+        let zstd = |bytes: &[u8]| zstd::encode_all(bytes, 0).unwrap();
+
+        // Concatenated frames are decompressed as one stream.
+        let input = [zstd(b"hello, "), zstd(b"world")].concat();
+        assert_eq!(read(&input, Compression::Auto).unwrap(), b"hello, world");
+        assert_eq!(read(&input, Compression::Zstd).unwrap(), b"hello, world");
+
+        // Truncated streams are errors.
+        let input = zstd(b"hello, world");
+        let err = read(&input[..input.len() - 4], Compression::Auto).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "Corrupt or truncated zstd stream");
+    }
+}
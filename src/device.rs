@@ -0,0 +1,57 @@
+use std::fs::File;
+
+/// Get the number of bytes in a regular file or block device.
+///
+/// Block devices report a length of 0 in their metadata, so their size is found by seeking to the
+/// end instead. Linux reports the device size this way; platforms that do not (like macOS) stream
+/// the device with no known length. Returns `None` for other streams, like pipes and character
+/// devices.
+///
+/// `hd` forbids unsafe code, so the `BLKGETSIZE64` and `DKIOCGETBLOCKCOUNT` ioctls are not used.
+pub(crate) fn len(file: &File) -> Option<u64> {
+    let metadata = file.metadata().ok()?;
+    if metadata.is_file() {
+        return Some(metadata.len());
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt as _;
+
+        if metadata.file_type().is_block_device() {
+            return block_device_size(file);
+        }
+    }
+
+    None
+}
+
+/// Get the size of a block device by seeking to its end, and rewind it.
+#[cfg(unix)]
+fn block_device_size(mut file: &File) -> Option<u64> {
+    use std::io::{Seek as _, SeekFrom};
+
+    let size = file.seek(SeekFrom::End(0)).ok();
+    file.rewind().ok()?;
+
+    size.filter(|&size| size > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_len() {
+        let file = File::open("example.bin").unwrap();
+        assert_eq!(len(&file), Some(176));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_len_character_device() {
+        // Character devices are streamed with no known length.
+        let file = File::open("/dev/null").unwrap();
+        assert_eq!(len(&file), None);
+    }
+}
@@ -0,0 +1,181 @@
+use std::io::{self, Read, Write};
+
+/// Text encodings for raw output.
+#[derive(Copy, Clone)]
+pub(crate) enum Encoding {
+    /// Plain hex digits, two per byte.
+    Hex { uppercase: bool },
+
+    /// Standard base64 with padding.
+    Base64,
+}
+
+/// Streaming encoder that writes input bytes as text with no dump layout.
+pub(crate) struct Encoder<W: Write> {
+    /// The writer receiving encoded text.
+    writer: W,
+
+    /// Text encoding to write.
+    encoding: Encoding,
+
+    /// Number of columns to write before wrapping, or `None` to never wrap.
+    wrap: Option<usize>,
+
+    /// Current output column.
+    column: usize,
+
+    /// Bytes waiting for a complete base64 group.
+    pending: Vec<u8>,
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const HEX_LOWER: &[u8; 16] = b"0123456789abcdef";
+const HEX_UPPER: &[u8; 16] = b"0123456789ABCDEF";
+
+impl<W: Write> Encoder<W> {
+    /// Create an encoder wrapping a writer.
+    pub(crate) fn new(writer: W, encoding: Encoding, wrap: Option<usize>) -> Self {
+        Self {
+            writer,
+            encoding,
+            wrap: wrap.filter(|&wrap| wrap > 0),
+            column: 0,
+            pending: Vec::with_capacity(3),
+        }
+    }
+
+    /// Encode all bytes from a reader.
+    ///
+    /// Multiple readers are encoded as if they were one concatenated stream.
+    pub(crate) fn encode<R>(&mut self, reader: &mut R) -> io::Result<()>
+    where
+        R: Read,
+    {
+        let mut buf = [0; 4096];
+
+        loop {
+            let size = reader.read(&mut buf)?;
+            if size == 0 {
+                break;
+            }
+
+            for &byte in &buf[..size] {
+                match self.encoding {
+                    Encoding::Hex { uppercase } => {
+                        let digits = if uppercase { HEX_UPPER } else { HEX_LOWER };
+                        self.write_text(&[digits[usize::from(byte >> 4)]])?;
+                        self.write_text(&[digits[usize::from(byte & 0xf)]])?;
+                    }
+                    Encoding::Base64 => {
+                        self.pending.push(byte);
+                        if self.pending.len() == 3 {
+                            self.write_base64()?;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Write any remaining partial base64 group and a final newline, then flush the writer.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            self.write_base64()?;
+        }
+        if self.column > 0 {
+            self.writer.write_all(b"\n")?;
+        }
+
+        self.writer.flush()
+    }
+
+    /// Write the pending bytes as one base64 group, with padding if it is incomplete.
+    fn write_base64(&mut self) -> io::Result<()> {
+        let len = self.pending.len();
+        self.pending.resize(3, 0);
+        let bits = u32::from_be_bytes([0, self.pending[0], self.pending[1], self.pending[2]]);
+        self.pending.clear();
+
+        let mut group = [b'='; 4];
+        for (i, ch) in group.iter_mut().enumerate().take(len + 1) {
+            *ch = BASE64[(bits >> (18 - i * 6)) as usize & 0x3f];
+        }
+
+        for ch in group {
+            self.write_text(&[ch])?;
+        }
+
+        Ok(())
+    }
+
+    /// Write encoded text, wrapping lines as necessary.
+    fn write_text(&mut self, text: &[u8]) -> io::Result<()> {
+        if self.wrap == Some(self.column) {
+            self.writer.write_all(b"\n")?;
+            self.column = 0;
+        }
+
+        self.writer.write_all(text)?;
+        self.column += text.len();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(input: &[u8], encoding: Encoding, wrap: Option<usize>) -> String {
+        let mut output = Vec::new();
+        let mut encoder = Encoder::new(&mut output, encoding, wrap);
+        encoder.encode(&mut &input[..]).unwrap();
+        encoder.finish().unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_hex_encoder() {
+        let lower = Encoding::Hex { uppercase: false };
+        let upper = Encoding::Hex { uppercase: true };
+
+        assert_eq!(encode(b"\xde\xad\xbe\xef", lower, None), "deadbeef\n");
+        assert_eq!(encode(b"\xde\xad\xbe\xef", upper, None), "DEADBEEF\n");
+        assert_eq!(encode(b"\xde\xad\xbe\xef", lower, Some(4)), "dead\nbeef\n");
+        assert_eq!(
+            encode(b"\xde\xad\xbe\xef", lower, Some(3)),
+            "dea\ndbe\nef\n"
+        );
+        assert_eq!(encode(b"", lower, None), "");
+    }
+
+    #[test]
+    fn test_base64_encoder() {
+        assert_eq!(
+            encode(b"hello world", Encoding::Base64, None),
+            "aGVsbG8gd29ybGQ=\n"
+        );
+        assert_eq!(encode(b"hi", Encoding::Base64, None), "aGk=\n");
+        assert_eq!(encode(b"h", Encoding::Base64, None), "aA==\n");
+        assert_eq!(encode(b"\xfb\xff\xbf", Encoding::Base64, None), "+/+/\n");
+        assert_eq!(
+            encode(b"hello world", Encoding::Base64, Some(8)),
+            "aGVsbG8g\nd29ybGQ=\n"
+        );
+        assert_eq!(encode(b"", Encoding::Base64, None), "");
+    }
+
+    #[test]
+    fn test_base64_encoder_concatenated() {
+        let mut output = Vec::new();
+        let mut encoder = Encoder::new(&mut output, Encoding::Base64, None);
+        encoder.encode(&mut &b"hello"[..]).unwrap();
+        encoder.encode(&mut &b" world"[..]).unwrap();
+        encoder.finish().unwrap();
+
+        assert_eq!(output, b"aGVsbG8gd29ybGQ=\n");
+    }
+}
@@ -1,3 +1,5 @@
+use crate::ambiguous::is_ambiguous;
+use crate::EastAsianWidth;
 use unicode_segmentation::UnicodeSegmentation;
 
 /// A grapheme cluster.
@@ -19,21 +21,107 @@ impl Span<'_> {
 
     /// Parse the first available grapheme cluster from a byte slice if possible.
     pub(crate) fn parse(bytes: &[u8]) -> Option<Span<'_>> {
-        let s = std::str::from_utf8(bytes).ok()?;
+        match Self::parse_partial(bytes) {
+            ParseResult::Complete(span) => Some(span),
+            ParseResult::NeedsMore(_) | ParseResult::Invalid => None,
+        }
+    }
+
+    /// Parse the first available grapheme cluster from a byte slice, or report whether the slice
+    /// is an incomplete UTF-8 sequence that may become valid with more bytes.
+    pub(crate) fn parse_partial(bytes: &[u8]) -> ParseResult<'_> {
+        let s = match std::str::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(err) if err.valid_up_to() > 0 => {
+                std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap()
+            }
+            Err(err) if err.error_len().is_none() => {
+                let expected = match bytes[0] {
+                    0xc0..=0xdf => 2,
+                    0xe0..=0xef => 3,
+                    _ => 4,
+                };
+                return ParseResult::NeedsMore(expected - bytes.len());
+            }
+            Err(_) => return ParseResult::Invalid,
+        };
         let mut graphemes = UnicodeSegmentation::graphemes(s, true);
 
-        graphemes.next().map(|parsed| Span {
-            bytes: &bytes[..parsed.len()],
-            parsed: Some(parsed),
-        })
+        match graphemes.next() {
+            Some(parsed) => ParseResult::Complete(Span {
+                bytes: &bytes[..parsed.len()],
+                parsed: Some(parsed),
+            }),
+            None => ParseResult::Invalid,
+        }
+    }
+
+    /// Check if a parsed grapheme cluster is an emoji.
+    ///
+    /// Always `false` without the `emoji` feature.
+    pub(crate) fn is_emoji(&self) -> bool {
+        #[cfg(feature = "emoji")]
+        return self.parsed.is_some_and(is_emoji);
+
+        #[cfg(not(feature = "emoji"))]
+        false
+    }
+
+    /// Check if a parsed grapheme cluster is valid UTF-8 that is unlikely to appear in text:
+    ///
+    /// - A cluster starting with a zero-width code point, like a combining mark, zero-width joiner,
+    ///   or variation selector, which has no base character to attach to
+    /// - A cluster containing a noncharacter: `U+FDD0..=U+FDEF`, or any code point ending in
+    ///   `FFFE` or `FFFF`
+    ///
+    /// Marks after an ASCII character start their own cluster, so `after_base` tells whether the
+    /// previous character could be their base.
+    pub(crate) fn is_suspicious(&self, after_base: bool) -> bool {
+        let cluster = self.parsed.unwrap_or_default();
+        let leading_mark = cluster.chars().next().is_some_and(is_zero_width);
+        let noncharacter = cluster.chars().any(|ch| {
+            let code = u32::from(ch);
+            (0xfdd0..=0xfdef).contains(&code) || code & 0xfffe == 0xfffe
+        });
+
+        (leading_mark && !after_base) || noncharacter
     }
 
     /// Show a parsed grapheme cluster in the character table.
-    pub(crate) fn as_char(&self, index: usize, column: usize, width: usize) -> Char<'_> {
+    ///
+    /// Characters with an ambiguous East Asian width take two columns when `east_asian` is
+    /// [`EastAsianWidth::Wide`].
+    pub(crate) fn as_char(
+        &self,
+        index: usize,
+        column: usize,
+        width: usize,
+        east_asian: EastAsianWidth,
+    ) -> Char<'_> {
         // Correctly handle row wrapping with double-wide characters.
         let cluster = self.parsed.unwrap();
-        let wide = unicode_display_width::width(cluster) == 2;
-        if (index == 0 && (!wide || column != width - 1)) || (index == 1 && wide && column == 0) {
+        let cluster_width = display_width(cluster, east_asian);
+        let wide = cluster_width == 2;
+        if wide && self.bytes.len() == 2 {
+            // Two-byte wide clusters only have room for themselves within one row. When they would
+            // wrap, a placeholder is shown instead.
+            return match (index, column) {
+                (0, column) if column == width - 1 => Char::Placeholder,
+                (0, _) => Char::Cluster(cluster),
+                (_, 0) => Char::Space,
+                _ => Char::Skip,
+            };
+        }
+        if cluster_width == 0 {
+            // Zero-width clusters (e.g. lone combining marks) are shown as a placeholder.
+            if index == 0 {
+                Char::Placeholder
+            } else {
+                Char::Space
+            }
+        } else if (index == 0 && (!wide || column != width - 1))
+            || (index == 1 && wide && column == 0)
+        {
             Char::Cluster(cluster)
         } else if wide && ((index == 1 && column != 0) || (index == 2 && column == 1)) {
             Char::Skip
@@ -43,11 +131,104 @@ impl Span<'_> {
     }
 }
 
+/// Get the display width of a grapheme cluster.
+///
+/// Clusters that start with a zero-width code point (e.g. a combining mark with no base character)
+/// have no width of their own; terminals render them on top of the previous cell. Clusters that
+/// start with an ambiguous-width code point are double-wide with [`EastAsianWidth::Wide`].
+pub(crate) fn display_width(cluster: &str, east_asian: EastAsianWidth) -> u64 {
+    let first = cluster.chars().next();
+    if first.is_some_and(is_zero_width) {
+        0
+    } else if matches!(east_asian, EastAsianWidth::Wide) && first.is_some_and(is_ambiguous) {
+        2
+    } else {
+        unicode_display_width::width(cluster)
+    }
+}
+
+/// Check if a code point has no width of its own, like a combining mark.
+fn is_zero_width(ch: char) -> bool {
+    matches!(ch,
+        '\u{0300}'..='\u{036f}' // Combining Diacritical Marks
+        | '\u{1ab0}'..='\u{1aff}' // Combining Diacritical Marks Extended
+        | '\u{1dc0}'..='\u{1dff}' // Combining Diacritical Marks Supplement
+        | '\u{200b}'..='\u{200d}' // Zero-width space, non-joiner, and joiner
+        | '\u{20d0}'..='\u{20ff}' // Combining Diacritical Marks for Symbols
+        | '\u{fe00}'..='\u{fe0f}' // Variation Selectors
+        | '\u{fe20}'..='\u{fe2f}' // Combining Half Marks
+        | '\u{e0100}'..='\u{e01ef}' // Variation Selectors Supplement
+    )
+}
+
+/// Check if a grapheme cluster is shown as an emoji.
+///
+/// Clusters with a character that defaults to emoji presentation are emoji. Other emoji characters
+/// (like `©` or `❤`) default to text presentation, so they are only emoji when followed by the
+/// emoji presentation selector.
+#[cfg(feature = "emoji")]
+fn is_emoji(cluster: &str) -> bool {
+    use unicode_properties::{emoji, EmojiStatus, UnicodeEmoji};
+
+    let presentation = |ch: char| {
+        matches!(
+            ch.emoji_status(),
+            EmojiStatus::EmojiPresentation
+                | EmojiStatus::EmojiPresentationAndModifierBase
+                | EmojiStatus::EmojiPresentationAndEmojiComponent
+                | EmojiStatus::EmojiPresentationAndModifierAndEmojiComponent
+        )
+    };
+
+    cluster.chars().any(presentation)
+        || (cluster.chars().any(emoji::is_emoji_presentation_selector)
+            && cluster.chars().any(UnicodeEmoji::is_emoji_char))
+}
+
+/// The result of parsing a grapheme cluster from a byte slice that may be incomplete.
+pub(crate) enum ParseResult<'a> {
+    /// A complete grapheme cluster.
+    Complete(Span<'a>),
+
+    /// An incomplete UTF-8 sequence which needs this many more bytes.
+    NeedsMore(usize),
+
+    /// Invalid UTF-8.
+    Invalid,
+}
+
+/// Get the length of an incomplete UTF-8 sequence at the end of a byte slice.
+///
+/// Returns 0 when the byte slice does not end with an incomplete sequence.
+pub(crate) fn incomplete_len(bytes: &[u8]) -> usize {
+    // Incomplete sequences never end with an ASCII byte.
+    if bytes.last().map_or(true, u8::is_ascii) {
+        return 0;
+    }
+
+    // UTF-8 sequences are at most 4 bytes long, so only the last 3 bytes can be incomplete.
+    let start = bytes.len().saturating_sub(3);
+
+    (start..bytes.len())
+        .find_map(|i| match Span::parse_partial(&bytes[i..]) {
+            ParseResult::NeedsMore(more) => {
+                let len = bytes.len() - i;
+                debug_assert!(len + more <= 4, "UTF-8 sequence is too long");
+                Some(len)
+            }
+            ParseResult::Complete(_) | ParseResult::Invalid => None,
+        })
+        .unwrap_or(0)
+}
+
 /// How to show a span in the character table.
 pub(crate) enum Char<'a> {
     /// Show the grapheme cluster.
     Cluster(&'a str),
 
+    /// Show a placeholder for a cluster with no display width.
+    Placeholder,
+
     /// Show a blank space.
     Space,
 
@@ -58,6 +239,7 @@ pub(crate) enum Char<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::EastAsianWidth::{Narrow, Wide};
 
     #[test]
     fn test_as_ascii() {
@@ -66,19 +248,213 @@ mod tests {
 
         // Normal cases: grapheme cluster is shown on first line.
         for j in 0..7 {
-            assert!(matches!(span.as_char(0, j, 8), Char::Cluster(_)));
-            assert!(matches!(span.as_char(1, (j + 1) % 8, 8), Char::Skip));
+            assert!(matches!(span.as_char(0, j, 8, Narrow), Char::Cluster(_)));
+            assert!(matches!(
+                span.as_char(1, (j + 1) % 8, 8, Narrow),
+                Char::Skip
+            ));
             for i in 2..astronaut.len() {
-                assert!(matches!(span.as_char(i, (j + i) % 8, 8), Char::Space));
+                assert!(matches!(
+                    span.as_char(i, (j + i) % 8, 8, Narrow),
+                    Char::Space
+                ));
             }
         }
 
         // Edge case: grapheme cluster is shown on second line.
-        assert!(matches!(span.as_char(0, 7, 8), Char::Space));
-        assert!(matches!(span.as_char(1, 0, 8), Char::Cluster(_)));
-        assert!(matches!(span.as_char(2, 1, 8), Char::Skip));
+        assert!(matches!(span.as_char(0, 7, 8, Narrow), Char::Space));
+        assert!(matches!(span.as_char(1, 0, 8, Narrow), Char::Cluster(_)));
+        assert!(matches!(span.as_char(2, 1, 8, Narrow), Char::Skip));
         for i in 3..astronaut.len() {
-            assert!(matches!(span.as_char(i, (i - 2) % 8, 8), Char::Space));
+            assert!(matches!(
+                span.as_char(i, (i - 2) % 8, 8, Narrow),
+                Char::Space
+            ));
         }
+
+        // A double-width CJK character starting in the final column wraps to the next row, leaving
+        // a space behind, for any width.
+        let wide = "\u{4e2d}".as_bytes();
+        let span = Span::parse(wide).unwrap();
+        for width in [2, 3, 8, 16, 32] {
+            assert!(matches!(
+                span.as_char(0, width - 2, width, Narrow),
+                Char::Cluster(_)
+            ));
+            assert!(matches!(
+                span.as_char(1, width - 1, width, Narrow),
+                Char::Skip
+            ));
+            assert!(matches!(span.as_char(2, 0, width, Narrow), Char::Space));
+
+            assert!(matches!(
+                span.as_char(0, width - 1, width, Narrow),
+                Char::Space
+            ));
+            assert!(matches!(
+                span.as_char(1, 0, width, Narrow),
+                Char::Cluster(_)
+            ));
+            assert!(matches!(span.as_char(2, 1, width, Narrow), Char::Skip));
+        }
+    }
+
+    #[test]
+    fn test_as_ascii_ambiguous() {
+        // The degree sign is single-wide, unless ambiguous characters are treated as wide.
+        let degree = "\u{b0}".as_bytes();
+        let span = Span::parse(degree).unwrap();
+        assert_eq!(display_width("\u{b0}", Narrow), 1);
+        assert_eq!(display_width("\u{b0}", Wide), 2);
+        for j in 0..8 {
+            assert!(matches!(span.as_char(0, j, 8, Narrow), Char::Cluster(_)));
+            assert!(matches!(
+                span.as_char(1, (j + 1) % 8, 8, Narrow),
+                Char::Space
+            ));
+        }
+
+        for j in 0..7 {
+            assert!(matches!(span.as_char(0, j, 8, Wide), Char::Cluster(_)));
+            assert!(matches!(span.as_char(1, j + 1, 8, Wide), Char::Skip));
+        }
+        // There is no room to wrap a two-byte wide character.
+        assert!(matches!(span.as_char(0, 7, 8, Wide), Char::Placeholder));
+        assert!(matches!(span.as_char(1, 0, 8, Wide), Char::Space));
+
+        // Unambiguous characters are unchanged.
+        assert_eq!(display_width("a", Wide), 1);
+        assert_eq!(display_width("\u{4e2d}", Narrow), 2);
+    }
+
+    #[test]
+    fn test_as_ascii_zero_width() {
+        let accent = "\u{301}".as_bytes();
+        let span = Span::parse(accent).unwrap();
+
+        // The placeholder is always shown in the first column, regardless of row wrapping.
+        for j in 0..8 {
+            assert!(matches!(span.as_char(0, j, 8, Narrow), Char::Placeholder));
+            assert!(matches!(
+                span.as_char(1, (j + 1) % 8, 8, Narrow),
+                Char::Space
+            ));
+        }
+
+        let zwj = "\u{200d}".as_bytes();
+        let span = Span::parse(zwj).unwrap();
+        for j in 0..8 {
+            assert!(matches!(span.as_char(0, j, 8, Narrow), Char::Placeholder));
+            for i in 1..zwj.len() {
+                assert!(matches!(
+                    span.as_char(i, (j + i) % 8, 8, Narrow),
+                    Char::Space
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_suspicious() {
+        let suspicious = |text: &str, after_base| {
+            Span::parse(text.as_bytes())
+                .unwrap()
+                .is_suspicious(after_base)
+        };
+
+        // Noncharacters.
+        for text in [
+            "\u{fdd0}",
+            "\u{fdef}",
+            "\u{fffe}",
+            "\u{ffff}",
+            "\u{1fffe}",
+            "\u{10ffff}",
+        ] {
+            assert!(suspicious(text, true), "{text:?}");
+        }
+
+        // Leading marks are only suspicious without a base character before them.
+        for text in ["\u{301}", "\u{200d}", "\u{fe0f}"] {
+            assert!(suspicious(text, false), "{text:?}");
+            assert!(!suspicious(text, true), "{text:?}");
+        }
+
+        for text in [
+            "é",
+            "e\u{301}",
+            "\u{4e2d}",
+            "€",
+            "\u{fdcf}",
+            "\u{fffd}",
+            "👩🏻‍🚀",
+        ] {
+            assert!(!suspicious(text, false), "{text:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "emoji")]
+    fn test_is_emoji() {
+        for emoji in ["👩🏻‍🚀", "🇯🇵", "⭐", "\u{2764}\u{fe0f}", "1\u{fe0f}\u{20e3}"]
+        {
+            assert!(Span::parse(emoji.as_bytes()).unwrap().is_emoji(), "{emoji}");
+        }
+        for text in ["é", "e\u{301}", "\u{4e2d}", "€", "©", "\u{2764}"] {
+            assert!(!Span::parse(text.as_bytes()).unwrap().is_emoji(), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_parse_partial() {
+        let euro = "€".as_bytes();
+        let astronaut = "👩🏻‍🚀".as_bytes();
+
+        // Complete clusters, including those followed by invalid bytes.
+        for bytes in [euro, astronaut, b"\xe2\x82\xac\xff", b"a\xe2"] {
+            assert!(matches!(
+                Span::parse_partial(bytes),
+                ParseResult::Complete(_)
+            ));
+        }
+        assert!(matches!(
+            Span::parse_partial(b"\xe2\x82\xac\xff"),
+            ParseResult::Complete(Span {
+                bytes: b"\xe2\x82\xac",
+                ..
+            })
+        ));
+
+        // Incomplete sequences.
+        assert!(matches!(
+            Span::parse_partial(&euro[..1]),
+            ParseResult::NeedsMore(2)
+        ));
+        assert!(matches!(
+            Span::parse_partial(&euro[..2]),
+            ParseResult::NeedsMore(1)
+        ));
+        assert!(matches!(
+            Span::parse_partial(&astronaut[..3]),
+            ParseResult::NeedsMore(1)
+        ));
+
+        // Invalid sequences.
+        for bytes in [&b"\xff"[..], b"\x82\xac", b"\xe2\x28", b""] {
+            assert!(matches!(Span::parse_partial(bytes), ParseResult::Invalid));
+        }
+    }
+
+    #[test]
+    fn test_incomplete_len() {
+        let euro = "€".as_bytes();
+
+        assert_eq!(incomplete_len(b"abc"), 0);
+        assert_eq!(incomplete_len(b""), 0);
+        assert_eq!(incomplete_len(euro), 0);
+        assert_eq!(incomplete_len(&euro[..1]), 1);
+        assert_eq!(incomplete_len(&[b"abc", &euro[..2]].concat()), 2);
+        assert_eq!(incomplete_len(b"abc\xff"), 0);
+        assert_eq!(incomplete_len(&"👩".as_bytes()[..3]), 3);
     }
 }
@@ -1,4 +1,8 @@
-use crate::{grapheme::Span, Numeric};
+use crate::{
+    grapheme::{self, Span},
+    Numeric,
+};
+use std::io::{self, Read};
 
 /// Byte slices are grouped into spans by [`Kind`].
 pub(crate) struct Group<'a> {
@@ -14,9 +18,17 @@ pub(crate) struct Group<'a> {
 pub(crate) enum Kind {
     /// Numeric characters, depending on [`Numeric`] context:
     ///
+    /// - None: No bytes are numeric
+    /// - Binary: `0x30..=0x31`
     /// - Octal decimal: `0x30..=0x37`
     /// - Decimal: `0x30..=0x39`
     /// - Hexadecimal: `0x30..=0x39`, `0x41..=0x46`, and `0x61..=0x66`
+    /// - Hexadecimal digits only: `0x30..=0x39`
+    ///
+    /// Numeric and printable spans break wherever the classification changes, so in hexadecimal
+    /// context a word like `package` splits into `p`, `ac`, `k`, `a`, `g`, and `e`.
+    /// Use `--numeric-color-only` to keep such words in one printable span while still coloring
+    /// the numeric bytes.
     Numeric,
 
     /// ASCII printable characters: `0x20..=0x7e`
@@ -36,21 +48,115 @@ impl Group<'_> {
     /// Parse a group (span and classification) from a byte slice.
     pub(crate) fn gather(bytes: &[u8], numeric: Numeric) -> Group<'_> {
         debug_assert!(!bytes.is_empty(), "Cannot gather an empty byte slice");
-        let byte = bytes[0];
-
-        if Kind::is_numeric(byte, numeric) {
-            Self::numeric_span(bytes, numeric)
-        } else if Kind::is_printable(byte) {
-            Self::printable_span(bytes, numeric)
-        } else if Kind::is_control(byte) {
-            Self::control_span(bytes)
-        } else if let Some(span) = Span::parse(bytes) {
-            Group {
+
+        // ASCII bytes never start a multibyte grapheme cluster, so they skip UTF-8 parsing.
+        if bytes[0].is_ascii() {
+            return match Kind::classify(bytes[0], numeric) {
+                Kind::Numeric => Self::numeric_span(bytes, numeric),
+                Kind::Printable => Self::printable_span(bytes, numeric),
+                _ => Self::control_span(bytes),
+            };
+        }
+
+        match Span::parse(bytes) {
+            Some(span) => Group {
                 kind: Kind::Graphemes,
                 span,
+            },
+            None => Self::invalid_span(bytes),
+        }
+    }
+
+    /// Parse a group like [`Group::gather`], keeping numbers together as whole tokens.
+    ///
+    /// A numeric token is a run of numeric bytes with an optional leading `-` and an optional
+    /// decimal point followed by more numeric bytes, like `-12.5` or `.5`. Printable groups stop
+    /// before the sign or decimal point that starts a token.
+    pub(crate) fn gather_tokens(bytes: &[u8], numeric: Numeric) -> Group<'_> {
+        debug_assert!(!bytes.is_empty(), "Cannot gather an empty byte slice");
+
+        let length = Self::token_len(bytes, numeric);
+        if length > 0 {
+            return Self::new(Kind::Numeric, &bytes[..length]);
+        }
+
+        match Kind::classify(bytes[0], numeric) {
+            Kind::Printable => {
+                let mut length = 1;
+                while length < bytes.len()
+                    && Kind::is_printable(bytes[length])
+                    && Self::token_len(&bytes[length..], numeric) == 0
+                {
+                    length += 1;
+                }
+
+                Self::new(Kind::Printable, &bytes[..length])
             }
+            _ => Self::gather(bytes, numeric),
+        }
+    }
+
+    /// Parse a group with `gather`, treating numeric groups shorter than `min_run` bytes as
+    /// printable.
+    ///
+    /// Short numeric groups are merged into the surrounding printable group, so a stray digit in a
+    /// word like `abc5def` does not split it.
+    pub(crate) fn gather_min_run(
+        bytes: &[u8],
+        numeric: Numeric,
+        min_run: usize,
+        gather: fn(&[u8], Numeric) -> Group<'_>,
+    ) -> Group<'_> {
+        let mut length = 0;
+        while length < bytes.len() {
+            let group = gather(&bytes[length..], numeric);
+            let size = group.span.bytes.len();
+            match group.kind {
+                Kind::Numeric if size >= min_run && length == 0 => return group,
+                Kind::Printable => length += size,
+                Kind::Numeric if size < min_run => length += size,
+                _ if length == 0 => return group,
+                _ => break,
+            }
+        }
+
+        Self::new(Kind::Printable, &bytes[..length])
+    }
+
+    /// Get the length of the numeric token at the start of a byte slice, or 0 if there is none.
+    fn token_len(bytes: &[u8], numeric: Numeric) -> usize {
+        let digits = |start: usize| {
+            bytes[start.min(bytes.len())..]
+                .iter()
+                .take_while(|&&byte| Kind::is_numeric(byte, numeric))
+                .count()
+        };
+
+        let sign = usize::from(bytes.first() == Some(&b'-'));
+        let integer = digits(sign);
+        let point = sign + integer;
+        let fraction = if bytes.get(point) == Some(&b'.') {
+            digits(point + 1)
         } else {
-            Self::invalid_span(bytes, numeric)
+            0
+        };
+
+        match (integer, fraction) {
+            (0, 0) => 0,
+            (_, 0) => point,
+            _ => point + 1 + fraction,
+        }
+    }
+
+    /// Classify suspicious grapheme clusters as invalid, for `--classify-utf8-strict`.
+    ///
+    /// See [`Span::is_suspicious`] for what is rejected. `after_base` tells whether the previous
+    /// group ended with a character that a combining mark could attach to.
+    pub(crate) fn strict(self, after_base: bool) -> Self {
+        if self.kind == Kind::Graphemes && self.span.is_suspicious(after_base) {
+            Self::new(Kind::Invalid, self.span.bytes)
+        } else {
+            self
         }
     }
 
@@ -97,14 +203,10 @@ impl Group<'_> {
         Self::new(Kind::Control, &bytes[..length])
     }
 
-    fn invalid_span(bytes: &[u8], numeric: Numeric) -> Group<'_> {
+    fn invalid_span(bytes: &[u8]) -> Group<'_> {
         let mut length = 1;
-        for (i, byte) in bytes[1..].iter().enumerate() {
-            if Kind::is_numeric(*byte, numeric)
-                || Kind::is_printable(*byte)
-                || Kind::is_control(*byte)
-                || Span::parse(&bytes[i..]).is_some()
-            {
+        for (i, byte) in bytes.iter().enumerate().skip(1) {
+            if byte.is_ascii() || Span::parse(&bytes[i..]).is_some() {
                 break;
             }
             length += 1;
@@ -114,12 +216,118 @@ impl Group<'_> {
     }
 }
 
+/// Read all bytes from a reader, calling `f` with each classified [`Group`].
+///
+/// A group that reaches the end of the read buffer may continue in the next read, so it is held
+/// back until more bytes arrive. Groups longer than the buffer (4096 bytes) are split.
+/// [`Numeric::Auto`] is resolved from the first read.
+pub(crate) fn read_groups<R, F>(reader: &mut R, numeric: &mut Numeric, mut f: F) -> io::Result<()>
+where
+    R: Read,
+    F: FnMut(Group<'_>) -> io::Result<()>,
+{
+    let mut buf = [0; 4096];
+    let mut pending = 0;
+
+    loop {
+        let size = reader.read(&mut buf[pending..])?;
+        let end = pending + size;
+        if let Numeric::Auto = numeric {
+            *numeric = Numeric::detect(&buf[..end]);
+        }
+
+        // Hold back an incomplete UTF-8 sequence until more bytes arrive.
+        let mut limit = if size == 0 {
+            end
+        } else {
+            end - grapheme::incomplete_len(&buf[..end])
+        };
+
+        let mut start = 0;
+        while start < limit {
+            let group = Group::gather(&buf[start..limit], *numeric);
+            let stop = start + group.span.bytes.len();
+
+            // A group that reaches the end of the buffer may continue in the next read.
+            if stop == limit && size > 0 && start > 0 {
+                limit = start;
+                break;
+            }
+
+            f(group)?;
+            start = stop;
+        }
+
+        if size == 0 {
+            break;
+        }
+        buf.copy_within(limit..end, 0);
+        pending = end - limit;
+    }
+
+    Ok(())
+}
+
 impl Kind {
+    /// All kinds, in the order they are reported.
+    pub(crate) const ALL: [Kind; 5] = [
+        Kind::Printable,
+        Kind::Numeric,
+        Kind::Control,
+        Kind::Graphemes,
+        Kind::Invalid,
+    ];
+
+    /// Get the position of this kind in [`Kind::ALL`].
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Self::Printable => 0,
+            Self::Numeric => 1,
+            Self::Control => 2,
+            Self::Graphemes => 3,
+            Self::Invalid => 4,
+        }
+    }
+
+    /// Classify a single byte, without considering multibyte UTF-8 sequences.
+    ///
+    /// Classes are checked in order of precedence, matching [`Group::gather`]: numeric, then
+    /// printable, then control. All other bytes are [`Kind::Invalid`], and never
+    /// [`Kind::Graphemes`].
+    pub(crate) fn classify(byte: u8, numeric: Numeric) -> Kind {
+        if Self::is_numeric(byte, numeric) {
+            Kind::Numeric
+        } else if Self::is_printable(byte) {
+            Kind::Printable
+        } else if Self::is_control(byte) {
+            Kind::Control
+        } else {
+            Kind::Invalid
+        }
+    }
+
+    /// Get the stable name of this kind, used in records output.
+    ///
+    /// One of `numeric`, `printable`, `control`, `graphemes`, or `invalid`.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Numeric => "numeric",
+            Self::Printable => "printable",
+            Self::Control => "control",
+            Self::Graphemes => "graphemes",
+            Self::Invalid => "invalid",
+        }
+    }
+
     fn is_numeric(byte: u8, numeric: Numeric) -> bool {
         match numeric {
-            Numeric::Octal => (b'0'..b'7').contains(&byte),
-            Numeric::Decimal => byte.is_ascii_digit(),
+            Numeric::None => false,
+            Numeric::Binary => matches!(byte, b'0' | b'1'),
+            Numeric::Octal => (b'0'..=b'7').contains(&byte),
+            // Auto detection falls back to decimal until it is resolved.
+            Numeric::Decimal | Numeric::Auto => byte.is_ascii_digit(),
             Numeric::Hexadecimal => byte.is_ascii_hexdigit(),
+            Numeric::HexDigitsOnly => byte.is_ascii_digit(),
         }
     }
 
@@ -131,3 +339,212 @@ impl Kind {
         byte.is_ascii_control()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert!(Kind::classify(b'7', Numeric::Decimal) == Kind::Numeric);
+        assert!(Kind::classify(b'a', Numeric::Decimal) == Kind::Printable);
+        assert!(Kind::classify(b'a', Numeric::Hexadecimal) == Kind::Numeric);
+        assert!(Kind::classify(b' ', Numeric::Hexadecimal) == Kind::Printable);
+        assert!(Kind::classify(b'7', Numeric::HexDigitsOnly) == Kind::Numeric);
+        assert!(Kind::classify(b'a', Numeric::HexDigitsOnly) == Kind::Printable);
+        assert!(Kind::classify(b'F', Numeric::HexDigitsOnly) == Kind::Printable);
+        assert!(Kind::classify(b'0', Numeric::Binary) == Kind::Numeric);
+        assert!(Kind::classify(b'1', Numeric::Binary) == Kind::Numeric);
+        assert!(Kind::classify(b'2', Numeric::Binary) == Kind::Printable);
+        assert!(Kind::classify(b'7', Numeric::Octal) == Kind::Numeric);
+        assert!(Kind::classify(b'8', Numeric::Octal) == Kind::Printable);
+        assert!(Kind::classify(b'\n', Numeric::Decimal) == Kind::Control);
+        assert!(Kind::classify(0x7f, Numeric::Decimal) == Kind::Control);
+        assert!(Kind::classify(0xe2, Numeric::Decimal) == Kind::Invalid);
+    }
+
+    #[test]
+    fn test_numeric_binary() {
+        let group = Group::gather(b"0110 1021", Numeric::Binary);
+        assert!(group.kind == Kind::Numeric);
+        assert_eq!(group.span.bytes, b"0110");
+
+        let group = Group::gather(b"21", Numeric::Binary);
+        assert!(group.kind == Kind::Printable);
+        assert_eq!(group.span.bytes, b"2");
+    }
+
+    #[test]
+    fn test_numeric_none() {
+        assert!(Group::gather(b"5", Numeric::Decimal).kind == Kind::Numeric);
+        assert!(Group::gather(b"5", Numeric::None).kind == Kind::Printable);
+
+        let group = Group::gather(b"abc123def", Numeric::None);
+        assert!(group.kind == Kind::Printable);
+        assert_eq!(group.span.bytes, b"abc123def");
+    }
+
+    fn spans(mut bytes: &[u8], numeric: Numeric) -> Vec<(&'static str, &[u8])> {
+        let mut spans = Vec::new();
+        while !bytes.is_empty() {
+            let group = Group::gather(bytes, numeric);
+            let (kind, len) = (group.kind.name(), group.span.bytes.len());
+            spans.push((kind, &bytes[..len]));
+            bytes = &bytes[len..];
+        }
+
+        spans
+    }
+
+    #[test]
+    fn test_numeric_hex_words() {
+        assert_eq!(
+            spans(b"cafebabe xyz", Numeric::Hexadecimal),
+            [("numeric", &b"cafebabe"[..]), ("printable", b" xyz"),]
+        );
+        assert_eq!(
+            spans(b"package", Numeric::Hexadecimal),
+            [
+                ("printable", &b"p"[..]),
+                ("numeric", b"ac"),
+                ("printable", b"k"),
+                ("numeric", b"a"),
+                ("printable", b"g"),
+                ("numeric", b"e"),
+            ]
+        );
+        assert_eq!(
+            spans(b"Deadbeef0x", Numeric::Hexadecimal),
+            [("numeric", &b"Deadbeef0"[..]), ("printable", b"x")]
+        );
+
+        // Hex digits only keeps the letters in printable spans.
+        assert_eq!(
+            spans(b"cafe42xyz", Numeric::HexDigitsOnly),
+            [
+                ("printable", &b"cafe"[..]),
+                ("numeric", b"42"),
+                ("printable", b"xyz"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid() {
+        let group = Group::gather(b"\xff\x80a", Numeric::Decimal);
+        assert!(group.kind == Kind::Invalid);
+        assert_eq!(group.span.bytes, b"\xff\x80");
+
+        // Invalid bytes stop at the start of a grapheme cluster.
+        let group = Group::gather(b"\xff\xe2\x82\xac", Numeric::Decimal);
+        assert_eq!(group.span.bytes, b"\xff");
+    }
+
+    #[test]
+    fn test_gather_min_run() {
+        let gather = |bytes, min_run| {
+            let group = Group::gather_min_run(bytes, Numeric::Decimal, min_run, Group::gather);
+            (group.kind.name(), group.span.bytes)
+        };
+
+        assert_eq!(gather(b"abc5def", 2), ("printable", &b"abc5def"[..]));
+        assert_eq!(gather(b"5def", 2), ("printable", &b"5def"[..]));
+        assert_eq!(gather(b"abc5", 2), ("printable", &b"abc5"[..]));
+        assert_eq!(gather(b"abc5def", 1), ("printable", &b"abc"[..]));
+
+        // Long enough runs are still numeric, and end the printable group.
+        assert_eq!(gather(b"v1 is 42!", 2), ("printable", &b"v1 is "[..]));
+        assert_eq!(gather(b"42!", 2), ("numeric", &b"42"[..]));
+        assert_eq!(gather(b"a1\n", 2), ("printable", &b"a1"[..]));
+        assert_eq!(gather(b"\n1", 2), ("control", &b"\n"[..]));
+
+        // Tokens count their sign and decimal point.
+        let group = Group::gather_min_run(b"x -1", Numeric::Decimal, 3, Group::gather_tokens);
+        assert_eq!(group.span.bytes, b"x -1");
+        let group = Group::gather_min_run(b"x -1.5", Numeric::Decimal, 3, Group::gather_tokens);
+        assert_eq!(group.span.bytes, b"x ");
+    }
+
+    #[test]
+    fn test_strict() {
+        let group = Group::gather("\u{fffe}a".as_bytes(), Numeric::Decimal).strict(true);
+        assert!(group.kind == Kind::Invalid);
+        assert_eq!(group.span.bytes, "\u{fffe}".as_bytes());
+
+        let accent = "\u{301}".as_bytes();
+        assert!(Group::gather(accent, Numeric::Decimal).strict(false).kind == Kind::Invalid);
+        assert!(Group::gather(accent, Numeric::Decimal).strict(true).kind == Kind::Graphemes);
+        assert!(Group::gather(b"abc", Numeric::Decimal).strict(false).kind == Kind::Printable);
+        assert!(Group::gather(b"\xff", Numeric::Decimal).strict(false).kind == Kind::Invalid);
+    }
+
+    // Gather a group without the ASCII fast path in `Group::gather`, by classifying the first byte
+    // and parsing grapheme clusters for anything that is not ASCII.
+    fn gather_slow(bytes: &[u8], numeric: Numeric) -> Group<'_> {
+        match Kind::classify(bytes[0], numeric) {
+            Kind::Numeric => Group::numeric_span(bytes, numeric),
+            Kind::Printable => Group::printable_span(bytes, numeric),
+            Kind::Control => Group::control_span(bytes),
+            Kind::Graphemes | Kind::Invalid => match Span::parse(bytes) {
+                Some(span) => Group {
+                    kind: Kind::Graphemes,
+                    span,
+                },
+                None => Group::invalid_span(bytes),
+            },
+        }
+    }
+
+    #[test]
+    fn test_ascii_fast_path() {
+        let inputs = [
+            (0..=255).collect(),
+            b"2024-01-01 12:34:56 INFO id=4711 path=/index.html\r\n\x7f".to_vec(),
+            "caf\u{e9} \u{1f600}\u{1f44d}\u{1f3fd}!".as_bytes().to_vec(),
+            b"\xff\xe2\x82\xac\x80a\xc3".to_vec(),
+        ];
+        for input in &inputs {
+            for numeric in [Numeric::None, Numeric::Decimal, Numeric::Hexadecimal] {
+                let mut bytes = &input[..];
+                while !bytes.is_empty() {
+                    let fast = Group::gather(bytes, numeric);
+                    let slow = gather_slow(bytes, numeric);
+                    assert!(fast.kind == slow.kind, "{bytes:x?}");
+                    assert_eq!(fast.span.bytes, slow.span.bytes);
+                    bytes = &bytes[fast.span.bytes.len()..];
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_numeric_tokens() {
+        let group = Group::gather_tokens(b"-12.5,", Numeric::Decimal);
+        assert!(group.kind == Kind::Numeric);
+        assert_eq!(group.span.bytes, b"-12.5");
+
+        let group = Group::gather_tokens(b".5 ", Numeric::Decimal);
+        assert!(group.kind == Kind::Numeric);
+        assert_eq!(group.span.bytes, b".5");
+
+        // A trailing decimal point is not part of the token.
+        let group = Group::gather_tokens(b"12.", Numeric::Decimal);
+        assert!(group.kind == Kind::Numeric);
+        assert_eq!(group.span.bytes, b"12");
+
+        // Printable groups stop before a sign or decimal point that starts a token.
+        let group = Group::gather_tokens(b"x=-1", Numeric::Decimal);
+        assert!(group.kind == Kind::Printable);
+        assert_eq!(group.span.bytes, b"x=");
+        let group = Group::gather_tokens(b"a-b.c", Numeric::Decimal);
+        assert!(group.kind == Kind::Printable);
+        assert_eq!(group.span.bytes, b"a-b.c");
+
+        // Without numeric classification, there are no tokens.
+        let group = Group::gather_tokens(b"-12.5", Numeric::None);
+        assert!(group.kind == Kind::Printable);
+        assert_eq!(group.span.bytes, b"-12.5");
+
+        assert!(Group::gather_tokens(b"\n-1", Numeric::Decimal).kind == Kind::Control);
+    }
+}
@@ -0,0 +1,354 @@
+use std::io::{self, Write};
+
+/// Colors of the 16 basic ANSI colors, from the xterm palette.
+const PALETTE: [&str; 16] = [
+    "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+    "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+];
+
+/// Class names of the 16 basic ANSI colors, without the `hd-` prefix.
+const NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// A foreground color selected by an SGR escape sequence.
+#[derive(Copy, Clone)]
+enum Fg {
+    /// One of the 16 basic ANSI colors.
+    Ansi(u8),
+
+    /// A color from the 256-color palette.
+    Xterm(u8),
+
+    /// A 24-bit color.
+    Rgb(u8, u8, u8),
+}
+
+impl Fg {
+    /// Get the CSS color.
+    fn css(self) -> String {
+        match self {
+            Self::Ansi(index) => PALETTE[usize::from(index)].to_string(),
+            Self::Xterm(index @ 0..=15) => PALETTE[usize::from(index)].to_string(),
+            Self::Xterm(index @ 16..=231) => {
+                const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+                let index = usize::from(index - 16);
+                let (r, g, b) = (LEVELS[index / 36], LEVELS[index / 6 % 6], LEVELS[index % 6]);
+
+                format!("#{r:02x}{g:02x}{b:02x}")
+            }
+            Self::Xterm(index) => {
+                let gray = 8 + (index - 232) * 10;
+
+                format!("#{gray:02x}{gray:02x}{gray:02x}")
+            }
+            Self::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+        }
+    }
+
+    /// Get the class name, if the color has one. 24-bit colors are always inline styles.
+    fn class(self) -> Option<String> {
+        match self {
+            Self::Ansi(index) => Some(format!("hd-{}", NAMES[usize::from(index)])),
+            Self::Xterm(index) => Some(format!("hd-xterm-{index}")),
+            Self::Rgb(..) => None,
+        }
+    }
+}
+
+/// Parser state for escape sequences.
+enum State {
+    /// Plain text.
+    Text,
+
+    /// After an `ESC` byte.
+    Escape,
+
+    /// In a Control Sequence Introducer (`ESC [`), collecting parameters.
+    Csi(Vec<u8>),
+
+    /// In an Operating System Command (`ESC ]`), collecting its payload.
+    Osc(Vec<u8>),
+
+    /// After an `ESC` byte in an Operating System Command, which starts the `ESC \` terminator.
+    OscEscape(Vec<u8>),
+}
+
+/// A writer that translates ANSI colored text into HTML.
+///
+/// Foreground colors and bold text become `<span>` elements, with inline `style` attributes or
+/// with class names for theming. Class names are `hd-bold` and the color name (e.g.
+/// `hd-bright-cyan`) or 256-color palette index (e.g. `hd-xterm-232`); 24-bit colors always use
+/// inline styles. OSC 8 hyperlinks become `<a>` elements. The characters `<`, `>`, and `&` are
+/// escaped, and other escape sequences are dropped.
+///
+/// The output is an HTML fragment for the inside of a `<pre>` element.
+pub(crate) struct Html<W: Write> {
+    /// The writer receiving HTML.
+    writer: W,
+
+    /// Use class names instead of inline styles.
+    classes: bool,
+
+    /// Escape sequence parser state.
+    state: State,
+
+    /// Current foreground color.
+    fg: Option<Fg>,
+
+    /// Current boldness.
+    bold: bool,
+
+    /// A `<span>` element is open.
+    span: bool,
+
+    /// HTML translated from the current write.
+    html: Vec<u8>,
+}
+
+impl<W: Write> Html<W> {
+    /// Create an HTML translator wrapping a writer.
+    pub(crate) fn new(writer: W, classes: bool) -> Self {
+        Self {
+            writer,
+            classes,
+            state: State::Text,
+            fg: None,
+            bold: false,
+            span: false,
+            html: Vec::new(),
+        }
+    }
+
+    /// Write a byte of text, opening a span for the current style if needed.
+    fn text(&mut self, byte: u8) {
+        if !self.span && (self.fg.is_some() || self.bold) {
+            let tag = self.open_tag();
+            self.html.extend_from_slice(tag.as_bytes());
+            self.span = true;
+        }
+
+        match byte {
+            b'<' => self.html.extend_from_slice(b"&lt;"),
+            b'>' => self.html.extend_from_slice(b"&gt;"),
+            b'&' => self.html.extend_from_slice(b"&amp;"),
+            byte => self.html.push(byte),
+        }
+    }
+
+    /// Get the opening tag of a span for the current style.
+    fn open_tag(&self) -> String {
+        let mut classes = Vec::new();
+        let mut styles = Vec::new();
+        if let Some(fg) = self.fg {
+            match fg.class().filter(|_| self.classes) {
+                Some(class) => classes.push(class),
+                None => styles.push(format!("color:{}", fg.css())),
+            }
+        }
+        if self.bold {
+            if self.classes {
+                classes.push("hd-bold".to_string());
+            } else {
+                styles.push("font-weight:bold".to_string());
+            }
+        }
+
+        let mut tag = "<span".to_string();
+        if !classes.is_empty() {
+            tag.push_str(&format!(" class=\"{}\"", classes.join(" ")));
+        }
+        if !styles.is_empty() {
+            tag.push_str(&format!(" style=\"{}\"", styles.join(";")));
+        }
+        tag.push('>');
+
+        tag
+    }
+
+    /// Close the open span, if any.
+    fn close_span(&mut self) {
+        if self.span {
+            self.span = false;
+            self.html.extend_from_slice(b"</span>");
+        }
+    }
+
+    /// Apply the parameters of a Select Graphic Rendition sequence (`ESC [ ... m`).
+    fn sgr(&mut self, params: &[u8]) {
+        self.close_span();
+
+        let params = String::from_utf8_lossy(params);
+        let mut params = params.split(';').map(|param| param.parse().unwrap_or(0_u8));
+        while let Some(param) = params.next() {
+            match param {
+                0 => {
+                    self.fg = None;
+                    self.bold = false;
+                }
+                1 => self.bold = true,
+                22 => self.bold = false,
+                30..=37 => self.fg = Some(Fg::Ansi(param - 30)),
+                90..=97 => self.fg = Some(Fg::Ansi(param - 90 + 8)),
+                39 => self.fg = None,
+                38 => match params.next() {
+                    Some(5) => self.fg = params.next().map(Fg::Xterm),
+                    Some(2) => {
+                        let mut next = || params.next().unwrap_or(0);
+                        self.fg = Some(Fg::Rgb(next(), next(), next()));
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+    }
+
+    /// Apply an Operating System Command. Only OSC 8 hyperlinks are supported.
+    fn osc(&mut self, payload: &[u8]) {
+        let Some(uri) = payload
+            .strip_prefix(b"8;")
+            .and_then(|link| link.splitn(2, |&b| b == b';').nth(1))
+        else {
+            return;
+        };
+
+        self.close_span();
+        if uri.is_empty() {
+            self.html.extend_from_slice(b"</a>");
+        } else {
+            let uri = String::from_utf8_lossy(uri)
+                .replace('&', "&amp;")
+                .replace('"', "&quot;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;");
+            self.html
+                .extend_from_slice(format!("<a href=\"{uri}\">").as_bytes());
+        }
+    }
+}
+
+impl<W: Write> Write for Html<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            self.state = match std::mem::replace(&mut self.state, State::Text) {
+                State::Text if byte == 0x1b => State::Escape,
+                State::Text => {
+                    self.text(byte);
+                    State::Text
+                }
+                State::Escape if byte == b'[' => State::Csi(Vec::new()),
+                State::Escape if byte == b']' => State::Osc(Vec::new()),
+                State::Escape => State::Text,
+                // A final byte ends the control sequence.
+                State::Csi(params) if (0x40..=0x7e).contains(&byte) => {
+                    if byte == b'm' {
+                        self.sgr(&params);
+                    }
+                    State::Text
+                }
+                State::Csi(mut params) => {
+                    params.push(byte);
+                    State::Csi(params)
+                }
+                State::Osc(payload) if byte == 0x1b => State::OscEscape(payload),
+                State::Osc(payload) if byte == 0x07 => {
+                    self.osc(&payload);
+                    State::Text
+                }
+                State::Osc(mut payload) => {
+                    payload.push(byte);
+                    State::Osc(payload)
+                }
+                State::OscEscape(payload) => {
+                    self.osc(&payload);
+                    State::Text
+                }
+            };
+        }
+
+        self.writer.write_all(&self.html)?;
+        self.html.clear();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn html(input: &str, classes: bool) -> String {
+        let mut output = Vec::new();
+        let mut html = Html::new(&mut output, classes);
+        // Split writes in the middle of escape sequences.
+        for chunk in input.as_bytes().chunks(3) {
+            html.write_all(chunk).unwrap();
+        }
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_html_styles() {
+        assert_eq!(
+            html("\x1b[94m0000\x1b[39m: \x1b[92m<a&b>\x1b[39m\n", false),
+            "<span style=\"color:#5c5cff\">0000</span>: \
+             <span style=\"color:#00ff00\">&lt;a&amp;b&gt;</span>\n"
+        );
+        assert_eq!(
+            html("\x1b[1m\x1b[35m\u{1f600}\x1b[22m\x1b[39m", false),
+            "<span style=\"color:#cd00cd;font-weight:bold\">\u{1f600}</span>"
+        );
+        assert_eq!(
+            html(
+                "\x1b[38;5;232m00\x1b[39m\x1b[38;2;1;2;255mff\x1b[39m",
+                false
+            ),
+            "<span style=\"color:#080808\">00</span><span style=\"color:#0102ff\">ff</span>"
+        );
+    }
+
+    #[test]
+    fn test_html_classes() {
+        assert_eq!(
+            html("\x1b[96m12\x1b[39m\x1b[1m\x1b[32m\u{e9}\x1b[0m", true),
+            "<span class=\"hd-bright-cyan\">12</span>\
+             <span class=\"hd-green hd-bold\">\u{e9}</span>"
+        );
+        assert_eq!(
+            html("\x1b[38;5;255mff\x1b[38;2;0;0;0m00\x1b[39m", true),
+            "<span class=\"hd-xterm-255\">ff</span><span style=\"color:#000000\">00</span>"
+        );
+    }
+
+    #[test]
+    fn test_html_hyperlinks() {
+        assert_eq!(
+            html(
+                "[\x1b]8;;file:///a&b\x1b\\\x1b[33mname\x1b[39m\x1b]8;;\x1b\\]",
+                false
+            ),
+            "[<a href=\"file:///a&amp;b\"><span style=\"color:#cdcd00\">name</span></a>]"
+        );
+    }
+}
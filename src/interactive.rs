@@ -0,0 +1,322 @@
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use std::io::{self, IsTerminal as _, Stdout, Write as _};
+
+/// Size of the terminal when it cannot be queried.
+const DEFAULT_SIZE: (usize, usize) = (24, 80);
+
+/// A key pressed in the interactive view.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum Key {
+    /// A printable character.
+    Char(char),
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Enter,
+    Backspace,
+    Escape,
+
+    /// Ctrl-C, which is read as a key because signals are disabled in raw mode.
+    Interrupt,
+
+    /// The terminal was resized, so the screen needs to be redrawn.
+    Resize,
+}
+
+/// Get the key for a key event. Releases and unknown keys are ignored.
+fn key_of(event: KeyEvent) -> Option<Key> {
+    if event.kind == KeyEventKind::Release {
+        return None;
+    }
+
+    let key = match event.code {
+        KeyCode::Char('c') if event.modifiers.contains(KeyModifiers::CONTROL) => Key::Interrupt,
+        KeyCode::Char(_)
+            if event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            return None;
+        }
+        KeyCode::Char(char) => Key::Char(char),
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Esc => Key::Escape,
+        _ => return None,
+    };
+
+    Some(key)
+}
+
+/// The terminal in raw mode, drawing on the alternate screen.
+///
+/// Dropping the terminal restores its mode and the main screen, so the shell is left as it was
+/// found, even when browsing fails.
+pub(crate) struct Terminal {
+    /// Standard output, which must be the terminal.
+    stdout: Stdout,
+}
+
+impl Terminal {
+    /// Put the terminal in raw mode and switch to the alternate screen.
+    ///
+    /// In raw mode, keys are read without waiting for a newline or echoing them, and Ctrl-C is
+    /// read as a key so the terminal is always restored.
+    pub(crate) fn open() -> io::Result<Self> {
+        let stdout = io::stdout();
+        if !stdout.is_terminal() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Standard output is not a terminal",
+            ));
+        }
+
+        terminal::enable_raw_mode()?;
+        let terminal = Self { stdout };
+        execute!(&terminal.stdout, EnterAlternateScreen, cursor::Hide)?;
+
+        Ok(terminal)
+    }
+
+    /// Get the number of rows and columns in the terminal.
+    pub(crate) fn size(&self) -> (usize, usize) {
+        terminal::size()
+            .ok()
+            .map(|(columns, rows)| (usize::from(rows), usize::from(columns)))
+            .filter(|&(rows, columns)| rows > 0 && columns > 0)
+            .unwrap_or(DEFAULT_SIZE)
+    }
+
+    /// Wait for the next key, or for the terminal to be resized.
+    pub(crate) fn read_key(&mut self) -> io::Result<Key> {
+        loop {
+            match event::read()? {
+                Event::Key(event) => {
+                    if let Some(key) = key_of(event) {
+                        return Ok(key);
+                    }
+                }
+                Event::Resize(..) => return Ok(Key::Resize),
+                _ => (),
+            }
+        }
+    }
+
+    /// Draw the lines of a screen, and a status line below them in reverse video.
+    ///
+    /// Lines past `height` are cut off, and the status line is cut to `columns`.
+    pub(crate) fn draw(
+        &mut self,
+        screen: &[u8],
+        height: usize,
+        columns: usize,
+        status: &str,
+    ) -> io::Result<()> {
+        let mut stdout = self.stdout.lock();
+        queue!(stdout, cursor::MoveTo(0, 0))?;
+        for line in screen.split(|&byte| byte == b'\n').take(height) {
+            stdout.write_all(line)?;
+            queue!(
+                stdout,
+                SetAttribute(Attribute::Reset),
+                Clear(ClearType::UntilNewLine),
+                cursor::MoveToNextLine(1),
+            )?;
+        }
+        let status: String = status.chars().take(columns).collect();
+        queue!(
+            stdout,
+            Clear(ClearType::FromCursorDown),
+            cursor::MoveTo(0, screen_row(height)),
+            SetAttribute(Attribute::Reverse),
+            Print(status),
+            SetAttribute(Attribute::Reset),
+        )?;
+
+        stdout.flush()
+    }
+
+    /// Read a line of text on a row of the screen, showing `label` before it.
+    ///
+    /// Returns `None` when the prompt is cancelled with Escape or Ctrl-C.
+    pub(crate) fn prompt(&mut self, row: usize, label: &str) -> io::Result<Option<String>> {
+        let mut input = String::new();
+        let input = loop {
+            execute!(
+                self.stdout,
+                cursor::MoveTo(0, screen_row(row)),
+                Clear(ClearType::CurrentLine),
+                Print(format_args!("{label}{input}")),
+                cursor::Show,
+            )?;
+            match self.read_key()? {
+                Key::Enter => break Some(input),
+                Key::Escape | Key::Interrupt => break None,
+                Key::Backspace => {
+                    input.pop();
+                }
+                Key::Char(char) => input.push(char),
+                _ => (),
+            }
+        };
+        execute!(self.stdout, cursor::Hide)?;
+
+        Ok(input)
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = execute!(self.stdout, cursor::Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Convert a row index to a terminal row, which is limited to `u16`.
+fn screen_row(row: usize) -> u16 {
+    u16::try_from(row).unwrap_or(u16::MAX)
+}
+
+/// The rows of an input that are visible in the interactive view.
+pub(crate) struct View {
+    /// Number of bytes in the input.
+    len: u64,
+
+    /// Number of bytes per row.
+    width: u64,
+
+    /// Number of rows on the screen.
+    height: u64,
+
+    /// Index of the first visible row.
+    top: u64,
+}
+
+impl View {
+    /// Create a view at the start of an input with `len` bytes.
+    pub(crate) fn new(len: u64) -> Self {
+        Self {
+            len,
+            width: 1,
+            height: 1,
+            top: 0,
+        }
+    }
+
+    /// Change the row width and screen height, keeping the first visible byte on screen.
+    pub(crate) fn resize(&mut self, width: usize, height: usize) {
+        let offset = self.offset();
+        self.width = width.max(1) as u64;
+        self.height = height.max(1) as u64;
+        self.go_to(offset);
+    }
+
+    /// Get the offset of the first visible byte.
+    pub(crate) fn offset(&self) -> u64 {
+        self.top * self.width
+    }
+
+    /// Get the number of visible bytes.
+    pub(crate) fn size(&self) -> u64 {
+        let size = self.len.saturating_sub(self.offset());
+
+        size.min(self.height * self.width)
+    }
+
+    /// Get the number of rows on the screen.
+    pub(crate) fn height(&self) -> i64 {
+        self.height as i64
+    }
+
+    /// Scroll by a number of rows, up when negative. The last row is never scrolled above the
+    /// bottom of the screen.
+    pub(crate) fn scroll(&mut self, rows: i64) {
+        let top = self.top.saturating_add_signed(rows);
+        self.top = top.min(self.last_top());
+    }
+
+    /// Scroll to the row containing an offset, or as close to it as possible.
+    pub(crate) fn go_to(&mut self, offset: u64) {
+        self.top = (offset / self.width).min(self.last_top());
+    }
+
+    /// Get the index of the first visible row when scrolled to the end.
+    fn last_top(&self) -> u64 {
+        self.len.div_ceil(self.width).saturating_sub(self.height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_of() {
+        let key = |code, modifiers| key_of(KeyEvent::new(code, modifiers));
+        assert_eq!(
+            key(KeyCode::Char('q'), KeyModifiers::NONE),
+            Some(Key::Char('q'))
+        );
+        assert_eq!(
+            key(KeyCode::Char('G'), KeyModifiers::SHIFT),
+            Some(Key::Char('G'))
+        );
+        assert_eq!(
+            key(KeyCode::PageDown, KeyModifiers::NONE),
+            Some(Key::PageDown)
+        );
+        assert_eq!(
+            key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Some(Key::Interrupt)
+        );
+
+        // Other control keys, unknown keys, and releases are ignored.
+        assert_eq!(key(KeyCode::Char('a'), KeyModifiers::CONTROL), None);
+        assert_eq!(key(KeyCode::F(1), KeyModifiers::NONE), None);
+        let mut release = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+        release.kind = KeyEventKind::Release;
+        assert_eq!(key_of(release), None);
+    }
+
+    #[test]
+    fn test_view() {
+        let mut view = View::new(100);
+        view.resize(16, 4);
+        assert_eq!((view.offset(), view.size()), (0, 64));
+
+        // Scrolling stops with the last row at the bottom of the screen.
+        view.scroll(10);
+        assert_eq!((view.offset(), view.size()), (48, 52));
+        view.scroll(-2);
+        assert_eq!(view.offset(), 16);
+        view.scroll(-10);
+        assert_eq!(view.offset(), 0);
+
+        view.go_to(40);
+        assert_eq!(view.offset(), 32);
+
+        // Resizing keeps the first visible byte on screen.
+        view.resize(8, 4);
+        assert_eq!(view.offset(), 32);
+        view.resize(16, 10);
+        assert_eq!(view.offset(), 0);
+
+        // Inputs shorter than the screen do not scroll.
+        let mut view = View::new(10);
+        view.resize(16, 4);
+        view.scroll(1);
+        assert_eq!((view.offset(), view.size()), (0, 10));
+    }
+}
@@ -0,0 +1,201 @@
+use crate::rows::{read_classified_rows, Row};
+use crate::Numeric;
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+
+/// Writes rows of bytes as JSON objects instead of a dump layout.
+///
+/// Each row is an object with these fields:
+///
+/// - `offset`: Byte offset of the start of the row
+/// - `hex`: Lowercase hex digits of the row's bytes
+/// - `ascii`: The row's character table, with `.` for control and invalid bytes. Grapheme clusters
+///   are shown in the row where they start.
+/// - `kinds`: The stable [`Kind::name`](crate::group::Kind::name) of each byte in the row
+///
+/// Rows are written as JSON Lines, one compact object per line, or pretty printed as a single
+/// indented array. Both are streamed; the array is closed by [`Json::finish`].
+pub(crate) struct Json<W: Write> {
+    /// The writer receiving JSON.
+    writer: W,
+
+    /// Number of bytes per row.
+    width: usize,
+
+    /// Numeric classification for groups.
+    numeric: Numeric,
+
+    /// Pretty print a single array instead of writing JSON Lines.
+    pretty: bool,
+
+    /// Offset of the current row.
+    offset: usize,
+
+    /// Number of rows written.
+    rows: usize,
+
+    /// The current row.
+    row: Row,
+}
+
+impl<W: Write> Json<W> {
+    /// Create a JSON writer wrapping a writer, with `width` bytes per row.
+    pub(crate) fn new(writer: W, width: usize, numeric: Numeric, pretty: bool) -> Self {
+        Self {
+            writer,
+            width,
+            numeric,
+            pretty,
+            offset: 0,
+            rows: 0,
+            row: Row::default(),
+        }
+    }
+
+    /// Write rows for all bytes from a reader.
+    ///
+    /// Rows and offsets continue across multiple readers.
+    pub(crate) fn write<R>(&mut self, reader: &mut R) -> io::Result<()>
+    where
+        R: Read,
+    {
+        let mut numeric = self.numeric;
+        let mut row = std::mem::take(&mut self.row);
+        let result = read_classified_rows(reader, &mut numeric, self.width, &mut row, |row| {
+            self.write_row(row)
+        });
+        self.numeric = numeric;
+        self.row = row;
+
+        result
+    }
+
+    /// Write the last partial row, close the array when pretty printing, and flush the writer.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        let row = std::mem::take(&mut self.row);
+        if !row.is_empty() {
+            self.write_row(&row)?;
+        }
+        if self.pretty {
+            let close = if self.rows == 0 { "[]" } else { "\n]" };
+            writeln!(self.writer, "{close}")?;
+        }
+
+        self.writer.flush()
+    }
+
+    /// Write a row.
+    fn write_row(&mut self, row: &Row) -> io::Result<()> {
+        let mut hex = String::with_capacity(row.bytes.len() * 2);
+        for byte in &row.bytes {
+            let _ = write!(hex, "{byte:02x}");
+        }
+        let kinds: Vec<_> = row
+            .kinds
+            .iter()
+            .map(|kind| format!("\"{}\"", kind.name()))
+            .collect();
+
+        // Both layouts share the same field values.
+        let fields = [
+            ("offset", self.offset.to_string()),
+            ("hex", quote(&hex)),
+            ("ascii", quote(&row.ascii)),
+            (
+                "kinds",
+                format!("[{}]", kinds.join(if self.pretty { ", " } else { "," })),
+            ),
+        ];
+
+        if self.pretty {
+            let open = if self.rows == 0 { "[" } else { "," };
+            writeln!(self.writer, "{open}\n  {{")?;
+            for (i, (name, value)) in fields.iter().enumerate() {
+                let comma = if i + 1 < fields.len() { "," } else { "" };
+                writeln!(self.writer, "    \"{name}\": {value}{comma}")?;
+            }
+            write!(self.writer, "  }}")?;
+        } else {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(name, value)| format!("\"{name}\":{value}"))
+                .collect();
+            writeln!(self.writer, "{{{}}}", fields.join(","))?;
+        }
+
+        self.offset += row.bytes.len();
+        self.rows += 1;
+
+        Ok(())
+    }
+}
+
+/// Quote a string as a JSON string literal.
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            ch if ch.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", ch as u32);
+            }
+            ch => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(input: &[u8], width: usize, pretty: bool) -> String {
+        let mut output = Vec::new();
+        let mut json = Json::new(&mut output, width, Numeric::Decimal, pretty);
+        json.write(&mut &input[..]).unwrap();
+        json.finish().unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_json_lines() {
+        assert_eq!(
+            json(b"a1\n\xff\"", 3, false),
+            "\
+                {\"offset\":0,\"hex\":\"61310a\",\"ascii\":\"a1.\",\
+                \"kinds\":[\"printable\",\"numeric\",\"control\"]}\n\
+                {\"offset\":3,\"hex\":\"ff22\",\"ascii\":\".\\\"\",\
+                \"kinds\":[\"invalid\",\"printable\"]}\n"
+        );
+        assert_eq!(json(b"", 3, false), "");
+    }
+
+    #[test]
+    fn test_json_pretty() {
+        assert_eq!(
+            json("a€".as_bytes(), 16, true),
+            "\
+                [\n  {\n    \"offset\": 0,\n    \"hex\": \"61e282ac\",\n    \"ascii\": \"a€\",\n    \
+                \"kinds\": [\"printable\", \"graphemes\", \"graphemes\", \"graphemes\"]\n  }\n]\n"
+        );
+        assert_eq!(json(b"", 16, true), "[]\n");
+
+        // Pretty printing only changes whitespace.
+        let input = b"hello,world!\x00\x01\x02\xfe\xff0123456789";
+        let pretty = json(input, 8, true).replace([' ', '\n'], "");
+        let lines = json(input, 8, false);
+        let lines: Vec<_> = lines.lines().collect();
+        assert_eq!(pretty, format!("[{}]", lines.join(",")));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("plain"), "\"plain\"");
+        assert_eq!(quote("\"\\\u{7}"), "\"\\\"\\\\\\u0007\"");
+    }
+}
@@ -1,16 +1,67 @@
-use self::grapheme::Char;
+use self::config::ConfigError;
+use self::counts::Counts;
+use self::decode::{Base64, DecodeError, Decoder, Dump, DumpLayout, Hex};
+use self::decompress::{decompress, Compression};
+use self::encode::{Encoder, Encoding};
+use self::grapheme::{display_width, Char};
 use self::group::{Group, Kind};
-use colorz::{mode::set_coloring_mode_from_env, Colorize as _};
+use self::html::Html;
+use self::json::Json;
+use self::pager::{Output, Pager};
+use self::progress::Progress;
+use self::records::Records;
+use self::repeats::Repeats;
+use self::scale::ColorScale;
+use self::transform::{Transform, Transformed};
+use self::trim::TrimZeros;
+use self::tsv::Tsv;
+use self::watch::{Stamp, Watcher};
+use self::xxd::Xxd;
+use colorz::{ansi::AnsiColor, mode::set_coloring_mode_from_env, Color, Colorize as _};
 use error_iter::ErrorIter as _;
 use onlyargs::OnlyArgs as _;
 use onlyargs_derive::OnlyArgs;
 use onlyerror::Error;
+use regex_lite::Regex;
+use std::cell::Cell;
+use std::ffi::OsString;
 use std::fmt::{self, Write as _};
-use std::io::{self, Read, Write as _};
-use std::{fs::File, path::PathBuf, process::ExitCode, str::FromStr};
+use std::io::{self, BufWriter, IsTerminal as _, Read, Seek as _, SeekFrom, Write as _};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::{fs::File, process::ExitCode, str::FromStr};
+use unicode_segmentation::UnicodeSegmentation as _;
 
+mod ambiguous;
+mod config;
+mod counts;
+mod crc32;
+mod decode;
+mod decompress;
+mod device;
+mod encode;
 mod grapheme;
 mod group;
+mod html;
+#[cfg(feature = "interactive")]
+mod interactive;
+mod json;
+mod pager;
+mod preset;
+mod process;
+mod progress;
+mod records;
+mod repeats;
+mod rows;
+mod scale;
+mod seek;
+mod stride;
+mod structs;
+mod transform;
+mod trim;
+mod tsv;
+mod watch;
+mod xxd;
 
 #[derive(OnlyArgs)]
 #[footer = "Environment variables:"]
@@ -19,25 +70,556 @@ mod group;
 #[footer = ""]
 #[footer = "  - CLICOLOR_FORCE: Same as ALWAYS_COLOR"]
 #[footer = "  - FORCE_COLOR: Same as ALWAYS_COLOR"]
+#[footer = "  - HD_SKIP: Size for `--skip`, when the option is not given"]
+#[footer = "  - HD_LENGTH: Size for `--length`, when the option is not given"]
+#[footer = "  - COLUMNS: Terminal width, for shrinking rows that would wrap"]
+#[footer = "\nConfig file:"]
+#[footer = "  Default options are read from `~/.config/hd/config.toml` (`$XDG_CONFIG_HOME/hd/config.toml`,"]
+#[footer = "  or `%APPDATA%\\hd\\config.toml` on Windows), as `key = value` lines like `width = 32` or"]
+#[footer = "  `uppercase = true`, and arrays like `mark = ['0x10:0x20', '0x30:0x40']` for options that"]
+#[footer = "  can be repeated. Command line arguments and the environment variables above take"]
+#[footer = "  precedence over the config file, but cannot unset its flags. Use `--config` with an empty"]
+#[footer = "  file to ignore it."]
 struct Args {
     /// Number of bytes to print per row.
-    #[default(16)]
-    width: usize,
+    /// Give up to 8 comma-separated widths (e.g. `8,16,32`) to compare layouts: the inputs are
+    /// printed once for each width, which reads every input into memory.
+    #[default("16")]
+    width: String,
+
+    /// Detect the size of fixed-size records in the first 64 KiB of the first input, and print it
+    /// to stderr. Periodic bytes are found with autocorrelation.
+    #[long]
+    detect_stride: bool,
+
+    /// Set the width to the stride found by `--detect-stride`, when one is found.
+    #[long]
+    apply_stride: bool,
+
+    /// Print rows at the requested width even when they are wider than the terminal. By default,
+    /// the width is reduced to a multiple of the group size that fits in `$COLUMNS` when stdout is
+    /// a terminal.
+    #[long]
+    no_shrink: bool,
 
     /// Number of bytes to group within a row.
-    #[default(2)]
-    group: usize,
+    /// Also accepts `byte` (1), `word` (2), `dword` (4), or `qword` (8).
+    #[default("2")]
+    group: String,
+
+    /// Byte order of the groups in the hex column. The character table is always in input order.
+    ///  - `big`: Bytes in input order
+    ///  - `little`: Bytes reversed within each group, so groups read as little-endian numbers.
+    ///    Requires a group of 2, 4, or 8 bytes.
+    ///
+    #[long]
+    #[default("big")]
+    endian: String,
+
+    /// Pad a partial group at the end of the last row to the size of the same group in the rows
+    /// above, so its bytes line up with theirs. Little-endian groups are padded on the left, where
+    /// their missing high bytes would be.
+    #[long]
+    pad_last_group: bool,
 
     /// Numeric classification for character table.
     /// Prints bytes in cyan that match one of the following numeric classes:
+    ///  - `b`, `bin`, or `binary`: `/[01]+/`
     ///  - `o`, `oct`, or `octal`: `/[0-7]+/`
     ///  - `d`, `dec`, or `decimal`: `/[\d]+/`
-    ///  - `h`, `x`, `hex`, or `hexadecimal`: `/[a-f\d]+/i`
+    ///  - `h`, `x`, `hex`, or `hexadecimal`: `/[a-f\d]+/i`. Words mixing hex and other letters
+    ///    split into separate groups; add `--numeric-color-only` to keep them together.
+    ///  - `hex-digits-only`: `/[\d]+/`, leaving the letters `a-f` printable in text
+    ///  - `auto`: Guess octal, decimal, or hex from the numbers in the first bytes of input
+    ///  - `none`: Disable numeric classification
     ///
     #[default("decimal")]
     numeric: String,
 
-    /// A list of file paths to read.
+    /// Color numeric bytes without splitting them from surrounding printable groups.
+    #[long]
+    numeric_color_only: bool,
+
+    /// How wide to show characters with an ambiguous East Asian width, like `°` or `α`, in the
+    /// character table. Match the terminal's setting to keep the table aligned.
+    ///  - `narrow`: One column, as in most terminals
+    ///  - `wide`: Two columns, as in terminals configured for CJK text
+    ///
+    #[long]
+    #[default("narrow")]
+    east_asian_width: String,
+
+    /// Columns where numeric bytes are colored as numeric. In other columns they get the printable
+    /// color, like `ascii` to keep the hex column from changing color at every number.
+    ///  - `ascii`: Only the character table
+    ///  - `hex`: Only the hex column
+    ///  - `both`: Both columns
+    ///
+    #[long]
+    #[default("both")]
+    numeric_color: String,
+
+    /// Color digits outside of the `--numeric` radix in red, like `8` and `9` in octal, to flag
+    /// non-octal or non-binary digits in numeric text.
+    #[long]
+    color_numeric_by_radix: bool,
+
+    /// Color numbers as whole tokens, including a leading `-` or decimal point, like `-12.5`.
+    #[long]
+    numeric_tokens: bool,
+
+    /// Treat numeric runs shorter than N bytes as printable, so stray digits in text are not
+    /// colored or split from the surrounding text. With `--numeric-tokens`, a token's sign and
+    /// decimal point count toward its length.
+    #[long]
+    numeric_min_run: Option<usize>,
+
+    /// Show valid UTF-8 that is unlikely in text as invalid bytes, so anomalies stand out in red.
+    /// Malformed UTF-8 (like overlong encodings and surrogates) is always invalid. Strict mode also
+    /// rejects noncharacters (`U+FDD0..=U+FDEF` and code points ending in `FFFE` or `FFFF`), and
+    /// combining marks, zero-width joiners, and variation selectors that do not follow a printable
+    /// character.
+    #[long]
+    classify_utf8_strict: bool,
+
+    /// Input format to decode before printing.
+    ///  - `raw`: Print input bytes as-is
+    ///  - `hex`: Decode ASCII hex text, ignoring whitespace and `0x` prefixes
+    ///  - `base64`: Decode standard or URL-safe base64 text, but not a mix of both, ignoring
+    ///    whitespace
+    ///  - `dump`: Reverse an `hd` dump into bytes from its addresses and hex column
+    ///
+    #[long]
+    #[default("raw")]
+    input_format: String,
+
+    /// Decompress gzip and zstd inputs, detected by their magic bytes, before decoding and
+    /// printing. Addresses are offsets in the decompressed stream. Other inputs are read as-is.
+    /// Each format requires a build with the feature of the same name; only `gzip` is built by
+    /// default.
+    #[long]
+    decompress: bool,
+
+    /// Decompress inputs in this format, like `--decompress` with a fixed format instead of
+    /// detecting it. Inputs in other formats fail, including stdin.
+    ///  - `auto`: Detect gzip and zstd by their magic bytes, and read other inputs as-is
+    ///  - `gzip` or `gz`: Decompress every input as gzip
+    ///  - `zstd` or `zst`: Decompress every input as zstd
+    ///
+    #[long]
+    decompress_format: Option<String>,
+
+    /// XOR each byte with a repeating hex key, like `5a` or `deadbeef`, before printing. The key
+    /// lines up with input offsets, including skipped bytes, so obfuscated data reads correctly
+    /// from any starting point. Addresses are unchanged.
+    ///
+    /// When multiple transforms are given, they are applied in a fixed order: `--xor`, then
+    /// `--add`, then `--rot13`.
+    #[long]
+    xor: Option<String>,
+
+    /// Add N to each byte before printing, wrapping at 256. Negative values subtract. Applied
+    /// after `--xor` and before `--rot13`.
+    #[long]
+    add: Option<i32>,
+
+    /// Rotate ASCII letters by 13 places before printing, leaving other bytes unchanged. Applied
+    /// after `--xor` and `--add`.
+    #[long]
+    rot13: bool,
+
+    /// Output format.
+    ///  - `dump`: Pretty print rows of hex bytes and a character table
+    ///  - `hex`: Write plain hex digits with no dump layout
+    ///  - `base64`: Write standard base64 with no dump layout
+    ///  - `records`: Write one `offset kind hexbytes` line per classified group, where `kind` is
+    ///    one of `numeric`, `printable`, `control`, `graphemes`, or `invalid`
+    ///  - `json`: Write one JSON object per row (JSON Lines) with `offset`, `hex`, `ascii`, and
+    ///    `kinds` fields
+    ///  - `json-pretty`: Write the same rows as one indented JSON array
+    ///  - `html`: Write the dump as an HTML fragment, in a `<pre>` element with colored `<span>`s
+    ///  - `tsv`: Write tab-separated values for spreadsheets, with a header line and one line per
+    ///    row: the decimal offset, one hex cell per byte (empty past the end), and the ASCII table
+    ///  - `xxd`: Write the same format as `xxd`, with groups of two bytes and no colors
+    ///
+    #[long]
+    #[default("dump")]
+    output_format: String,
+
+    /// Use CSS class names instead of inline styles in `html` output, for theming.
+    /// Classes are `hd-bold` and the ANSI color name, like `hd-bright-cyan` for numeric bytes.
+    #[long]
+    html_class: bool,
+
+    /// Print how many bytes fall into each classification, and the total, instead of the dump.
+    /// Overrides `--output-format`.
+    #[long]
+    count_only: bool,
+
+    /// Report rows of `--width` bytes that repeat anywhere in the inputs, instead of the dump.
+    /// Each line is the repeat count and the offsets of one repeated row, most repeated first.
+    /// Only the first 8 offsets are listed, followed by the number of others, like `… and 4 more`.
+    /// Offsets count from the start of the first input, and continue across inputs.
+    #[long]
+    find_repeats: bool,
+
+    /// Maximum number of distinct rows tracked by `--find-repeats`, to bound memory. Rows first
+    /// seen after the limit is reached are not tracked, and the report notes where it stopped.
+    #[long]
+    #[default(1000000)]
+    repeats_limit: usize,
+
+    /// Number of bytes to skip at the start of each input.
+    /// Sizes are decimal or `0x` hex, with an optional `K`, `M`, `G`, or `T` binary suffix.
+    #[long]
+    skip: Option<String>,
+
+    /// Maximum number of bytes to print from each input.
+    #[long]
+    length: Option<String>,
+
+    /// Print the bytes at addresses in a half-open range `START:END` of each input, like
+    /// `--skip START --length END-START`. Either bound may be left out to print from the start
+    /// (`:END`) or to the end (`START:`). Cannot be combined with `--skip` or `--length`, but
+    /// replaces them when they are set by the config file, and is replaced by them when they are
+    /// set on the command line or in the environment.
+    #[long]
+    dump_range: Option<String>,
+
+    /// Start printing each input at the first match of a hex byte pattern (e.g. `ffd8ff`), after
+    /// `--skip`. Addresses and `--length` count from the match. Inputs without a match are not
+    /// printed.
+    #[long]
+    seek_to: Option<String>,
+
+    /// Stop printing each input at its last non-zero byte. The dump notes how many zeros were
+    /// trimmed.
+    #[long]
+    trim_trailing_zeros: bool,
+
+    /// Print N evenly spaced samples of each file, from the first bytes to the last, separated by
+    /// `...` lines. Each sample is `--length` bytes, or one row. Files with fewer bytes than the
+    /// samples are printed in full. Only applies to raw files that can seek; `--seek-to` and
+    /// `--trim-trailing-zeros` are not applied to samples.
+    #[long]
+    sample: Option<usize>,
+
+    /// Address of the first byte printed from each input. Defaults to the skipped size.
+    #[long]
+    base_addr: Option<String>,
+
+    /// Refuse to print files with more bytes than this, after `--skip` and `--length`.
+    /// Does not apply to stdin.
+    #[long]
+    max_file_size: Option<String>,
+
+    /// Read the memory of a process instead of files (Linux only).
+    /// Use `--skip` to set the virtual address.
+    #[long]
+    pid: Option<u32>,
+
+    /// Verify that a dump file reconstructs the input exactly, instead of printing anything.
+    /// Reports the offset of the first difference.
+    #[long]
+    verify: Option<PathBuf>,
+
+    /// Print the hex value of the byte at this offset in the first input, instead of a dump. Fails
+    /// when the offset is past the end of the input.
+    #[long]
+    byte_at: Option<String>,
+
+    /// Print a single file again from the start each time its size or modification time changes,
+    /// clearing the screen first when stdout is a terminal. Files that are rewritten or truncated
+    /// are printed in full. Only prints a dump, so it cannot be combined with other output formats,
+    /// `--seek-to`, or `--sample`. Runs until interrupted, e.g. with Ctrl-C.
+    #[long]
+    watch: bool,
+
+    /// Milliseconds between checks for changes with `--watch`. Must be at least 1.
+    #[long]
+    #[default(500)]
+    watch_interval: u64,
+
+    /// Browse a single file in a full-screen view, reading only the rows on screen. Requires a
+    /// build with the `interactive` feature. Keys:
+    ///  - `q`: Quit
+    ///  - `j`, `k`, arrows, `PgUp`, `PgDn`, `Home`, `End`: Scroll
+    ///  - `g`: Go to an offset
+    ///  - `/`: Search for text after the top row, and `n` to search again
+    ///
+    #[long]
+    interactive: bool,
+
+    /// Wrap `hex` and `base64` output at this many columns.
+    #[long]
+    wrap: Option<usize>,
+
+    /// Print hex digits in uppercase.
+    uppercase: bool,
+
+    /// Round the width up to a multiple of the group size.
+    #[long]
+    align_width: bool,
+
+    /// Number of characters to show in the character table. Defaults to the width.
+    /// Narrower tables are truncated with an ellipsis, and wider tables are padded.
+    #[long]
+    table_width: Option<usize>,
+
+    /// Insert an extra space between every N groups.
+    #[long]
+    group_gap: Option<usize>,
+
+    /// Remove the `|` separators around the character table.
+    #[long]
+    no_separator: bool,
+
+    /// Remove the space before the first group, so hex bytes start right after the address.
+    /// Spaces between groups are kept.
+    #[long]
+    no_leading_space: bool,
+
+    /// Print only the character table, without the hex column.
+    #[long]
+    no_hex: bool,
+
+    /// Show common control characters in the table as C escapes: NUL, tab, newline, and carriage
+    /// return are shown as a backslash followed by `0`, `t`, `n`, and `r`. Escapes are two columns
+    /// wide, so this requires `--no-hex`.
+    // The escapes are spelled out, because the help text turns a backslash before `t`, `n`, or `r`
+    // into a control character.
+    #[long]
+    ascii_escape: bool,
+
+    /// Space the character table into groups like the hex column, so each character lines up with
+    /// the group of its hex byte.
+    #[long]
+    mirror_groups: bool,
+
+    /// Space the character table into groups of this many bytes, independently of `--group`.
+    /// Takes precedence over `--mirror-groups`.
+    #[long]
+    table_group: Option<usize>,
+
+    /// Append a checksum of each complete group after the character table.
+    ///  - `xor`: XOR of the group's bytes
+    ///  - `sum`: Sum of the group's bytes, modulo 256
+    ///
+    #[long]
+    group_checksum: Option<String>,
+
+    /// Append a hash of each row's bytes after the character table, so rows of a printed dump can
+    /// be verified against the original one at a time. Addresses are not included. Rows hidden by
+    /// `--squeeze` are identical to the row before the `*`, so they share its hash.
+    ///  - `crc32`: CRC-32 (ISO 3309, as used by gzip and zip), as 8 hex digits
+    ///
+    #[long]
+    row_hash: Option<String>,
+
+    /// Show the number of printable and numeric bytes before each row, after the character table.
+    #[long]
+    byte_count_column: bool,
+
+    /// Repeat the file header every N rows when reading multiple files.
+    #[long]
+    repeat_header: Option<usize>,
+
+    /// Start each file's addresses at a hex offset extracted from its file name.
+    /// The offset is taken from the first capture group, or the whole match when the pattern has
+    /// no groups. An optional `0x` prefix is allowed, e.g. `_(0x[[:xdigit:]]+)\.`
+    #[long]
+    addr_from_name: Option<String>,
+
+    /// Make file headers clickable hyperlinks in terminals that support them.
+    #[long]
+    hyperlinks: bool,
+
+    /// Prefix each row with the input file name.
+    #[long]
+    with_filename: bool,
+
+    /// Only colorize control characters and invalid bytes.
+    #[long]
+    invert: bool,
+
+    /// Show invalid bytes `0xa0..=0xff` as their Latin-1 (ISO-8859-1) characters in the table.
+    /// They are still colored as invalid.
+    #[long]
+    invalid_as_latin1: bool,
+
+    /// Change the case of ASCII letters in the character table. The hex column is unchanged.
+    ///  - `upper`: Show all letters in uppercase
+    ///  - `lower`: Show all letters in lowercase
+    ///  - `swap`: Show uppercase letters in lowercase, and lowercase letters in uppercase
+    ///
+    #[long]
+    table_case: Option<String>,
+
+    /// Color hex bytes on a gradient by value, from dark to bright, instead of by classification.
+    #[long]
+    color_scale: bool,
+
+    /// Dim zero bytes in the hex column so other bytes stand out.
+    #[long]
+    collapse_zeros: bool,
+
+    /// Comma-separated columns to color by classification, like `ascii` to leave the hex column
+    /// plain. Columns are `hex` and `ascii`, or `none` for neither. Defaults to both.
+    #[long]
+    color_columns: Option<String>,
+
+    /// Highlight the bytes at addresses in a half-open range `START:END` (e.g. `0x10:0x20`) with
+    /// reversed and underlined text in both columns. Repeat to mark multiple ranges.
+    #[long]
+    mark: Vec<String>,
+
+    /// Annotate the start of each input with a struct spec, e.g. `magic:4,version:u16le,name:16`.
+    /// Each decoded field is shown after the character table, and every other field is marked.
+    /// Fields past the end of the input are not shown. Field types:
+    ///  - `N`: N raw bytes (up to 4096), shown as a string or hex
+    ///  - `u8`, `u16`, `u32`, `u64`: Unsigned integer; `i8` to `i64` are signed
+    ///  - Integers are little-endian; add a `be` suffix (e.g. `u32be`) for big-endian
+    ///
+    #[long]
+    dump_struct: Option<String>,
+
+    /// Collapse runs of at least 4096 zero bytes into a single line.
+    #[long]
+    sparse: bool,
+
+    /// Collapse runs of identical rows into a single `*` line.
+    #[long]
+    squeeze: bool,
+
+    /// Minimum number of identical rows to collapse with `--squeeze`.
+    #[long]
+    #[default(2)]
+    min_run: usize,
+
+    /// Only print rows containing part of a run of at least N printable ASCII bytes, like
+    /// `strings`. Skipped rows are collapsed into a `...` line. Rows are shown in full, so the
+    /// bytes around each string are visible.
+    #[long]
+    min_string: Option<usize>,
+
+    /// Print at most N rows of each input, followed by a `...` line when the input continues. The
+    /// line has the number of bytes left in files; streams are not read past the limit. Every
+    /// input gets the same preview.
+    #[long]
+    limit_rows_per_file: Option<usize>,
+
+    /// Prefix each row with its line number.
+    #[long]
+    line_numbers: bool,
+
+    /// Prefix each row with its position in fixed-size records, as `record:offset`.
+    /// Combine with `--no-addr` to show it instead of the address.
+    #[long]
+    record_size: Option<usize>,
+
+    /// Do not print the address column.
+    #[long]
+    no_addr: bool,
+
+    /// Print the address column and other row prefixes, like line numbers and file names, without
+    /// color, while still coloring bytes.
+    #[long]
+    no_color_addr: bool,
+
+    /// Print the address as plain hex with at least 8 digits, like `000001a0`, instead of grouping
+    /// the digits with underscores. Dumps with either form can be reversed with `--input-format
+    /// dump`.
+    #[long]
+    raw_offsets: bool,
+
+    /// Print a final line after each input with only the number of bytes dumped from it, like the
+    /// file size at the end of classic `hexdump`. With `--concat`, the line follows all inputs
+    /// and counts their bytes together.
+    #[long]
+    final_offset: bool,
+
+    /// Dim the leading digits of the address that are unchanged from the previous row, so only
+    /// the digits that changed stand out.
+    #[long]
+    dim_addr_repeats: bool,
+
+    /// Character printed after the address column and other row prefixes, instead of `:`. Must be a
+    /// single character with a display width of 1, like a space or `│`. Use the same separator
+    /// with `--input-format dump` to reverse the dump.
+    #[long]
+    offset_separator: Option<String>,
+
+    /// Opening and closing delimiters around the character table, separated by a comma, instead of
+    /// ` | , |`. They are printed as given, e.g. ` «,»`, and the closing delimiter may be empty.
+    /// The opening delimiter must have a character that is not a space or hex digit, so the dump
+    /// can be reversed with `--input-format dump` and the same delimiters.
+    #[long]
+    ascii_delimiters: Option<String>,
+
+    /// Color of the delimiters around the character table, as an ANSI color name like `red` or
+    /// `bright-black`. Delimiters are uncolored by default.
+    #[long]
+    separator_color: Option<String>,
+
+    /// Color of the `:` (or `--offset-separator`) after the address column and other row prefixes,
+    /// as an ANSI color name like `red` or `bright-black`. It is uncolored by default.
+    #[long]
+    punct_color: Option<String>,
+
+    /// Do not print a newline after the last row.
+    #[long]
+    no_final_newline: bool,
+
+    /// Show progress on stderr while reading files.
+    #[long]
+    progress: bool,
+
+    /// Print a notice on stderr naming the encoding when an input starts with a byte-order mark.
+    #[long]
+    show_bom: bool,
+
+    /// Page output through `$PAGER` when stdout is a terminal. Defaults to `less -FRX`, which keeps
+    /// colors and quits when the output fits on one screen.
+    #[long]
+    paginate: bool,
+
+    /// Dump all inputs as one concatenated stream, like the parts of a split file. Addresses and
+    /// rows continue across inputs, and the row where each later input starts is marked with its
+    /// name. Use `--quiet` to hide the markers.
+    #[long]
+    concat: bool,
+
+    /// Suppress auxiliary output, like file headers. Only dump rows are printed.
+    quiet: bool,
+
+    /// Write auxiliary output, like file headers, to stderr instead of stdout.
+    #[long]
+    meta_stderr: bool,
+
+    /// Name shown for standard input in headers and row prefixes.
+    #[long]
+    stdin_name: Option<String>,
+
+    /// Print a column ruler above each input, and report rows where the table separator is not
+    /// aligned. A layout debugging aid.
+    #[long]
+    debug_layout: bool,
+
+    /// Read default options from this config file instead of the default config file.
+    #[long]
+    config: Option<PathBuf>,
+
+    /// Apply a bundle of options for a common structure. Options given on the command line
+    /// override the preset's values, and the preset overrides the config file. `--mark` ranges
+    /// are added to the preset's marks. Presets:
+    ///  - `ipv4`: 4 bytes per row, one 32-bit word of the header per row
+    ///  - `mbr`: 16 bytes per row, marking the first and third partition table entries, so the
+    ///    four entries alternate between marked and unmarked, and the boot signature
+    ///
+    #[long]
+    preset: Option<String>,
+
+    /// A list of file paths to read. Use `-` to read from stdin.
     #[positional]
     input: Vec<PathBuf>,
 }
@@ -48,35 +630,262 @@ enum Error {
     /// CLI argument parsing error
     Cli(#[from] onlyargs::CliError),
 
-    /// Width must be in range `2 <= width < 4096`
-    Width,
+    /// Width must be in range `2..=4096`
+    #[error("Width must be in range `2..=4096`, got {0}")]
+    Width(usize),
+
+    /// Grouping must be between 1 and width
+    #[error("Group must be in range `1..={1}`, got {0}")]
+    Grouping(usize, usize),
 
-    /// Grouping must not be larger than width
-    Grouping,
+    /// Byte order swapping needs a power-of-two group
+    #[error("`--endian little` requires a group of 2, 4, or 8 bytes, got {0}")]
+    EndianGroup(usize),
+
+    /// Escapes do not line up with the hex column
+    #[error(
+        "`--ascii-escape` requires `--no-hex`, because escapes are wider than their hex bytes"
+    )]
+    EscapeWithHex,
 
     /// Unable to read file
     #[error("Unable to read file: {1:?}")]
     File(#[source] io::Error, PathBuf),
 
+    /// Invalid size
+    #[error("Invalid size: `{0}`")]
+    InvalidSize(String),
+
+    /// Invalid byte pattern
+    #[error("Invalid hex pattern: `{0}`")]
+    InvalidPattern(String),
+
+    /// Invalid mark range
+    #[error("Invalid mark range: `{0}`")]
+    InvalidMark(String),
+
+    /// Invalid dump range
+    #[error("Invalid dump range: `{0}`")]
+    InvalidDumpRange(String),
+
+    /// Watching something other than one file
+    #[error("`--watch` requires a single file")]
+    WatchInput,
+
+    /// Watching with an option that does not print a dump of the whole file
+    #[error("`--watch` cannot be combined with `{0}`")]
+    WatchOption(&'static str),
+
+    /// Watching without waiting between checks
+    #[error("`--watch-interval` must be at least 1 millisecond")]
+    WatchInterval,
+
+    /// Browsing something other than one raw file
+    #[error("`--interactive` requires a single uncompressed file, read as raw bytes and shown as a dump")]
+    InteractiveInput,
+
+    /// Browsing is not supported by this build
+    #[cfg(not(feature = "interactive"))]
+    #[error(
+        "`--interactive` is not supported by this build, which lacks the `interactive` feature"
+    )]
+    InteractiveUnsupported,
+
+    /// Unable to use the terminal for browsing
+    #[cfg(feature = "interactive")]
+    #[error("Unable to use the terminal for `--interactive`")]
+    Terminal(#[source] io::Error),
+
+    /// Dump range with skip or length
+    #[error("`--dump-range` cannot be combined with `--skip` or `--length`")]
+    DumpRangeWithWindow,
+
+    /// Invalid struct spec
+    #[error("Invalid struct spec: `{0}`")]
+    InvalidStruct(String),
+
+    /// Invalid offset separator
+    #[error("Offset separator must be a single character with a display width of 1: `{0}`")]
+    InvalidSeparator(String),
+
+    /// Invalid table delimiters
+    #[error("Invalid table delimiters: `{0}`")]
+    InvalidDelimiters(String),
+
+    /// Offset is past the end of the input
+    #[error("Offset {0:#x} is past the end of {1:?}")]
+    PastEnd(u64, String),
+
+    /// Address is too large
+    #[error("Address {0:#x} plus offset {1:#x} is too large")]
+    AddrOverflow(usize, u64),
+
+    /// Invalid XOR key
+    #[error("XOR key must be an even number of hex digits: `{0}`")]
+    InvalidXorKey(String),
+
+    /// Invalid width list
+    #[error("Invalid width: `{0}`")]
+    InvalidWidth(String),
+
+    /// Too many widths
+    #[error("Too many widths: at most {0} can be compared")]
+    TooManyWidths(usize),
+
+    /// File is larger than the maximum file size
+    #[error("File {0:?} has {1} bytes to print, exceeding `--max-file-size` {2}. Use `--length` or `--skip` to print part of it")]
+    FileTooLarge(PathBuf, u64, u64),
+
+    /// Invalid config file
+    #[error("Invalid config file: {1:?}")]
+    Config(#[source] ConfigError, PathBuf),
+
+    /// Unable to start pager
+    Pager(#[source] io::Error),
+
+    /// Unable to read process memory
+    #[error("Unable to read memory of process {1}")]
+    Process(#[source] io::Error, u32),
+
+    /// Unknown group size
+    #[error("Unknown group size: `{0}`")]
+    UnknownGroup(String),
+
+    /// Unknown compression format
+    #[error("Unknown compression format: `{0}`")]
+    UnknownCompression(String),
+
     /// Unknown numeric class
     #[error("Unknown numeric class: `{0}`")]
     UnknownNumeric(String),
 
+    /// Unknown input format
+    #[error("Unknown input format: `{0}`")]
+    UnknownInputFormat(String),
+
+    /// Invalid address pattern
+    AddrPattern(#[source] regex_lite::Error),
+
+    /// Unknown output format
+    #[error("Unknown output format: `{0}`")]
+    UnknownOutputFormat(String),
+
+    /// Unknown group checksum
+    #[error("Unknown group checksum: `{0}`")]
+    UnknownChecksum(String),
+
+    /// Unknown row hash
+    #[error("Unknown row hash: `{0}`")]
+    UnknownRowHash(String),
+
+    /// Unknown table case
+    #[error("Unknown table case: `{0}`")]
+    UnknownTableCase(String),
+
+    /// Unknown numeric color column
+    #[error("Unknown numeric color column: `{0}`. Expected `ascii`, `hex`, or `both`")]
+    UnknownNumericColor(String),
+
+    /// Unknown byte order
+    #[error("Unknown byte order: `{0}`. Expected `big` or `little`")]
+    UnknownEndian(String),
+
+    /// Unknown East Asian width
+    #[error("Unknown East Asian width: `{0}`. Expected `narrow` or `wide`")]
+    UnknownEastAsianWidth(String),
+
+    /// Unknown color column
+    #[error("Unknown color column: `{0}`")]
+    UnknownColorColumn(String),
+
+    /// Unknown preset
+    #[error("Unknown preset: `{0}`")]
+    UnknownPreset(String),
+
+    /// Unknown color name
+    #[error("Unknown color: `{0}`. Expected an ANSI color name, like `red` or `bright-black`")]
+    UnknownColor(String),
+
+    /// Dump does not match the input
+    #[error("Dump differs from input at offset {0}: dump has {1}, input has {2}")]
+    Mismatch(String, String, String),
+
+    /// Text input cannot be decoded
+    #[error("{0}")]
+    Decode(DecodeError),
+
     /// I/O error
-    Io(#[from] io::Error),
+    Io(#[source] io::Error),
 
     /// String formatting error
     Fmt(#[from] fmt::Error),
 }
 
+impl From<io::Error> for Error {
+    /// Decoding errors are read from decoders as I/O errors, and are unwrapped so they are reported
+    /// on their own.
+    fn from(err: io::Error) -> Self {
+        if err.get_ref().is_some_and(|inner| inner.is::<DecodeError>()) {
+            let inner = err.into_inner().unwrap();
+
+            return Error::Decode(*inner.downcast().unwrap());
+        }
+
+        Error::Io(err)
+    }
+}
+
 impl Error {
+    /// Check if the error was caused by the reader of the output closing the pipe, e.g. `hd | head`.
+    ///
+    /// This is not a failure; there is just nobody left to write to.
+    fn is_broken_pipe(&self) -> bool {
+        matches!(self, Error::Io(err) if err.kind() == io::ErrorKind::BrokenPipe)
+    }
+
     /// Check if the error was caused by CLI inputs.
     fn is_cli(&self) -> bool {
         use Error::*;
 
         matches!(
             self,
-            Cli(_) | Width | Grouping | File(_, _) | UnknownNumeric(_)
+            Cli(_)
+                | Width(_)
+                | Grouping(_, _)
+                | EndianGroup(_)
+                | EscapeWithHex
+                | File(_, _)
+                | InvalidSize(_)
+                | InvalidPattern(_)
+                | InvalidMark(_)
+                | InvalidDumpRange(_)
+                | DumpRangeWithWindow
+                | WatchInput
+                | WatchOption(_)
+                | WatchInterval
+                | InteractiveInput
+                | InvalidStruct(_)
+                | InvalidSeparator(_)
+                | InvalidDelimiters(_)
+                | AddrOverflow(_, _)
+                | InvalidXorKey(_)
+                | InvalidWidth(_)
+                | TooManyWidths(_)
+                | UnknownGroup(_)
+                | UnknownCompression(_)
+                | UnknownNumeric(_)
+                | UnknownInputFormat(_)
+                | UnknownOutputFormat(_)
+                | UnknownChecksum(_)
+                | UnknownRowHash(_)
+                | UnknownTableCase(_)
+                | UnknownColorColumn(_)
+                | UnknownNumericColor(_)
+                | UnknownEastAsianWidth(_)
+                | UnknownEndian(_)
+                | UnknownColor(_)
+                | UnknownPreset(_)
+                | AddrPattern(_)
         )
     }
 }
@@ -86,14 +895,23 @@ fn main() -> ExitCode {
 
     match run() {
         Ok(()) => ExitCode::SUCCESS,
+        Err(error) if error.is_broken_pipe() => ExitCode::SUCCESS,
         Err(error) => {
             if error.is_cli() {
                 let _ = writeln!(io::stderr(), "{}", Args::HELP);
             }
 
-            let _ = writeln!(io::stderr(), "{}: {error}", "Error".bright_red());
+            let _ = writeln!(
+                io::stderr(),
+                "{}: {error}",
+                stderr_label("Error", AnsiColor::BrightRed)
+            );
             for source in error.sources().skip(1) {
-                let _ = writeln!(io::stderr(), "  {}: {source}", "Caused by".bright_yellow());
+                let _ = writeln!(
+                    io::stderr(),
+                    "  {}: {source}",
+                    stderr_label("Caused by", AnsiColor::BrightYellow)
+                );
             }
 
             ExitCode::FAILURE
@@ -101,249 +919,3989 @@ fn main() -> ExitCode {
     }
 }
 
-fn run() -> Result<(), Error> {
-    let args: Args = onlyargs::parse()?;
-    let width = args.width;
-    let group = args.group;
-    let numeric = args.numeric.parse()?;
-    let mut printer = Printer::new(width, group, numeric)?;
+/// Parse command line arguments, over the default options from the config file.
+///
+/// The command line is parsed alone first to find `--config`. Then the config file options are
+/// parsed before the command line, so the same options on the command line replace them. A
+/// missing default config file is ignored, but a missing `--config` file is an error.
+///
+/// `HD_SKIP` and `HD_LENGTH` replace the config file's `skip` and `length`, but not the command
+/// line's.
+fn parse_args() -> Result<Args, Error> {
+    let cli: Vec<OsString> = std::env::args_os().skip(1).collect();
+    let args = Args::parse(cli.clone())?;
+    let sizes_on_cli = [args.skip.is_some(), args.length.is_some()];
+    let range_on_cli = args.dump_range.is_some();
+    if range_on_cli && sizes_on_cli.contains(&true) {
+        return Err(Error::DumpRangeWithWindow);
+    }
+    let config = match args.config.clone() {
+        Some(path) => match std::fs::read_to_string(&path) {
+            Ok(config) => Some((config, path)),
+            Err(err) => return Err(Error::File(err, path)),
+        },
+        None => config::default_path()
+            .and_then(|path| Some((std::fs::read_to_string(&path).ok()?, path))),
+    };
+    let mut defaults = match config {
+        Some((config, path)) => config::parse(&config).map_err(|err| Error::Config(err, path))?,
+        None => Vec::new(),
+    };
+    let args = if defaults.is_empty() {
+        args
+    } else {
+        Args::parse(defaults.iter().chain(&cli).cloned().collect())?
+    };
+
+    // A preset is expanded between the config file and the command line, so later options win.
+    let mut args = match args.preset {
+        Some(name) => {
+            defaults.extend(preset::args(&name).ok_or(Error::UnknownPreset(name))?);
+            defaults.extend(cli);
+            Args::parse(defaults)?
+        }
+        None => args,
+    };
 
-    if args.input.is_empty() {
-        // Read from stdin.
-        printer.pretty_hex(&mut io::stdin())?;
+    // The environment is between the config file and the command line. `--dump-range` and the
+    // sizes replace each other, so a window from a later source replaces one from an earlier
+    // source. They only conflict when they come from the same source.
+    if range_on_cli {
+        args.skip = None;
+        args.length = None;
     } else {
-        // Read file paths.
-        let show_header = args.input.len() > 1;
-        for path in args.input.into_iter() {
-            if show_header && writeln!(io::stdout(), "\n[{}]", path.display().yellow()).is_err() {
-                std::process::exit(1);
+        let sizes = [(&mut args.skip, "HD_SKIP"), (&mut args.length, "HD_LENGTH")];
+        let mut replaced = false;
+        for ((size, var), on_cli) in sizes.into_iter().zip(sizes_on_cli) {
+            let value = std::env::var(var).ok().filter(|value| !value.is_empty());
+            if on_cli {
+                replaced = true;
+            } else if value.is_some() {
+                *size = value;
+                replaced = true;
             }
-            let mut file = File::open(&path).map_err(|err| Error::File(err, path.to_path_buf()))?;
-            printer.pretty_hex(&mut file)?;
+        }
+        if replaced {
+            args.dump_range = None;
         }
     }
 
-    Ok(())
+    Ok(args)
 }
 
-/// Numeric context for byte classification.
-#[derive(Copy, Clone)]
-enum Numeric {
-    Octal,
-    Decimal,
-    Hexadecimal,
-}
+fn run() -> Result<(), Error> {
+    let args = parse_args()?;
+    let mut widths = parse_widths(&args.width)?;
+    let group = parse_group(&args.group)?;
+    let numeric = args.numeric.parse()?;
+    let input_format: InputFormat = args.input_format.parse()?;
+    let compression: Option<Compression> = match args.decompress_format.as_deref() {
+        Some(format) => Some(format.parse()?),
+        None => args.decompress.then_some(Compression::Auto),
+    };
+    let output_format: OutputFormat = args.output_format.parse()?;
+    // HTML is translated from colored output, wherever it is written.
+    let html = matches!(output_format, OutputFormat::Html);
+    if html {
+        colorz::mode::set_coloring_mode(colorz::mode::Mode::Always);
+    }
+    let window = match args.dump_range.as_deref() {
+        Some(_) if args.skip.is_some() || args.length.is_some() => {
+            return Err(Error::DumpRangeWithWindow);
+        }
+        Some(range) => parse_dump_range(range)?,
+        None => Window {
+            skip: args
+                .skip
+                .as_deref()
+                .map(parse_size)
+                .transpose()?
+                .unwrap_or(0),
+            length: args.length.as_deref().map(parse_size).transpose()?,
+        },
+    };
+    let base_addr = args.base_addr.as_deref().map(parse_size).transpose()?;
+    let max_file_size = args.max_file_size.as_deref().map(parse_size).transpose()?;
+    let seek_to = args.seek_to.as_deref().map(parse_pattern).transpose()?;
+    let sample = args.sample.filter(|&count| count > 0);
+    let mut transforms = Vec::new();
+    if let Some(key) = args.xor {
+        transforms.push(Transform::Xor(
+            transform::parse_key(&key).ok_or(Error::InvalidXorKey(key))?,
+        ));
+    }
+    if let Some(value) = args.add {
+        transforms.push(Transform::Add(value.rem_euclid(256) as u8));
+    }
+    if args.rot13 {
+        transforms.push(Transform::Rot13);
+    }
+    let offset_separator = args.offset_separator.map(parse_separator).transpose()?;
+    let table_delimiters = args.ascii_delimiters.map(parse_delimiters).transpose()?;
+    let layout = DumpLayout {
+        separator: offset_separator.clone(),
+        delimiter: table_delimiters.as_ref().map(|(open, _)| open.clone()),
+    };
+    let paths = if let Some(pid) = args.pid {
+        vec![PathBuf::from(process::path(pid))]
+    } else if args.input.is_empty() {
+        vec![PathBuf::from(STDIN_PATH)]
+    } else {
+        args.input
+    };
+    let is_stdin = |path: &Path| path == Path::new(STDIN_PATH);
+    let stdin_name = args.stdin_name.as_deref().unwrap_or(STDIN_NAME);
 
-impl FromStr for Numeric {
-    type Err = Error;
+    // Stdin cannot be read twice, so its sample is replayed before the rest of it.
+    let mut stdin_sample = Vec::new();
+    if args.detect_stride || args.apply_stride {
+        let mut sample = Vec::new();
+        if is_stdin(&paths[0]) {
+            io::stdin()
+                .take(stride::SAMPLE_SIZE)
+                .read_to_end(&mut stdin_sample)?;
+            sample.clone_from(&stdin_sample);
+        } else {
+            let path = &paths[0];
+            let mut file = File::open(path).map_err(|err| Error::File(err, path.clone()))?;
+            let _ = file.seek(SeekFrom::Start(window.skip));
+            file.take(stride::SAMPLE_SIZE).read_to_end(&mut sample)?;
+        }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "o" | "oct" | "octal" => Ok(Self::Octal),
-            "d" | "dec" | "decimal" => Ok(Self::Decimal),
-            "h" | "x" | "hex" | "hexadecimal" => Ok(Self::Hexadecimal),
-            _ => Err(Error::UnknownNumeric(s.to_string())),
+        let stride = stride::detect(&sample);
+        match stride {
+            Some(stride) => writeln!(io::stderr(), "Detected stride: {stride} bytes")?,
+            None => writeln!(io::stderr(), "No stride detected")?,
+        }
+        if let Some(stride) = stride.filter(|_| args.apply_stride) {
+            widths = vec![stride];
         }
     }
-}
-
-/// Row printer. Pretty prints byte slices one row at a time.
-struct Printer {
-    /// Number of bytes per row.
-    width: usize,
+    let names: Vec<_> = paths
+        .iter()
+        .map(|path| {
+            if is_stdin(path) {
+                stdin_name.to_string()
+            } else {
+                path.display().to_string()
+            }
+        })
+        .collect();
+    let addr_pattern = args
+        .addr_from_name
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(Error::AddrPattern)?;
+    let options = Options {
+        uppercase: args.uppercase,
+        numeric_color_only: args.numeric_color_only,
+        color_numeric_by_radix: args.color_numeric_by_radix,
+        numeric_tokens: args.numeric_tokens,
+        numeric_min_run: args.numeric_min_run.filter(|&run| run > 1),
+        classify_utf8_strict: args.classify_utf8_strict,
+        align_width: args.align_width,
+        table_width: args.table_width.filter(|&width| width > 0),
+        group_gap: args.group_gap.filter(|&gap| gap > 0),
+        no_separator: args.no_separator,
+        no_leading_space: args.no_leading_space,
+        no_hex: args.no_hex,
+        ascii_escape: args.ascii_escape,
+        mirror_groups: args.mirror_groups,
+        endian: args.endian.parse()?,
+        pad_last_group: args.pad_last_group,
+        table_group: args.table_group.filter(|&group| group > 0),
+        group_checksum: args.group_checksum.as_deref().map(str::parse).transpose()?,
+        row_hash: args.row_hash.as_deref().map(str::parse).transpose()?,
+        byte_count_column: args.byte_count_column,
+        repeat_header: args.repeat_header.filter(|&rows| rows > 0),
+        with_filename: args.with_filename.then(|| {
+            let widths = names.iter().map(|name| unicode_display_width::width(name));
+            widths.max().unwrap_or_default() as usize
+        }),
+        invert: args.invert,
+        invalid_as_latin1: args.invalid_as_latin1,
+        table_case: args.table_case.as_deref().map(str::parse).transpose()?,
+        color_scale: args.color_scale.then(|| {
+            if html {
+                ColorScale::Rgb
+            } else {
+                ColorScale::detect()
+            }
+        }),
+        collapse_zeros: args.collapse_zeros,
+        numeric_color: parse_numeric_color(&args.numeric_color)?,
+        east_asian_width: args.east_asian_width.parse()?,
+        color_columns: args
+            .color_columns
+            .as_deref()
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default(),
+        marks: args
+            .mark
+            .iter()
+            .map(|mark| parse_mark(mark))
+            .collect::<Result<_, _>>()?,
+        fields: match &args.dump_struct {
+            Some(spec) => structs::parse(spec).ok_or_else(|| Error::InvalidStruct(spec.clone()))?,
+            None => Vec::new(),
+        },
+        sparse: args.sparse,
+        squeeze: args.squeeze.then_some(args.min_run.max(2)),
+        min_string: args.min_string.filter(|&min| min > 0),
+        limit_rows: args.limit_rows_per_file.filter(|&rows| rows > 0),
+        line_numbers: args.line_numbers,
+        record_size: args.record_size.filter(|&size| size > 0),
+        no_addr: args.no_addr,
+        no_color_addr: args.no_color_addr,
+        raw_offsets: args.raw_offsets,
+        final_offset: args.final_offset,
+        dim_addr_repeats: args.dim_addr_repeats,
+        offset_separator,
+        table_delimiters,
+        separator_color: args
+            .separator_color
+            .as_deref()
+            .map(parse_color)
+            .transpose()?,
+        punct_color: args.punct_color.as_deref().map(parse_color).transpose()?,
+        no_final_newline: args.no_final_newline,
+        progress: args.progress && io::stderr().is_terminal(),
+        show_bom: args.show_bom,
+        concat: args.concat,
+        quiet: args.quiet,
+        meta_stderr: args.meta_stderr,
+        debug_layout: args.debug_layout,
+    };
+    if let Some(path) = args.verify {
+        let dump = File::open(&path).map_err(|err| Error::File(err, path))?;
+        let mut input: Box<dyn Read> = Box::new(io::empty());
+        for path in paths {
+            let mut reader: Box<dyn Read> = if is_stdin(&path) {
+                Box::new(io::stdin())
+            } else {
+                Box::new(File::open(&path).map_err(|err| Error::File(err, path))?)
+            };
+            if let Some(compression) = compression {
+                reader = decompress(reader, compression)?;
+            }
+            input = Box::new(input.chain(input_format.decoder(reader, &layout)));
+        }
 
-    /// Number of bytes to group within a row.
-    group: usize,
+        return verify(&mut InputFormat::Dump.decoder(dump, &layout), &mut input);
+    }
+    if let Some(offset) = args.byte_at.as_deref().map(parse_size).transpose()? {
+        let path = &paths[0];
+        let mut skip = offset;
+        let mut reader: Box<dyn Read> = if is_stdin(path) {
+            Box::new(io::stdin())
+        } else {
+            let mut file = File::open(path).map_err(|err| Error::File(err, path.clone()))?;
+            let raw = matches!(input_format, InputFormat::Raw) && compression.is_none();
+            if raw && file.seek(SeekFrom::Start(offset)).is_ok() {
+                skip = 0;
+            }
+            Box::new(file)
+        };
+        if let Some(compression) = compression {
+            reader = decompress(reader, compression)?;
+        }
 
-    /// Numeric classification for character table.
-    numeric: Numeric,
+        let reader = input_format.decoder(reader, &layout);
+        let mut reader = transform(reader, &transforms, offset - skip);
+        return match byte_at(&mut reader, skip)? {
+            Some(byte) if args.uppercase => Ok(writeln!(io::stdout(), "{byte:02X}")?),
+            Some(byte) => Ok(writeln!(io::stdout(), "{byte:02x}")?),
+            None => Err(Error::PastEnd(offset, names[0].clone())),
+        };
+    }
+    if args.interactive {
+        let [path] = paths.as_slice() else {
+            return Err(Error::InteractiveInput);
+        };
+        let raw = matches!(input_format, InputFormat::Raw) && compression.is_none();
+        let dump = matches!(output_format, OutputFormat::Dump) && !args.count_only;
+        if is_stdin(path) || args.pid.is_some() || !raw || !dump || args.find_repeats {
+            return Err(Error::InteractiveInput);
+        }
 
-    /// Total number of columns to print for the hex digits in each row.
-    max: usize,
+        #[cfg(not(feature = "interactive"))]
+        return Err(Error::InteractiveUnsupported);
 
-    /// Internal state for printing rows and grouping bytes.
-    state: PrinterState,
-}
+        #[cfg(feature = "interactive")]
+        {
+            let file = File::open(path).map_err(|err| Error::File(err, path.clone()))?;
+            let len = window.size(device::len(&file).unwrap_or(0)) + window.skip;
+            let options = Options {
+                progress: false,
+                ..options
+            };
+            let new_printer = |columns| {
+                let mut printer =
+                    Printer::with_writer(widths[0], group, numeric, options.clone(), Vec::new())?;
+                if let Some(width) = printer.fit_width(columns).filter(|_| !args.no_shrink) {
+                    printer.set_width(width);
+                }
 
-#[derive(Default)]
-struct PrinterState {
-    addr: usize,
-    column: usize,
-    hex: String,
-    table: String,
-    hex_group: String,
-    table_group: String,
-}
+                Ok(printer)
+            };
+            let view = Browse {
+                name: &names[0],
+                len,
+                start: window.skip,
+                base_addr: base_addr.map(|addr| addr as usize),
+                transforms: &transforms,
+            };
 
-impl Printer {
-    /// Create a new row printer with width and group counts.
-    ///
-    /// # Errors
-    ///
-    /// - [`Error::Width`]: `width` is greater than 4096.
-    /// - [`Error::Grouping`]: `group` is greater than `width`.
-    fn new(width: usize, group: usize, numeric: Numeric) -> Result<Self, Error> {
-        if width <= 1 || width > 4096 {
-            Err(Error::Width)
-        } else if group > width {
-            Err(Error::Grouping)
-        } else {
-            Ok(Self {
-                width,
-                group,
-                numeric,
-                max: padding(group, width),
-                state: Default::default(),
-            })
+            return view.browse(&file, new_printer);
         }
     }
+    if args.watch {
+        let [path] = paths.as_slice() else {
+            return Err(Error::WatchInput);
+        };
+        if is_stdin(path) || args.pid.is_some() {
+            return Err(Error::WatchInput);
+        }
+        // Only a dump of the whole window is printed.
+        let conflicts = [
+            (
+                "--output-format",
+                !matches!(output_format, OutputFormat::Dump),
+            ),
+            ("--count-only", args.count_only),
+            ("--find-repeats", args.find_repeats),
+            ("--seek-to", seek_to.is_some()),
+            ("--sample", sample.is_some()),
+        ];
+        if let Some((option, _)) = conflicts.into_iter().find(|(_, conflict)| *conflict) {
+            return Err(Error::WatchOption(option));
+        }
+        if args.watch_interval == 0 {
+            return Err(Error::WatchInterval);
+        }
 
-    /// Pretty print a [`Reader`] as hex bytes.
-    fn pretty_hex<R>(&mut self, reader: &mut R) -> Result<(), Error>
-    where
-        R: Read,
-    {
-        let mut buf = [0; 4096];
-
+        // The screen is only cleared and the rows only shrink on a terminal.
+        let terminal = io::stdout().is_terminal();
+        let columns = terminal_columns().filter(|_| terminal && !args.no_shrink);
+        let interval = std::time::Duration::from_millis(args.watch_interval);
+        let mut watcher = Watcher::default();
         loop {
-            // Read as much as possible, appending to buffer.
-            let size = reader.read(&mut buf)?;
-            if size == 0 {
-                break;
-            }
+            // The file may be briefly missing while it is replaced.
+            if watcher.poll(Stamp::of(path).ok()) {
+                if let Ok(file) = File::open(path) {
+                    let mut reader: Box<dyn Read> = Box::new(file);
+                    if let Some(compression) = compression {
+                        reader = decompress(reader, compression)?;
+                    }
+                    let reader = input_format.decoder(reader, &layout);
+                    let reader = window.skip(reader, window.skip)?;
+                    let mut reader = window.limit(transform(reader, &transforms, window.skip));
+                    let addr = base_addr
+                        .map(|addr| addr as usize)
+                        .or((window.skip > 0).then_some(window.skip as usize));
 
-            // Print bytes grouped by classification.
-            let mut start = 0;
-            while start < size {
-                let group = Group::gather(&buf[start..size], self.numeric);
-                start += group.span.bytes.len();
-                self.format_group(group)?;
+                    if terminal {
+                        write!(io::stdout(), "\x1b[2J\x1b[H")?;
+                    }
+                    let mut printer = Printer::with_writer(
+                        widths[0],
+                        group,
+                        numeric,
+                        options.clone(),
+                        io::stdout(),
+                    )?;
+                    if let Some(width) = columns.and_then(|columns| printer.fit_width(columns)) {
+                        printer.set_width(width);
+                    }
+                    printer.start_input(names[0].clone(), false, None, addr, window.skip == 0)?;
+                    printer.pretty_hex(&mut reader, None)?;
+                    printer.finish()?;
+                }
             }
+            std::thread::sleep(interval);
         }
+    }
 
-        // Print any remaining row.
-        if self.state.column > 0 {
-            self.print_row()?;
+    // Paging is only useful when the output would otherwise go to a terminal.
+    let pager = if args.paginate && io::stdout().is_terminal() {
+        Pager::spawn().map_err(Error::Pager)?
+    } else {
+        None
+    };
+    let mut output = pager.as_ref().map_or(Output::Stdout, Pager::output);
+    let mut printers = widths
+        .iter()
+        .map(|&width| {
+            let writer: Box<dyn io::Write> = if html {
+                Box::new(Html::new(output.clone(), args.html_class))
+            } else {
+                Box::new(output.clone())
+            };
+            Printer::with_writer(width, group, numeric, options.clone(), writer)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let columns = terminal_columns().filter(|_| {
+        matches!(output_format, OutputFormat::Dump) && !args.no_shrink && io::stdout().is_terminal()
+    });
+    if let Some(columns) = columns {
+        for printer in &mut printers {
+            if let Some(width) = printer.fit_width(columns) {
+                if !args.quiet {
+                    writeln!(
+                        io::stderr(),
+                        "{}: Shrinking width from {} to {width} bytes to fit {columns} columns",
+                        stderr_label("Note", AnsiColor::BrightCyan),
+                        printer.width,
+                    )?;
+                }
+                printer.set_width(width);
+            }
         }
-
-        Ok(())
     }
+    if html {
+        write!(output, "<pre class=\"hd\">")?;
+    }
+    let mut buffered = Vec::new();
 
-    /// Format a classified group of bytes.
-    fn format_group(&mut self, group: Group<'_>) -> Result<(), Error> {
-        for (i, byte) in group.span.bytes.iter().enumerate() {
-            // Write byte group separator.
-            if self.state.column % self.group == 0 {
-                self.state.hex_group.write_char(' ')?;
+    // Raw encoders bypass the printer entirely.
+    let mut encoder = output_format.encoding(args.uppercase).map(|encoding| {
+        let writer = BufWriter::new(output.clone());
+        Encoder::new(writer, encoding, args.wrap)
+    });
+    let grouping = if args.numeric_color_only {
+        Numeric::None
+    } else {
+        numeric
+    };
+    let mut counts = args
+        .count_only
+        .then(|| Counts::new(BufWriter::new(output.clone()), grouping));
+    let mut repeats = args.find_repeats.then(|| {
+        Repeats::new(
+            BufWriter::new(output.clone()),
+            widths[0],
+            args.repeats_limit,
+        )
+    });
+    let mut records = matches!(output_format, OutputFormat::Records)
+        .then(|| Records::new(BufWriter::new(output.clone()), grouping));
+    let mut json = match output_format {
+        OutputFormat::Json => Some(false),
+        OutputFormat::JsonPretty => Some(true),
+        _ => None,
+    }
+    .map(|pretty| Json::new(BufWriter::new(output.clone()), widths[0], grouping, pretty));
+    let mut tsv = matches!(output_format, OutputFormat::Tsv)
+        .then(|| Tsv::new(BufWriter::new(output.clone()), widths[0], grouping));
+    let mut xxd = matches!(output_format, OutputFormat::Xxd)
+        .then(|| Xxd::new(BufWriter::new(output.clone()), widths[0], args.uppercase));
+    // Naming stdin also shows its header.
+    let show_header =
+        paths.len() > 1 || (args.stdin_name.is_some() && paths.iter().any(|path| is_stdin(path)));
+    // Number of zeros trimmed from the end of the current input.
+    let trimmed = Cell::new(0);
+    let mut dump = |input: Input, mut reader: Box<dyn Read + '_>| match (
+        &mut counts,
+        &mut repeats,
+        &mut encoder,
+        &mut records,
+        &mut json,
+        &mut tsv,
+        &mut xxd,
+    ) {
+        (Some(counts), _, _, _, _, _, _) => Ok(counts.write(&mut reader)?),
+        (None, Some(repeats), _, _, _, _, _) => Ok(repeats.write(&mut reader)?),
+        (None, None, Some(encoder), _, _, _, _) => Ok(encoder.encode(&mut reader)?),
+        (None, None, None, Some(records), _, _, _) => Ok(records.write(&mut reader)?),
+        (None, None, None, None, Some(json), _, _) => Ok(json.write(&mut reader)?),
+        (None, None, None, None, None, Some(tsv), _) => Ok(tsv.write(&mut reader)?),
+        (None, None, None, None, None, None, Some(xxd)) => {
+            if let Some(addr) = input.addr {
+                xxd.set_offset(addr as u64);
+            }
+            Ok(xxd.write(&mut reader)?)
+        }
+        // Inputs are read into memory to print them at every width.
+        (None, None, None, None, None, None, None) if printers.len() > 1 => {
+            let mut data = Vec::new();
+            reader.read_to_end(&mut data)?;
+            buffered.push((input, data, trimmed.get()));
+            Ok(())
+        }
+        (None, None, None, None, None, None, None) => {
+            let printer = &mut printers[0];
+            if input.continued {
+                printer.print_meta("...")?;
             }
+            let header = show_header && !input.continued;
+            printer.start_input(input.name, header, input.link, input.addr, input.at_start)?;
+            printer.pretty_hex(&mut reader, input.size_hint)?;
+            printer.print_trimmed(trimmed.get())
+        }
+    };
 
-            // Write hex.
-            write!(&mut self.state.hex_group, "{byte:02x}")?;
+    // Hyperlinks are only written to terminals that accept escape sequences.
+    let hyperlinks = args.hyperlinks
+        && colorz::mode::get_coloring_mode() != colorz::mode::Mode::Never
+        && if args.meta_stderr {
+            io::stderr().is_terminal()
+        } else {
+            io::stdout().is_terminal()
+        };
 
-            // Write character table.
-            let ch = match group.kind {
-                Kind::Printable | Kind::Numeric => Some(*byte as char),
-                Kind::Graphemes => match group.span.as_char(i, self.state.column, self.width) {
-                    Char::Cluster(cluster) => {
-                        self.state.table_group.write_str(cluster)?;
-                        None
+    for (path, name) in paths.into_iter().zip(names) {
+        let mut input = Input {
+            name,
+            link: None,
+            size_hint: None,
+            addr: None,
+            continued: false,
+            at_start: window.skip == 0 && args.pid.is_none(),
+        };
+        let mut skip = window.skip;
+        let mut len = None;
+
+        let reader: Box<dyn Read> = if is_stdin(&path) {
+            // Read from stdin.
+            let sample = io::Cursor::new(std::mem::take(&mut stdin_sample));
+            Box::new(sample.chain(io::stdin()))
+        } else if let Some(pid) = args.pid {
+            // Process memory is opened at the skipped address.
+            skip = 0;
+            Box::new(process::open(pid, window.skip)?)
+        } else {
+            // Read file path.
+            let mut file = File::open(&path).map_err(|err| Error::File(err, path.to_path_buf()))?;
+
+            // The file length is only meaningful for raw, uncompressed input, which can also seek
+            // past the skipped bytes.
+            len = device::len(&file);
+            let raw = matches!(input_format, InputFormat::Raw) && compression.is_none();
+            if let (Some(max), Some(len)) = (max_file_size, len) {
+                let size = if raw { window.size(len) } else { len };
+                if size > max {
+                    return Err(Error::FileTooLarge(path, size, max));
+                }
+            }
+            if raw {
+                input.size_hint = len.map(|len| window.size(len));
+                if skip > 0 && file.seek(SeekFrom::Start(skip)).is_ok() {
+                    skip = 0;
+                }
+            }
+            input.link = hyperlinks.then(|| file_uri(&path)).flatten();
+            input.addr = addr_pattern.as_ref().map(|pattern| {
+                addr_from_name(pattern, &input.name).unwrap_or_else(|| {
+                    if !args.quiet {
+                        let _ = writeln!(
+                            io::stderr(),
+                            "{}: No address found in file name: {:?}",
+                            stderr_label("Warning", AnsiColor::BrightYellow),
+                            input.name,
+                        );
                     }
-                    Char::Space => Some(' '),
-                    Char::Skip => None,
-                },
-                Kind::Control | Kind::Invalid => Some('.'),
-            };
-            if let Some(ch) = ch {
-                self.state.table_group.write_char(ch)?;
+                    0
+                })
+            });
+
+            if let (Some(count), Some(len), true) = (sample, len, raw) {
+                let sample_size = window.length.unwrap_or(widths[0] as u64);
+                let base = match base_addr {
+                    Some(addr) => addr as usize,
+                    None => offset_addr(input.addr.unwrap_or(0), window.skip)?,
+                };
+                for (i, (offset, size)) in samples(len, window.skip, sample_size, count).enumerate()
+                {
+                    file.seek(SeekFrom::Start(offset))?;
+                    let input = Input {
+                        addr: Some(offset_addr(base, offset - window.skip)?),
+                        size_hint: Some(size),
+                        continued: i > 0,
+                        at_start: offset == 0,
+                        ..input.clone()
+                    };
+                    let reader = Box::new((&file).take(size));
+                    dump(input, transform(reader, &transforms, offset))?;
+                }
+                continue;
             }
 
-            self.state.column += 1;
-            if self.state.column == self.width {
-                self.colorize_group(group.kind)?;
-                self.print_row()?;
+            Box::new(file)
+        };
+        let reader = match compression {
+            Some(compression) => decompress(reader, compression)?,
+            None => reader,
+        };
+        let reader = input_format.decoder(reader, &layout);
+
+        // Addresses reflect the skipped bytes unless a base address is given.
+        let skipped = input
+            .addr
+            .map(|addr| offset_addr(addr, window.skip))
+            .transpose()?;
+        input.addr = base_addr
+            .map(|addr| addr as usize)
+            .or(skipped)
+            .or((window.skip > 0).then_some(window.skip as usize));
+
+        let reader = window.skip(reader, skip)?;
+        let mut reader = transform(reader, &transforms, window.skip);
+        if let Some(pattern) = &seek_to {
+            let Some((offset, found)) = seek::seek_to(reader, pattern)? else {
+                if !args.quiet {
+                    let _ = writeln!(
+                        io::stderr(),
+                        "{}: Pattern not found in {:?}",
+                        stderr_label("Warning", AnsiColor::BrightYellow),
+                        input.name,
+                    );
+                }
+                continue;
+            };
+            reader = found;
+
+            input.at_start = input.at_start && offset == 0;
+
+            // A base address is the address of the match.
+            if base_addr.is_none() {
+                input.addr = Some(offset_addr(input.addr.unwrap_or(0), offset)?);
+            }
+            if input.size_hint.is_some() {
+                input.size_hint = len.map(|len| window.size(len.saturating_sub(offset)));
             }
         }
-
-        if self.state.column > 0 {
-            self.colorize_group(group.kind)?;
+        let mut reader = window.limit(reader);
+        if args.trim_trailing_zeros {
+            trimmed.set(0);
+            reader = Box::new(TrimZeros::new(reader, &trimmed));
         }
 
-        Ok(())
+        dump(input, reader)?;
     }
 
-    // Colorize formatted group.
-    fn colorize_group(&mut self, kind: Kind) -> Result<(), Error> {
-        let hex = &mut self.state.hex;
-        let table = &mut self.state.table;
-        let row_group = &self.state.hex_group;
-        let table_group = &self.state.table_group;
-        match kind {
-            Kind::Control => {
-                write!(hex, "{}", row_group.bright_yellow())?;
-                write!(table, "{}", table_group.bright_yellow())?;
-            }
-            Kind::Printable => {
-                write!(hex, "{}", row_group.bright_green())?;
-                write!(table, "{}", table_group.bright_green())?;
+    if let Some(counts) = counts {
+        counts.finish()?;
+    }
+    if let Some(repeats) = repeats {
+        repeats.finish()?;
+    }
+    if let Some(encoder) = encoder {
+        encoder.finish()?;
+    }
+    if let Some(records) = records {
+        records.finish()?;
+    }
+    if let Some(json) = json {
+        json.finish()?;
+    }
+    if let Some(tsv) = tsv {
+        tsv.finish()?;
+    }
+    if let Some(xxd) = xxd {
+        xxd.finish()?;
+    }
+
+    for mut printer in printers {
+        if !buffered.is_empty() {
+            printer.print_meta(&format!("\n[width {}]", printer.width))?;
+        }
+        for (input, data, trimmed) in &buffered {
+            let input = input.clone();
+            if input.continued {
+                printer.print_meta("...")?;
             }
-            Kind::Numeric => {
-                write!(hex, "{}", row_group.bright_cyan())?;
-                write!(table, "{}", table_group.bright_cyan())?;
+            let header = show_header && !input.continued;
+            printer.start_input(input.name, header, input.link, input.addr, input.at_start)?;
+            printer.pretty_hex(&mut data.as_slice(), None)?;
+            printer.print_trimmed(*trimmed)?;
+        }
+        printer.finish()?;
+    }
+    if html {
+        writeln!(output, "</pre>")?;
+    }
+
+    Ok(())
+}
+
+/// A file browsed with `--interactive`.
+#[cfg(feature = "interactive")]
+struct Browse<'a> {
+    /// Name shown on the status line.
+    name: &'a str,
+
+    /// Offset of the end of the browsed bytes.
+    len: u64,
+
+    /// Offset shown first.
+    start: u64,
+
+    /// Address of the first byte in the file.
+    base_addr: Option<usize>,
+
+    /// Transforms applied to the bytes.
+    transforms: &'a [Transform],
+}
+
+#[cfg(feature = "interactive")]
+impl Browse<'_> {
+    /// Browse the file until the user quits.
+    ///
+    /// The screen is redrawn after each key, with a new printer from `new_printer` for the
+    /// terminal's width in columns. Only the visible rows are read, by seeking to them, so files of
+    /// any size can be browsed.
+    fn browse<F>(&self, mut file: &File, new_printer: F) -> Result<(), Error>
+    where
+        F: Fn(usize) -> Result<Printer<Vec<u8>>, Error>,
+    {
+        use self::interactive::{Key, Terminal, View};
+
+        let mut terminal = Terminal::open().map_err(Error::Terminal)?;
+        let mut view = View::new(self.len);
+        let mut pattern = String::new();
+        let mut message = None;
+        let mut start = Some(self.start);
+
+        loop {
+            let (rows, columns) = terminal.size();
+            let height = rows.saturating_sub(1).max(1);
+            let mut printer = new_printer(columns)?;
+            view.resize(printer.width, height);
+            if let Some(offset) = start.take() {
+                view.go_to(offset);
             }
-            Kind::Graphemes => {
-                write!(hex, "{}", row_group.green().bold())?;
-                write!(table, "{}", table_group.green().bold())?;
+
+            let offset = view.offset();
+            file.seek(SeekFrom::Start(offset))?;
+            let reader: Box<dyn Read> = Box::new(file.take(view.size()));
+            let mut reader = transform(reader, self.transforms, offset);
+            let addr = offset_addr(self.base_addr.unwrap_or(0), offset)?;
+            printer.start_input(self.name.to_string(), false, None, Some(addr), offset == 0)?;
+            printer.pretty_hex(&mut reader, Some(view.size()))?;
+            printer.finish()?;
+
+            let status = message.take().unwrap_or_else(|| {
+                let percent = (offset + view.size()) * 100 / self.len.max(1);
+                format!(
+                    " {}  {offset:#x}/{:#x}  {percent}%  q: quit  /: search  g: go to",
+                    self.name, self.len,
+                )
+            });
+            terminal.draw(&printer.writer, height, columns, &status)?;
+
+            let page = view.height();
+            match terminal.read_key()? {
+                Key::Char('q') | Key::Interrupt => return Ok(()),
+                Key::Char('j') | Key::Down | Key::Enter => view.scroll(1),
+                Key::Char('k') | Key::Up => view.scroll(-1),
+                Key::Char(' ') | Key::PageDown => view.scroll(page),
+                Key::Char('b') | Key::PageUp => view.scroll(-page),
+                Key::Char('G') | Key::End => view.go_to(self.len),
+                Key::Home => view.go_to(0),
+                Key::Char('g') => {
+                    if let Some(input) = terminal.prompt(height, "Go to offset: ")? {
+                        match parse_size(input.trim()) {
+                            Ok(offset) => view.go_to(offset),
+                            Err(err) => message = Some(format!(" {err}")),
+                        }
+                    }
+                }
+                Key::Char(key @ ('/' | 'n')) => {
+                    if key == '/' {
+                        match terminal.prompt(height, "Search: ")? {
+                            Some(input) => pattern = input,
+                            None => continue,
+                        }
+                    }
+                    if pattern.is_empty() {
+                        continue;
+                    }
+
+                    // Search from the row after the top, so repeated searches move forward.
+                    let from = (offset + printer.width as u64).min(self.len);
+                    file.seek(SeekFrom::Start(from))?;
+                    let reader: Box<dyn Read> = Box::new(file.take(self.len - from));
+                    let reader = transform(reader, self.transforms, from);
+                    match seek::seek_to(reader, pattern.as_bytes())? {
+                        Some((found, _)) => view.go_to(from + found),
+                        None => message = Some(format!(" Pattern not found: {pattern:?}")),
+                    }
+                }
+                _ => (),
             }
-            Kind::Invalid => {
-                write!(hex, "{}", row_group.bright_red())?;
-                write!(table, "{}", table_group.bright_red())?;
+        }
+    }
+}
+
+/// Length of the longest byte-order mark.
+const BOM_LEN: usize = 4;
+
+/// Minimum number of zero bytes that are collapsed into a sparse hole.
+const SPARSE_HOLE: usize = 4096;
+
+/// Default name used for standard input in headers and row prefixes.
+const STDIN_NAME: &str = "stdin";
+
+/// Path which reads from standard input.
+const STDIN_PATH: &str = "-";
+
+/// Describes one input to be printed.
+#[derive(Clone)]
+struct Input {
+    /// Name shown in headers and row prefixes.
+    name: String,
+
+    /// A `file://` URI to link from the header.
+    link: Option<String>,
+
+    /// Total number of bytes expected, if known.
+    size_hint: Option<u64>,
+
+    /// Starting address, or `None` to continue from the previous input.
+    addr: Option<usize>,
+
+    /// The input is a later sample of a file, which follows a `...` line instead of a header.
+    continued: bool,
+
+    /// The input is read from its first byte, where a byte-order mark may be.
+    at_start: bool,
+}
+
+/// Maximum number of widths that can be compared.
+const MAX_WIDTHS: usize = 8;
+
+/// Parse a comma-separated list of row widths.
+fn parse_widths(s: &str) -> Result<Vec<usize>, Error> {
+    let widths = s
+        .split(',')
+        .map(|width| {
+            width
+                .trim()
+                .parse()
+                .map_err(|_| Error::InvalidWidth(width.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if widths.len() > MAX_WIDTHS {
+        Err(Error::TooManyWidths(MAX_WIDTHS))
+    } else {
+        Ok(widths)
+    }
+}
+
+/// Parse a group size from a number or a named size.
+fn parse_group(s: &str) -> Result<usize, Error> {
+    match s.to_lowercase().as_str() {
+        "byte" => Ok(1),
+        "word" => Ok(2),
+        "dword" => Ok(4),
+        "qword" => Ok(8),
+        _ => s.parse().map_err(|_| Error::UnknownGroup(s.to_string())),
+    }
+}
+
+/// A window of bytes to print from each input.
+struct Window {
+    /// Number of bytes to skip at the start.
+    skip: u64,
+
+    /// Maximum number of bytes to print after skipping.
+    length: Option<u64>,
+}
+
+impl Window {
+    /// Get the number of bytes in the window for an input with a known size.
+    fn size(&self, size: u64) -> u64 {
+        let size = size.saturating_sub(self.skip);
+
+        self.length.map_or(size, |length| size.min(length))
+    }
+
+    /// Discard `skip` bytes from a reader.
+    ///
+    /// The skip is given separately for readers that have already seeked past the window start.
+    fn skip<'a>(
+        &self,
+        mut reader: Box<dyn Read + 'a>,
+        skip: u64,
+    ) -> io::Result<Box<dyn Read + 'a>> {
+        io::copy(&mut (&mut reader).take(skip), &mut io::sink())?;
+
+        Ok(reader)
+    }
+
+    /// Limit a reader to the window length.
+    fn limit<'a>(&self, reader: Box<dyn Read + 'a>) -> Box<dyn Read + 'a> {
+        match self.length {
+            Some(length) => Box::new(reader.take(length)),
+            None => reader,
+        }
+    }
+}
+
+/// Get the offsets and sizes of `count` samples evenly spaced through a file, after `skip`.
+///
+/// The first sample starts at `skip` and the last ends at the end of the file. When the samples
+/// would cover the whole file, it is a single sample instead.
+fn samples(len: u64, skip: u64, size: u64, count: usize) -> impl Iterator<Item = (u64, u64)> {
+    let region = len.saturating_sub(skip);
+    let (count, size) = if region <= size.saturating_mul(count as u64) {
+        (1, region)
+    } else {
+        (count as u64, size)
+    };
+    let last = count.saturating_sub(1).max(1);
+
+    (0..count).map(move |i| {
+        let offset = u128::from(region - size) * u128::from(i) / u128::from(last);
+        (skip + offset as u64, size)
+    })
+}
+
+/// Parse a byte pattern from hex digits, ignoring whitespace and `0x` prefixes.
+fn parse_pattern(s: &str) -> Result<Vec<u8>, Error> {
+    let mut pattern = Vec::new();
+    match Decoder::new(s.as_bytes(), Hex::default()).read_to_end(&mut pattern) {
+        Ok(_) if !pattern.is_empty() => Ok(pattern),
+        _ => Err(Error::InvalidPattern(s.to_string())),
+    }
+}
+
+/// Parse comma-separated opening and closing table delimiters.
+///
+/// The opening delimiter needs a character that cannot be in the hex column, to find the end of
+/// the hex column when reversing a dump.
+fn parse_delimiters(s: String) -> Result<(String, String), Error> {
+    let Some((open, close)) = s.split_once(',') else {
+        return Err(Error::InvalidDelimiters(s));
+    };
+    if !open
+        .chars()
+        .any(|ch| !ch.is_whitespace() && !ch.is_ascii_hexdigit())
+        || s.chars().any(char::is_control)
+    {
+        return Err(Error::InvalidDelimiters(s));
+    }
+
+    Ok((open.to_string(), close.to_string()))
+}
+
+/// Parse an offset separator, which must be a single grapheme with a display width of 1.
+fn parse_separator(s: String) -> Result<String, Error> {
+    let is_control = s.chars().any(char::is_control);
+    if !is_control && s.graphemes(true).count() == 1 && unicode_display_width::width(&s) == 1 {
+        Ok(s)
+    } else {
+        Err(Error::InvalidSeparator(s))
+    }
+}
+
+/// Parse the name of one of the 16 basic ANSI colors, like `red` or `bright-black`.
+fn parse_color(s: &str) -> Result<Color, Error> {
+    const COLORS: [(&str, AnsiColor); 16] = [
+        ("black", AnsiColor::Black),
+        ("red", AnsiColor::Red),
+        ("green", AnsiColor::Green),
+        ("yellow", AnsiColor::Yellow),
+        ("blue", AnsiColor::Blue),
+        ("magenta", AnsiColor::Magenta),
+        ("cyan", AnsiColor::Cyan),
+        ("white", AnsiColor::White),
+        ("bright-black", AnsiColor::BrightBlack),
+        ("bright-red", AnsiColor::BrightRed),
+        ("bright-green", AnsiColor::BrightGreen),
+        ("bright-yellow", AnsiColor::BrightYellow),
+        ("bright-blue", AnsiColor::BrightBlue),
+        ("bright-magenta", AnsiColor::BrightMagenta),
+        ("bright-cyan", AnsiColor::BrightCyan),
+        ("bright-white", AnsiColor::BrightWhite),
+    ];
+
+    let name = s.to_lowercase().replace('_', "-");
+    COLORS
+        .iter()
+        .find(|(color, _)| *color == name)
+        .map(|&(_, color)| Color::Ansi(color))
+        .ok_or_else(|| Error::UnknownColor(s.to_string()))
+}
+
+/// Parse a half-open address range `START:END`, where both are sizes.
+fn parse_mark(s: &str) -> Result<Range<usize>, Error> {
+    let error = || Error::InvalidMark(s.to_string());
+    let (start, end) = s.split_once(':').ok_or_else(error)?;
+    let size = |s| {
+        parse_size(s)
+            .ok()
+            .and_then(|size| usize::try_from(size).ok())
+    };
+    let (start, end) = (size(start).ok_or_else(error)?, size(end).ok_or_else(error)?);
+    if start > end {
+        return Err(error());
+    }
+
+    Ok(start..end)
+}
+
+/// Parse a half-open address range `START:END` into a window, where either bound may be empty.
+///
+/// An empty `START` is the start of the input, and an empty `END` is the end of the input.
+fn parse_dump_range(s: &str) -> Result<Window, Error> {
+    let error = || Error::InvalidDumpRange(s.to_string());
+    let (start, end) = s.split_once(':').ok_or_else(error)?;
+    let size = |s: &str| (!s.is_empty()).then(|| parse_size(s)).transpose();
+    let skip = size(start).map_err(|_| error())?.unwrap_or(0);
+    let end = size(end).map_err(|_| error())?;
+    let length = end
+        .map(|end| end.checked_sub(skip).ok_or_else(error))
+        .transpose()?;
+
+    Ok(Window { skip, length })
+}
+
+/// Parse a size as a decimal or `0x` hex number, with an optional binary suffix.
+fn parse_size(s: &str) -> Result<u64, Error> {
+    let error = || Error::InvalidSize(s.to_string());
+    let (digits, shift) = match s.bytes().last().map(|b| b.to_ascii_lowercase()) {
+        Some(b'k') => (&s[..s.len() - 1], 10),
+        Some(b'm') => (&s[..s.len() - 1], 20),
+        Some(b'g') => (&s[..s.len() - 1], 30),
+        Some(b't') => (&s[..s.len() - 1], 40),
+        _ => (s, 0),
+    };
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => digits.parse(),
+    };
+
+    value
+        .map_err(|_| error())?
+        .checked_mul(1 << shift)
+        .ok_or_else(error)
+}
+
+/// Numeric context for byte classification.
+#[derive(Copy, Clone)]
+enum Numeric {
+    None,
+    Binary,
+    Octal,
+    Decimal,
+    Hexadecimal,
+    HexDigitsOnly,
+
+    /// Detected from the start of the input with [`Numeric::detect`].
+    Auto,
+}
+
+impl FromStr for Numeric {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Self::None),
+            "b" | "bin" | "binary" => Ok(Self::Binary),
+            "o" | "oct" | "octal" => Ok(Self::Octal),
+            "d" | "dec" | "decimal" => Ok(Self::Decimal),
+            "h" | "x" | "hex" | "hexadecimal" => Ok(Self::Hexadecimal),
+            "hex-digits-only" => Ok(Self::HexDigitsOnly),
+            "auto" => Ok(Self::Auto),
+            _ => Err(Error::UnknownNumeric(s.to_string())),
+        }
+    }
+}
+
+impl Numeric {
+    /// Guess the numeric class from a sample of input bytes.
+    ///
+    /// The sample is split into words of ASCII letters and digits, and numeric words are counted:
+    ///
+    /// - Hexadecimal: digits mixed with the letters `a-f`, or a `0x` prefix (e.g. `1f`, `0xff`)
+    /// - Decimal: only digits
+    /// - Octal: decimal words with a leading `0` and only the digits `0-7` (e.g. `0755`)
+    ///
+    /// Hexadecimal is chosen when there are more hexadecimal words than decimal words, and octal
+    /// when every decimal word is octal. Anything else is ambiguous, and chooses decimal.
+    fn detect(sample: &[u8]) -> Self {
+        let mut hex = 0;
+        let mut decimal = 0;
+        let mut octal = 0;
+
+        for word in sample.split(|byte| !byte.is_ascii_alphanumeric()) {
+            if let Some(digits) = word.strip_prefix(b"0x").or(word.strip_prefix(b"0X")) {
+                if !digits.is_empty() && digits.iter().all(u8::is_ascii_hexdigit) {
+                    hex += 1;
+                }
+            } else if !word.is_empty() && word.iter().all(u8::is_ascii_digit) {
+                decimal += 1;
+                if word.len() > 1 && word[0] == b'0' && word.iter().all(|b| *b <= b'7') {
+                    octal += 1;
+                }
+            } else if word.iter().all(u8::is_ascii_hexdigit) && word.iter().any(u8::is_ascii_digit)
+            {
+                hex += 1;
             }
         }
 
-        self.state.hex_group.clear();
-        self.state.table_group.clear();
+        if hex > decimal {
+            Self::Hexadecimal
+        } else if octal > 0 && octal == decimal {
+            Self::Octal
+        } else {
+            Self::Decimal
+        }
+    }
+}
 
-        Ok(())
+/// Input decoding applied before printing.
+#[derive(Copy, Clone)]
+enum InputFormat {
+    Raw,
+    Hex,
+    Base64,
+    Dump,
+}
+
+impl FromStr for InputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "raw" => Ok(Self::Raw),
+            "hex" => Ok(Self::Hex),
+            "base64" => Ok(Self::Base64),
+            "dump" => Ok(Self::Dump),
+            _ => Err(Error::UnknownInputFormat(s.to_string())),
+        }
     }
+}
 
-    // Print a complete row.
-    fn print_row(&mut self) -> Result<(), Error> {
-        let written = writeln!(
-            io::stdout(),
-            "{addr}:{hex}{hex_pad} | {table}{table_pad} |",
-            addr = self.pretty_addr(),
-            hex = self.state.hex,
-            hex_pad = " ".repeat(self.max - padding(self.group, self.state.column)),
-            table = self.state.table,
-            table_pad = " ".repeat(self.width - self.state.column),
-        );
+impl InputFormat {
+    /// Wrap a reader with the decoder for this input format. Dump rows have the separators in
+    /// `layout`.
+    fn decoder<'a, R>(self, reader: R, layout: &DumpLayout) -> Box<dyn Read + 'a>
+    where
+        R: Read + 'a,
+    {
+        match self {
+            Self::Raw => Box::new(reader),
+            Self::Hex => Box::new(Decoder::new(reader, Hex::default())),
+            Self::Base64 => Box::new(Decoder::new(reader, Base64::default())),
+            Self::Dump => Box::new(Decoder::new(reader, Dump::new(layout.clone()))),
+        }
+    }
+}
+
+/// Output format for the input bytes.
+#[derive(Copy, Clone)]
+enum OutputFormat {
+    Dump,
+    Hex,
+    Base64,
+    Records,
+    Json,
+    JsonPretty,
+    Html,
+    Tsv,
+    Xxd,
+}
 
-        // Exit process if the stdout pipe was closed.
-        if written.is_err() {
-            std::process::exit(1);
+impl FromStr for OutputFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "dump" => Ok(Self::Dump),
+            "hex" => Ok(Self::Hex),
+            "base64" => Ok(Self::Base64),
+            "records" => Ok(Self::Records),
+            "json" => Ok(Self::Json),
+            "json-pretty" => Ok(Self::JsonPretty),
+            "html" => Ok(Self::Html),
+            "tsv" => Ok(Self::Tsv),
+            "xxd" => Ok(Self::Xxd),
+            _ => Err(Error::UnknownOutputFormat(s.to_string())),
         }
+    }
+}
 
-        self.state.column = 0;
-        self.state.addr += self.width;
-        self.state.hex.clear();
-        self.state.table.clear();
+impl OutputFormat {
+    /// Get the raw text encoding for this output format, if it has one.
+    fn encoding(self, uppercase: bool) -> Option<Encoding> {
+        match self {
+            Self::Dump
+            | Self::Records
+            | Self::Json
+            | Self::JsonPretty
+            | Self::Html
+            | Self::Tsv
+            | Self::Xxd => None,
+            Self::Hex => Some(Encoding::Hex { uppercase }),
+            Self::Base64 => Some(Encoding::Base64),
+        }
+    }
+}
 
-        Ok(())
+/// Checksum algorithm for groups of bytes.
+#[derive(Copy, Clone)]
+enum Checksum {
+    /// XOR of all bytes.
+    Xor,
+
+    /// Sum of all bytes, modulo 256.
+    Sum,
+}
+
+impl FromStr for Checksum {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "xor" => Ok(Self::Xor),
+            "sum" => Ok(Self::Sum),
+            _ => Err(Error::UnknownChecksum(s.to_string())),
+        }
     }
+}
 
-    // Return the address as a formatted and colorized string.
-    fn pretty_addr(&self) -> colorz::StyledValue<String, colorz::ansi::BrightBlue> {
-        let a = self.state.addr >> 48;
-        let b = (self.state.addr >> 32) & 0xffff;
-        let c = (self.state.addr >> 16) & 0xffff;
-        let d = self.state.addr & 0xffff;
+impl Checksum {
+    /// Compute the checksum of a group of bytes.
+    fn compute(self, bytes: &[u8]) -> u8 {
+        match self {
+            Self::Xor => bytes.iter().fold(0, |acc, byte| acc ^ byte),
+            Self::Sum => bytes.iter().fold(0, |acc, &byte| acc.wrapping_add(byte)),
+        }
+    }
+}
+
+/// Display width of characters with an ambiguous East Asian width.
+#[derive(Copy, Clone, Default)]
+enum EastAsianWidth {
+    #[default]
+    Narrow,
+    Wide,
+}
+
+impl FromStr for EastAsianWidth {
+    type Err = Error;
 
-        format!("{:04x}_{:04x}_{:04x}_{:04x}", a, b, c, d).into_bright_blue()
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "narrow" => Ok(Self::Narrow),
+            "wide" => Ok(Self::Wide),
+            _ => Err(Error::UnknownEastAsianWidth(s.to_string())),
+        }
     }
 }
 
-/// Compute the number of columns needed to print a byte slice of the given length as grouped hex
-/// bytes.
-fn padding(group: usize, length: usize) -> usize {
-    length * 2 + length.div_ceil(group)
+/// Byte order of the groups in the hex column.
+#[derive(Copy, Clone, Default, PartialEq)]
+enum Endian {
+    #[default]
+    Big,
+    Little,
+}
+
+impl FromStr for Endian {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "big" | "be" => Ok(Self::Big),
+            "little" | "le" => Ok(Self::Little),
+            _ => Err(Error::UnknownEndian(s.to_string())),
+        }
+    }
+}
+
+/// Hash of the bytes in a row.
+#[derive(Copy, Clone)]
+enum RowHash {
+    /// CRC-32 (ISO 3309).
+    Crc32,
+}
+
+impl FromStr for RowHash {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "crc32" => Ok(Self::Crc32),
+            _ => Err(Error::UnknownRowHash(s.to_string())),
+        }
+    }
+}
+
+impl RowHash {
+    /// Compute the hash of a row of bytes.
+    fn compute(self, bytes: &[u8]) -> u32 {
+        match self {
+            Self::Crc32 => crc32::crc32(0, bytes),
+        }
+    }
+}
+
+/// Letter case transform for the character table.
+#[derive(Copy, Clone)]
+enum TableCase {
+    Upper,
+    Lower,
+    Swap,
+}
+
+impl FromStr for TableCase {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "upper" => Ok(Self::Upper),
+            "lower" => Ok(Self::Lower),
+            "swap" => Ok(Self::Swap),
+            _ => Err(Error::UnknownTableCase(s.to_string())),
+        }
+    }
+}
+
+impl TableCase {
+    /// Apply the transform to an ASCII character.
+    fn apply(self, ch: char) -> char {
+        match self {
+            Self::Upper => ch.to_ascii_uppercase(),
+            Self::Lower => ch.to_ascii_lowercase(),
+            Self::Swap if ch.is_ascii_uppercase() => ch.to_ascii_lowercase(),
+            Self::Swap => ch.to_ascii_uppercase(),
+        }
+    }
+}
+
+/// Columns colored by byte classification.
+#[derive(Copy, Clone)]
+struct ColorColumns {
+    hex: bool,
+    ascii: bool,
+}
+
+impl Default for ColorColumns {
+    fn default() -> Self {
+        Self {
+            hex: true,
+            ascii: true,
+        }
+    }
+}
+
+impl FromStr for ColorColumns {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns = Self {
+            hex: false,
+            ascii: false,
+        };
+        for column in s.split(',') {
+            match column.trim().to_lowercase().as_str() {
+                "hex" => columns.hex = true,
+                "ascii" => columns.ascii = true,
+                "none" => (),
+                _ => return Err(Error::UnknownColorColumn(column.to_string())),
+            }
+        }
+
+        Ok(columns)
+    }
+}
+
+/// Parse the columns where numeric bytes are colored as numeric.
+fn parse_numeric_color(s: &str) -> Result<ColorColumns, Error> {
+    let (hex, ascii) = match s.to_lowercase().as_str() {
+        "ascii" => (false, true),
+        "hex" => (true, false),
+        "both" => (true, true),
+        _ => return Err(Error::UnknownNumericColor(s.to_string())),
+    };
+
+    Ok(ColorColumns { hex, ascii })
+}
+
+/// Row printer. Pretty prints byte slices one row at a time.
+struct Printer<W> {
+    /// Number of bytes per row.
+    width: usize,
+
+    /// Number of bytes to group within a row.
+    group: usize,
+
+    /// Numeric classification for character table.
+    numeric: Numeric,
+
+    /// Display options.
+    options: Options,
+
+    /// Total number of columns to print for the hex digits in each row.
+    max: usize,
+
+    /// Number of characters to print in the character table for each row.
+    table_width: usize,
+
+    /// Internal state for printing rows and grouping bytes.
+    state: PrinterState,
+
+    /// The writer receiving dump rows.
+    writer: W,
+}
+
+/// Display options for the row printer.
+///
+/// Output is either data or meta. Data is the dump rows themselves, which are always written to
+/// the printer's writer (stdout). Meta is auxiliary output, like file headers, which can be
+/// suppressed or redirected to stderr.
+#[derive(Clone, Default)]
+struct Options {
+    /// Print hex digits in uppercase.
+    uppercase: bool,
+
+    /// Color numeric bytes without grouping them separately.
+    numeric_color_only: bool,
+
+    /// Color digits outside of the numeric radix with a warning color.
+    color_numeric_by_radix: bool,
+
+    /// Show suspicious grapheme clusters as invalid bytes.
+    classify_utf8_strict: bool,
+
+    /// Group numeric bytes into tokens with a sign and decimal point.
+    numeric_tokens: bool,
+
+    /// Minimum length of numeric groups; shorter ones are printable.
+    numeric_min_run: Option<usize>,
+
+    /// Round the width up to a multiple of the group size.
+    align_width: bool,
+
+    /// Number of characters to show in the character table, if different from the width.
+    table_width: Option<usize>,
+
+    /// Insert an extra space between every N groups.
+    group_gap: Option<usize>,
+
+    /// Remove the `|` separators around the character table.
+    no_separator: bool,
+
+    /// Remove the space before the first group of hex bytes.
+    no_leading_space: bool,
+
+    /// Do not print the hex column.
+    no_hex: bool,
+
+    /// Show C escapes for common control characters in the table.
+    ascii_escape: bool,
+
+    /// Space the character table into groups like the hex column.
+    mirror_groups: bool,
+
+    /// Byte order of the groups in the hex column.
+    endian: Endian,
+
+    /// Pad a partial group at the end of the last row to its full size.
+    pad_last_group: bool,
+
+    /// Space the character table into groups of this many bytes.
+    table_group: Option<usize>,
+
+    /// Append a checksum of each complete group to every row.
+    group_checksum: Option<Checksum>,
+
+    /// Append a hash of the bytes in every row.
+    row_hash: Option<RowHash>,
+
+    /// Append the number of printable bytes before each row within the input.
+    byte_count_column: bool,
+
+    /// Repeat the file header every N rows.
+    repeat_header: Option<usize>,
+
+    /// Prefix each row with the input name, padded to this display width.
+    with_filename: Option<usize>,
+
+    /// Only colorize control characters and invalid bytes.
+    invert: bool,
+
+    /// Show invalid bytes as Latin-1 characters in the character table.
+    invalid_as_latin1: bool,
+
+    /// Change the case of ASCII letters in the character table.
+    table_case: Option<TableCase>,
+
+    /// Color hex bytes by value instead of by classification.
+    color_scale: Option<ColorScale>,
+
+    /// Dim zero bytes in the hex column.
+    collapse_zeros: bool,
+
+    /// Columns where numeric bytes are colored as numeric instead of printable.
+    numeric_color: ColorColumns,
+
+    /// Display width of characters with an ambiguous East Asian width.
+    east_asian_width: EastAsianWidth,
+
+    /// Columns colored by classification.
+    color_columns: ColorColumns,
+
+    /// Highlight bytes at addresses in these ranges.
+    marks: Vec<Range<usize>>,
+
+    /// Annotate the start of each input with these struct fields.
+    fields: Vec<structs::Field>,
+
+    /// Collapse long runs of zero bytes into sparse holes.
+    sparse: bool,
+
+    /// Collapse runs of at least this many identical rows.
+    squeeze: Option<usize>,
+
+    /// Only print rows with part of a printable run of at least this many bytes.
+    min_string: Option<usize>,
+
+    /// Print at most this many rows of each input.
+    limit_rows: Option<usize>,
+
+    /// Prefix each row with its line number within the input.
+    line_numbers: bool,
+
+    /// Prefix each row with its position in records of this size.
+    record_size: Option<usize>,
+
+    /// Do not print the address column.
+    no_addr: bool,
+
+    /// Do not colorize the address column and other row prefixes.
+    no_color_addr: bool,
+
+    /// Print the address as plain hex without underscores.
+    raw_offsets: bool,
+
+    /// Print the ending address after the last row of each input.
+    final_offset: bool,
+
+    /// Dim the prefix of the address shared with the previous row.
+    dim_addr_repeats: bool,
+
+    /// Separator after the row prefixes, when it is not `:`.
+    offset_separator: Option<String>,
+
+    /// Opening and closing delimiters around the character table, when they are not ` | ` and ` |`.
+    table_delimiters: Option<(String, String)>,
+
+    /// Color of the character table delimiters.
+    separator_color: Option<Color>,
+
+    /// Color of the separator after the row prefixes.
+    punct_color: Option<Color>,
+
+    /// Do not print a newline after the last line of stdout.
+    no_final_newline: bool,
+
+    /// Show progress on stderr for inputs with a known length.
+    progress: bool,
+
+    /// Continue rows and addresses across inputs.
+    concat: bool,
+
+    /// Print a notice on stderr when an input starts with a byte-order mark.
+    show_bom: bool,
+
+    /// Suppress all meta output.
+    quiet: bool,
+
+    /// Write meta output to stderr instead of stdout.
+    meta_stderr: bool,
+
+    /// Print a column ruler and check the table separator alignment on every row.
+    debug_layout: bool,
+}
+
+/// Totals of the bytes and rows printed, returned by [`Printer::finish`].
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Stats {
+    /// Number of bytes printed.
+    total_bytes: u64,
+
+    /// Number of rows printed, including sparse holes and squeezed `*` lines.
+    rows: usize,
+
+    /// Number of bytes of each kind, in [`Kind::ALL`] order.
+    counts_by_kind: [u64; 5],
+}
+
+/// Style of a run of bytes within a group.
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct Style {
+    /// Classification of the bytes.
+    kind: Kind,
+
+    /// The bytes are highlighted.
+    marked: bool,
+
+    /// The bytes are digits outside of the numeric radix.
+    overflow: bool,
+}
+
+#[derive(Default)]
+struct PrinterState {
+    name: String,
+    header: bool,
+    link: Option<String>,
+    rows: usize,
+    addr: usize,
+    column: usize,
+    hex: String,
+    table: String,
+    hex_group: String,
+    table_group: String,
+    hex_cells: Vec<String>,
+    row: Vec<u8>,
+    prev_row: Vec<u8>,
+    prev_addr: String,
+    detect_bom: bool,
+    repeats: Vec<String>,
+    squeezing: bool,
+    string_run: usize,
+    held: Vec<(Vec<u8>, String)>,
+    hidden: bool,
+    dumped: u64,
+    newline: bool,
+    emoji: bool,
+    after_base: bool,
+    printable: usize,
+    row_printable: usize,
+    struct_addr: usize,
+    struct_data: Vec<u8>,
+    annotations: Vec<String>,
+    inputs: usize,
+    stats: Stats,
+    finished: bool,
+}
+
+impl<W: io::Write> Printer<W> {
+    /// Create a new row printer with width and group counts, writing to any writer.
+    ///
+    /// # Errors
+    ///
+    /// - [`Error::Width`]: `width` is not in range `2..=4096`.
+    /// - [`Error::Grouping`]: `group` is greater than `width`.
+    /// - [`Error::EndianGroup`]: Little-endian groups are not 2, 4, or 8 bytes.
+    /// - [`Error::EscapeWithHex`]: Escapes are enabled with the hex column.
+    fn with_writer(
+        width: usize,
+        group: usize,
+        numeric: Numeric,
+        options: Options,
+        writer: W,
+    ) -> Result<Self, Error> {
+        // Round the width up to a whole number of groups.
+        let width = if options.align_width && group > 0 && group <= width {
+            width.next_multiple_of(group)
+        } else {
+            width
+        };
+
+        if width <= 1 || width > 4096 {
+            Err(Error::Width(width))
+        } else if group == 0 || group > width {
+            Err(Error::Grouping(group, width))
+        } else if options.endian == Endian::Little && !matches!(group, 2 | 4 | 8) {
+            Err(Error::EndianGroup(group))
+        } else if options.ascii_escape && !options.no_hex {
+            Err(Error::EscapeWithHex)
+        } else {
+            Ok(Self {
+                width,
+                group,
+                numeric,
+                max: padding(group, options.group_gap, width)
+                    - usize::from(options.no_leading_space),
+                table_width: options.table_width.unwrap_or(width),
+                options,
+                state: Default::default(),
+                writer,
+            })
+        }
+    }
+
+    /// Finish printing and flush any buffered output.
+    ///
+    /// Any partial row is printed first, and the totals of all inputs are returned. Calling this
+    /// again only returns the totals.
+    fn finish(&mut self) -> Result<Stats, Error> {
+        if !std::mem::replace(&mut self.state.finished, true) {
+            self.end_rows()?;
+            if self.options.concat && self.state.inputs > 0 {
+                self.print_final_offset()?;
+            }
+        }
+        self.writer.flush()?;
+
+        let mut stats = self.state.stats.clone();
+        stats.rows += self.state.rows;
+        stats.total_bytes = stats.counts_by_kind.iter().sum();
+
+        Ok(stats)
+    }
+
+    /// Pretty print a [`Reader`] as hex bytes.
+    ///
+    /// `size_hint` is the total number of bytes expected from the reader, if known.
+    fn pretty_hex<R>(&mut self, reader: &mut R, size_hint: Option<u64>) -> Result<(), Error>
+    where
+        R: Read,
+    {
+        let mut buf = [0; 4096];
+        let mut pending = 0;
+        let mut zeros = 0;
+        let mut progress = size_hint
+            .filter(|_| self.options.progress)
+            .map(Progress::new);
+        let max_bytes = self
+            .options
+            .limit_rows
+            .map_or(u64::MAX, |rows| rows.saturating_mul(self.width) as u64);
+        let mut limited = reader.by_ref().take(max_bytes);
+
+        loop {
+            // Read as much as possible, appending to buffer.
+            let size = limited.read(&mut buf[pending..])?;
+            let end = pending + size;
+            if let Some(progress) = &mut progress {
+                progress.update(size);
+            }
+            // Wait for enough bytes to tell the byte-order marks apart, e.g. a short read of
+            // `ff fe` may be followed by `00 00`.
+            if self.state.detect_bom {
+                if size > 0 && end < BOM_LEN {
+                    pending = end;
+                    continue;
+                }
+                self.state.detect_bom = false;
+                self.print_bom(&buf[..end])?;
+            }
+            if let Numeric::Auto = self.numeric {
+                self.numeric = Numeric::detect(&buf[..end]);
+            }
+
+            // Hold back an incomplete UTF-8 sequence until more bytes arrive.
+            let limit = if size == 0 {
+                end
+            } else {
+                end - grapheme::incomplete_len(&buf[..end])
+            };
+
+            // Print bytes grouped by classification.
+            let mut start = 0;
+            while start < limit {
+                let mut stop = limit;
+                if self.options.sparse {
+                    if self.state.column == 0 {
+                        // Count zeros at the start of the row, which may continue in the next read.
+                        let run = buf[start..limit].iter().take_while(|&&b| b == 0).count();
+                        zeros += run;
+                        start += run;
+                        if start == limit {
+                            break;
+                        }
+                        self.print_zeros(zeros)?;
+                        zeros = 0;
+                    }
+
+                    // Stop at the end of the row if the next row might start a hole.
+                    let row_end = start + self.width - self.state.column;
+                    if row_end < limit && buf[row_end] == 0 {
+                        stop = row_end;
+                    }
+                }
+
+                let gather: fn(&[u8], Numeric) -> Group<'_> = if self.options.numeric_tokens {
+                    Group::gather_tokens
+                } else {
+                    Group::gather
+                };
+                let mut group = match self.options.numeric_min_run {
+                    Some(min_run) => {
+                        Group::gather_min_run(&buf[start..stop], self.grouping(), min_run, gather)
+                    }
+                    None => gather(&buf[start..stop], self.grouping()),
+                };
+                if self.options.classify_utf8_strict {
+                    group = group.strict(self.state.after_base);
+                }
+                start += group.span.bytes.len();
+                self.format_group(group)?;
+            }
+
+            if size == 0 {
+                break;
+            }
+            buf.copy_within(limit..end, 0);
+            pending = end - limit;
+        }
+        let read = max_bytes - limited.limit();
+        self.state.dumped += read;
+
+        if let Some(progress) = progress {
+            progress.finish();
+        }
+        self.print_zeros(zeros)?;
+
+        // Concatenated inputs finish their last row in the next input.
+        if !self.options.concat {
+            self.end_rows()?;
+            self.print_final_offset()?;
+        }
+
+        // The rest of the input is counted from its size. Streams are not read to the end, since
+        // they may never end; a single byte shows whether they continue.
+        if self.options.limit_rows.is_some() {
+            let rest = match size_hint {
+                Some(size) => Some(size.saturating_sub(read)),
+                None if reader.read(&mut [0])? > 0 => None,
+                None => Some(0),
+            };
+            self.print_truncated(rest)?;
+        }
+
+        Ok(())
+    }
+
+    /// Format a classified group of bytes.
+    fn format_group(&mut self, group: Group<'_>) -> Result<(), Error> {
+        self.state.emoji = group.kind == Kind::Graphemes && group.span.is_emoji();
+        self.state.stats.counts_by_kind[group.kind.index()] += group.span.bytes.len() as u64;
+        self.state.after_base = matches!(
+            group.kind,
+            Kind::Printable | Kind::Numeric | Kind::Graphemes
+        );
+        let mut style = Style {
+            kind: group.kind,
+            marked: false,
+            overflow: false,
+        };
+        for (i, byte) in group.span.bytes.iter().enumerate() {
+            // Bytes within a group may be styled separately.
+            let byte_style = self.byte_style(group.kind, *byte);
+            if byte_style != style && !self.state.hex_group.is_empty() {
+                self.colorize_group(style)?;
+            }
+            style = byte_style;
+
+            // Write byte group separator.
+            let separator = self.hex_separator(self.state.column);
+            self.state.hex_group.push_str(separator);
+
+            // Write hex.
+            let cell = self.state.hex_group.len();
+            self.state.row.push(*byte);
+            self.collect_struct(*byte);
+            let hex = if self.options.uppercase {
+                format!("{byte:02X}")
+            } else {
+                format!("{byte:02x}")
+            };
+            if style.marked {
+                paint_marked(&mut self.state.hex_group, &hex)?;
+            } else if self.per_byte_hex() && self.options.color_columns.hex {
+                let (color, bold) = match self.options.color_scale {
+                    _ if self.options.collapse_zeros && *byte == 0 => {
+                        (Some(Color::Ansi(AnsiColor::BrightBlack)), false)
+                    }
+                    Some(scale) => (Some(scale.color(*byte)), false),
+                    None => {
+                        self.style_color(self.numeric_style(style, self.options.numeric_color.hex))
+                    }
+                };
+                paint(&mut self.state.hex_group, &hex, color, bold)?;
+            } else {
+                self.state.hex_group.push_str(&hex);
+            }
+            if self.options.endian == Endian::Little {
+                let hex = self.state.hex_group[cell..].to_string();
+                self.state.hex_cells.push(hex);
+            }
+
+            // Write character table, truncating it with an ellipsis when it is narrower than a row.
+            let column = self.state.column;
+            let cap = self.table_cap();
+            let east_asian = self.options.east_asian_width;
+            if let Some((group, gap)) = self
+                .table_grouping()
+                .filter(|_| column > 0 && column <= cap)
+            {
+                let separator = group_separator(group, gap, column);
+                self.state.table_group.push_str(separator);
+            }
+            let ch = match group.kind {
+                _ if column > cap => None,
+                _ if column == cap => Some('\u{2026}'),
+                Kind::Printable | Kind::Numeric => match self.options.table_case {
+                    Some(case) => Some(case.apply(*byte as char)),
+                    None => Some(*byte as char),
+                },
+                Kind::Graphemes => match group.span.as_char(i, column, self.width, east_asian) {
+                    // Double-wide characters that would cover the ellipsis are not shown.
+                    Char::Cluster(cluster)
+                        if column + 1 == cap && display_width(cluster, east_asian) > 1 =>
+                    {
+                        Some(' ')
+                    }
+                    Char::Cluster(cluster) => {
+                        self.state.table_group.write_str(cluster)?;
+                        None
+                    }
+                    Char::Placeholder => Some('.'),
+                    Char::Space => Some(' '),
+                    Char::Skip => None,
+                },
+                // Latin-1 `0x80..=0x9f` are control characters.
+                Kind::Invalid if self.options.invalid_as_latin1 && *byte >= 0xa0 => {
+                    Some(*byte as char)
+                }
+                Kind::Control if self.options.ascii_escape => match escape(*byte) {
+                    Some(escape) => {
+                        self.state.table_group.write_str(escape)?;
+                        None
+                    }
+                    None => Some('.'),
+                },
+                Kind::Control | Kind::Invalid => Some('.'),
+            };
+            if let Some(ch) = ch {
+                self.state.table_group.write_char(ch)?;
+            }
+
+            if matches!(group.kind, Kind::Printable | Kind::Numeric) {
+                self.state.row_printable += 1;
+            }
+
+            self.state.column += 1;
+            if self.state.column == self.width {
+                self.colorize_group(style)?;
+                self.print_row()?;
+            }
+        }
+
+        if self.state.column > 0 {
+            self.colorize_group(style)?;
+        }
+
+        Ok(())
+    }
+
+    // Get the number of columns in a row of `width` bytes: the label, hex bytes, and character
+    // table with its separators. Trailing columns like checksums are not included.
+    fn line_len(&self, width: usize) -> usize {
+        let mut label = self.options.with_filename.map_or(0, |width| width + 1);
+        let mut columns = Vec::new();
+        if self.options.line_numbers {
+            columns.push(6);
+        }
+        if let Some(size) = self.options.record_size {
+            columns.push(record_addr(0, size).len());
+        }
+        if !self.options.no_addr {
+            let addr = if self.options.raw_offsets {
+                "00000000"
+            } else {
+                "0000_0000_0000_0000"
+            };
+            columns.push(addr.len());
+        }
+        if !columns.is_empty() {
+            let separator = self.options.offset_separator.as_deref().unwrap_or(":");
+            label += columns.iter().sum::<usize>() + columns.len() - 1 + visible_width(separator);
+        }
+
+        let (open, close) = match &self.options.table_delimiters {
+            _ if self.options.no_separator => (" ", ""),
+            Some((open, close)) => (open.as_str(), close.as_str()),
+            None => (" | ", " |"),
+        };
+        let table_width = self.options.table_width.unwrap_or(width);
+
+        label
+            + if self.options.no_hex {
+                0
+            } else {
+                self.hex_width(width)
+            }
+            + visible_width(open)
+            + table_width
+            + self.table_separators(table_width)
+            + visible_width(close)
+    }
+
+    // Find the widest row, in multiples of the group size, that fits in `columns`, when the current
+    // width does not fit. The width never shrinks below one group (or two bytes).
+    fn fit_width(&self, columns: usize) -> Option<usize> {
+        if self.line_len(self.width) <= columns {
+            return None;
+        }
+
+        let min = self.group.max(2);
+        let mut width = (self.width - 1) / self.group * self.group;
+        while width > min && self.line_len(width) > columns {
+            width -= self.group;
+        }
+
+        Some(width.max(min)).filter(|&width| width < self.width)
+    }
+
+    // Change the number of bytes per row.
+    fn set_width(&mut self, width: usize) {
+        self.width = width;
+        self.max = self.hex_width(width);
+        self.table_width = self.options.table_width.unwrap_or(width);
+    }
+
+    // Get the numeric classification used for grouping bytes.
+    fn grouping(&self) -> Numeric {
+        if self.options.numeric_color_only {
+            Numeric::None
+        } else {
+            self.numeric
+        }
+    }
+
+    // Get the separator written before the hex byte in `column`.
+    fn hex_separator(&self, column: usize) -> &'static str {
+        if column == 0 && self.options.no_leading_space {
+            ""
+        } else {
+            group_separator(self.group, self.options.group_gap, column)
+        }
+    }
+
+    // Get the number of columns needed to print this many hex bytes, with their separators.
+    fn hex_width(&self, columns: usize) -> usize {
+        let leading = self.options.no_leading_space && columns > 0;
+
+        padding(self.group, self.options.group_gap, columns) - usize::from(leading)
+    }
+
+    // Get the number of spaces between groups in the character table for this many columns.
+    fn table_separators(&self, columns: usize) -> usize {
+        match self.table_grouping() {
+            // Padding includes a space before the first group, which the table does not have.
+            Some((group, gap)) if columns > 0 => padding(group, gap, columns) - columns * 2 - 1,
+            _ => 0,
+        }
+    }
+
+    // Get the group size and gap for spacing the character table, if it is grouped.
+    fn table_grouping(&self) -> Option<(usize, Option<usize>)> {
+        match self.options.table_group {
+            Some(group) => Some((group, None)),
+            None if self.options.mirror_groups => Some((self.group, self.options.group_gap)),
+            None => None,
+        }
+    }
+
+    // Get the number of table columns available for bytes before the truncation ellipsis.
+    fn table_cap(&self) -> usize {
+        if self.table_width < self.width {
+            self.table_width - 1
+        } else {
+            usize::MAX
+        }
+    }
+
+    // Colorize formatted group. Marked groups are highlighted instead.
+    fn colorize_group(&mut self, style: Style) -> Result<(), Error> {
+        if style.marked {
+            // Hex bytes are already highlighted individually.
+            self.state.hex.push_str(&self.state.hex_group);
+            paint_marked(&mut self.state.table, &self.state.table_group)?;
+        } else {
+            let numeric = self.options.numeric_color;
+            let (hex_color, hex_bold) = self.style_color(self.numeric_style(style, numeric.hex));
+            let (table_color, table_bold) =
+                self.style_color(self.numeric_style(style, numeric.ascii));
+
+            // Hex bytes may already be colored individually.
+            let columns = self.options.color_columns;
+            let hex_color = hex_color.filter(|_| columns.hex && !self.per_byte_hex());
+            let table_color = table_color.filter(|_| columns.ascii);
+            paint(
+                &mut self.state.hex,
+                &self.state.hex_group,
+                hex_color,
+                hex_bold,
+            )?;
+            paint(
+                &mut self.state.table,
+                &self.state.table_group,
+                table_color,
+                table_bold,
+            )?;
+        }
+
+        self.state.hex_group.clear();
+        self.state.table_group.clear();
+
+        Ok(())
+    }
+
+    // Get the style of a byte in a group of this kind.
+    fn byte_style(&self, kind: Kind, byte: u8) -> Style {
+        // Numeric bytes within printable groups are colored separately.
+        let kind = match kind {
+            Kind::Printable
+                if self.options.numeric_color_only
+                    && Kind::classify(byte, self.numeric) == Kind::Numeric =>
+            {
+                Kind::Numeric
+            }
+            _ => kind,
+        };
+        // Digits that are not numeric are outside of the radix.
+        let overflow = self.options.color_numeric_by_radix
+            && !matches!(self.numeric, Numeric::None)
+            && kind == Kind::Printable
+            && byte.is_ascii_digit();
+
+        Style {
+            kind,
+            marked: self.is_marked(),
+            overflow,
+        }
+    }
+
+    // Get the style of bytes in a column, where numeric bytes are printable unless `numeric`.
+    fn numeric_style(&self, style: Style, numeric: bool) -> Style {
+        if style.kind == Kind::Numeric && !numeric {
+            Style {
+                kind: Kind::Printable,
+                ..style
+            }
+        } else {
+            style
+        }
+    }
+
+    // Get the color and boldness for a style of bytes.
+    fn style_color(&self, style: Style) -> (Option<Color>, bool) {
+        if style.overflow {
+            (Some(Color::Ansi(AnsiColor::Red)), false)
+        } else {
+            self.kind_style(style.kind)
+        }
+    }
+
+    // Get the color and boldness for a kind of group.
+    fn kind_style(&self, kind: Kind) -> (Option<Color>, bool) {
+        let color = match kind {
+            // Only anomalies are colorized when inverted.
+            Kind::Printable | Kind::Numeric | Kind::Graphemes if self.options.invert => None,
+            Kind::Control => Some(AnsiColor::BrightYellow),
+            Kind::Printable => Some(AnsiColor::BrightGreen),
+            Kind::Numeric => Some(AnsiColor::BrightCyan),
+            Kind::Graphemes if self.state.emoji => Some(AnsiColor::Magenta),
+            Kind::Graphemes => Some(AnsiColor::Green),
+            Kind::Invalid => Some(AnsiColor::BrightRed),
+        };
+
+        (color.map(Color::Ansi), kind == Kind::Graphemes)
+    }
+
+    // Get the address of the next byte. Addresses wrap at the end of the address space.
+    fn next_addr(&self) -> usize {
+        self.state.addr.wrapping_add(self.state.column)
+    }
+
+    // Check if the next byte is within a marked address range, or in an even struct field.
+    fn is_marked(&self) -> bool {
+        let addr = self.next_addr();
+        let field = addr.checked_sub(self.state.struct_addr).and_then(|offset| {
+            let mut fields = self.options.fields.iter();
+            fields.position(|field| field.range().contains(&offset))
+        });
+
+        self.options.marks.iter().any(|mark| mark.contains(&addr))
+            || field.is_some_and(|i| i % 2 == 0)
+    }
+
+    // Collect the next byte when it is part of the struct, and annotate the row with each field
+    // it completes.
+    fn collect_struct(&mut self, byte: u8) {
+        let addr = self.next_addr();
+        let data = &mut self.state.struct_data;
+        // Bytes skipped by sparse holes stop the collection.
+        if addr.checked_sub(self.state.struct_addr) != Some(data.len()) {
+            return;
+        }
+        let Some(last) = self.options.fields.last() else {
+            return;
+        };
+        if data.len() >= last.range().end {
+            return;
+        }
+
+        data.push(byte);
+        if let Some(field) = self
+            .options
+            .fields
+            .iter()
+            .find(|f| f.range().end == data.len())
+        {
+            let value = field.decode(&data[field.range()]);
+            let note = format!("{}={value}", field.name.bright_black());
+            self.state.annotations.push(note);
+        }
+    }
+
+    // Check if hex bytes are colored individually, instead of by group. Swapped bytes are colored
+    // individually so they keep their colors when they are reordered.
+    fn per_byte_hex(&self) -> bool {
+        self.options.color_scale.is_some()
+            || self.options.collapse_zeros
+            || self.options.endian == Endian::Little
+    }
+
+    // Rebuild the hex column of the row with the bytes of each group reversed.
+    fn swap_hex(&mut self, missing: usize) {
+        let cells = std::mem::take(&mut self.state.hex_cells);
+        self.state.hex.clear();
+        let groups = cells.len().div_ceil(self.group);
+        for (i, group) in cells.chunks(self.group).enumerate() {
+            for (j, hex) in group.iter().rev().enumerate() {
+                let separator = self.hex_separator(i * self.group + j);
+                self.state.hex.push_str(separator);
+                if j == 0 && i + 1 == groups {
+                    self.state.hex.push_str(&"  ".repeat(missing));
+                }
+                self.state.hex.push_str(hex);
+            }
+        }
+    }
+
+    // Get the number of bytes that `--pad-last-group` pads the last group of the row with: the
+    // bytes missing from it, compared with the same group in a full row.
+    fn missing_in_last_group(&self) -> usize {
+        let column = self.state.column;
+        if !self.options.pad_last_group || column == 0 {
+            return 0;
+        }
+        let start = (column - 1) / self.group * self.group;
+
+        self.group
+            .min(self.width.saturating_sub(start))
+            .saturating_sub(column - start)
+    }
+
+    /// Start a new input with the given name, optionally printing a header for it.
+    ///
+    /// The header is an OSC 8 hyperlink when `link` is provided. Addresses start at `addr` when
+    /// provided, otherwise they continue from the previous input. A byte-order mark is only
+    /// detected when the input is read from `start`.
+    ///
+    /// Concatenated inputs after the first only mark the row where they start.
+    fn start_input(
+        &mut self,
+        name: String,
+        header: bool,
+        link: Option<String>,
+        addr: Option<usize>,
+        start: bool,
+    ) -> Result<(), Error> {
+        self.state.inputs += 1;
+        self.state.name = name;
+        self.state.detect_bom = self.options.show_bom && start;
+        if self.options.concat && self.state.inputs > 1 {
+            if !self.options.quiet {
+                let addr = self.next_addr();
+                let note = format!("[{} at {addr:#x}]", self.state.name);
+                self.state.annotations.push(note.bright_black().to_string());
+            }
+
+            return Ok(());
+        }
+
+        if let Some(addr) = addr {
+            self.state.addr = addr;
+        }
+        self.state.header = header;
+        self.state.link = link;
+        self.state.stats.rows += self.state.rows;
+        self.state.rows = 0;
+        self.state.printable = 0;
+        self.state.struct_addr = self.state.addr;
+        self.state.struct_data.clear();
+        self.state.prev_addr.clear();
+        self.state.after_base = false;
+        self.print_header()
+    }
+
+    // Print the current input header.
+    fn print_header(&mut self) -> Result<(), Error> {
+        if !self.state.header || self.options.quiet {
+            return Ok(());
+        }
+
+        let name = self.state.name.yellow();
+        let header = match &self.state.link {
+            Some(link) => format!("\x1b]8;;{link}\x1b\\{name}\x1b]8;;\x1b\\"),
+            None => name.to_string(),
+        };
+        self.print_meta(&format!("\n[{header}]"))
+    }
+
+    // Note the zeros trimmed from the end of the input.
+    fn print_trimmed(&mut self, count: u64) -> Result<(), Error> {
+        if count == 0 {
+            return Ok(());
+        }
+
+        let note = format!("[trimmed {count:#x} trailing zero bytes]");
+        self.print_meta(&note.bright_black().to_string())
+    }
+
+    // Note the bytes left in an input after the row limit, or only that it continues when the
+    // count is unknown.
+    fn print_truncated(&mut self, count: Option<u64>) -> Result<(), Error> {
+        let note = match count {
+            Some(0) => return Ok(()),
+            Some(count) => format!("... [{count:#x} more bytes]"),
+            None => "...".to_string(),
+        };
+        self.print_meta(&note.bright_black().to_string())
+    }
+
+    // Print a line of meta output, unless it is suppressed.
+    fn print_meta(&mut self, line: &str) -> Result<(), Error> {
+        if self.options.quiet {
+            Ok(())
+        } else if self.options.meta_stderr {
+            let line = if stderr_color() {
+                line.to_string()
+            } else {
+                strip_colors(line)
+            };
+            Ok(writeln!(io::stderr(), "{line}")?)
+        } else {
+            self.print_line(line)
+        }
+    }
+
+    // Count a new row, and return its label with the file name, line number, and address.
+    fn start_row(&mut self) -> Result<String, Error> {
+        // Repeat the header periodically.
+        if let Some(rows) = self.options.repeat_header {
+            if self.state.rows > 0 && self.state.rows % rows == 0 {
+                self.print_header()?;
+            }
+        }
+        self.state.rows += 1;
+
+        self.row_label(true)
+    }
+
+    // Print a line with only the label at the number of bytes dumped since the last final offset,
+    // like the file size at the end of `hexdump`. Addresses of later inputs still continue from
+    // the end of the last row.
+    fn print_final_offset(&mut self) -> Result<(), Error> {
+        let dumped = std::mem::take(&mut self.state.dumped);
+        if self.options.final_offset {
+            let addr = std::mem::replace(&mut self.state.addr, dumped as usize);
+            let label = self.row_label(false)?;
+            self.state.addr = addr;
+            self.print_line(&label)?;
+        }
+
+        Ok(())
+    }
+
+    // Get the label for the row at the current address. Lines that are not rows have a blank line
+    // number.
+    fn row_label(&mut self, line_number: bool) -> Result<String, Error> {
+        let color = |color| (!self.options.no_color_addr).then_some(Color::Ansi(color));
+        let mut label = String::new();
+        if let Some(width) = self.options.with_filename {
+            let name_width = unicode_display_width::width(&self.state.name) as usize;
+            let pad = " ".repeat(width - name_width);
+            paint(
+                &mut label,
+                &self.state.name,
+                color(AnsiColor::BrightBlack),
+                false,
+            )?;
+            write!(label, ":{pad}")?;
+        }
+        let mut columns = Vec::new();
+        if self.options.line_numbers && line_number {
+            let mut column = String::new();
+            let text = format!("{:>6}", self.state.rows);
+            paint(&mut column, &text, color(AnsiColor::Blue), false)?;
+            columns.push(column);
+        } else if self.options.line_numbers {
+            columns.push(" ".repeat(6));
+        }
+        if let Some(size) = self.options.record_size {
+            let mut column = String::new();
+            let text = record_addr(self.state.addr, size);
+            paint(&mut column, &text, color(AnsiColor::Blue), false)?;
+            columns.push(column);
+        }
+        if !self.options.no_addr {
+            columns.push(self.pretty_addr());
+        }
+        if !columns.is_empty() {
+            let separator = self.options.offset_separator.as_deref().unwrap_or(":");
+            label.push_str(&columns.join(" "));
+            paint(&mut label, separator, self.options.punct_color, false)?;
+        }
+
+        Ok(label)
+    }
+
+    // Print any remaining row and held back repeated rows.
+    fn end_rows(&mut self) -> Result<(), Error> {
+        if self.state.column > 0 {
+            self.print_row()?;
+        }
+        if self.options.min_string.is_some() {
+            self.end_strings()?;
+        }
+        self.flush_repeats()?;
+        self.state.prev_row.clear();
+
+        Ok(())
+    }
+
+    // Print a notice on stderr if the input starts with a byte-order mark.
+    fn print_bom(&self, bytes: &[u8]) -> Result<(), Error> {
+        if let Some(encoding) = bom_encoding(bytes).filter(|_| !self.options.quiet) {
+            writeln!(
+                io::stderr(),
+                "{}: {:?} starts with a {encoding} byte-order mark",
+                stderr_label("Note", AnsiColor::BrightCyan),
+                self.state.name,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Print a run of zero bytes at the start of a row, collapsing whole rows into a hole.
+    fn print_zeros(&mut self, count: usize) -> Result<(), Error> {
+        const ZEROS: [u8; 4096] = [0; 4096];
+
+        let mut remaining = count;
+        if count >= SPARSE_HOLE {
+            self.flush_repeats()?;
+            self.state.prev_row.clear();
+
+            let hole = count - count % self.width;
+            let label = self.start_row()?;
+            let note = format!("[sparse hole: {hole:#x} bytes]");
+            self.print_line(&format!("{label} {}", note.bright_black()))?;
+
+            self.state.addr = self.state.addr.wrapping_add(hole);
+            self.state.stats.counts_by_kind[Kind::Control.index()] += hole as u64;
+            remaining -= hole;
+        }
+
+        while remaining > 0 {
+            let size = remaining.min(ZEROS.len());
+            self.format_group(Group::gather(&ZEROS[..size], self.numeric))?;
+            remaining -= size;
+        }
+
+        Ok(())
+    }
+
+    // Print a complete row.
+    fn print_row(&mut self) -> Result<(), Error> {
+        let missing = self.missing_in_last_group();
+        if self.options.endian == Endian::Little {
+            self.swap_hex(missing);
+        } else {
+            self.state.hex.push_str(&"  ".repeat(missing));
+        }
+        let label = self.start_row()?;
+        let hex_width = self.hex_width(self.state.column + missing);
+        if self.options.debug_layout {
+            self.check_layout(&label, hex_width)?;
+        }
+
+        // The table only needs padding when it is followed by a closing separator.
+        let (open, close) = match &self.options.table_delimiters {
+            _ if self.options.no_separator => (" ", ""),
+            Some((open, close)) => (open.as_str(), close.as_str()),
+            None => (" | ", " |"),
+        };
+        let table_pad = if close.is_empty() {
+            0
+        } else if self.options.ascii_escape {
+            // Every byte takes at most two columns, so the closing separator is aligned past that.
+            let full = self.table_width * 2 + self.table_separators(self.table_width);
+            full.saturating_sub(visible_width(&self.state.table))
+        } else {
+            let cap = self.table_cap();
+            let columns = if self.state.column > cap {
+                self.table_width
+            } else {
+                self.state.column
+            };
+            self.table_width + self.table_separators(self.table_width)
+                - columns
+                - self.table_separators(columns)
+        };
+        let mut line = label;
+        if !self.options.no_hex {
+            write!(
+                line,
+                "{hex}{hex_pad}",
+                hex = self.state.hex,
+                hex_pad = " ".repeat(self.max - hex_width),
+            )?;
+        }
+        paint(&mut line, open, self.options.separator_color, false)?;
+        write!(
+            line,
+            "{table}{table_pad}",
+            table = self.state.table,
+            table_pad = " ".repeat(table_pad),
+        )?;
+        paint(&mut line, close, self.options.separator_color, false)?;
+        if self.options.byte_count_column {
+            write!(line, " {}", format!("{:>8}", self.state.printable).blue())?;
+        }
+        self.state.printable += std::mem::take(&mut self.state.row_printable);
+        if let Some(checksum) = self.options.group_checksum {
+            // Incomplete groups at the end of the input are skipped.
+            for group in self.state.row.chunks_exact(self.group) {
+                let sum = checksum.compute(group);
+                let sum = if self.options.uppercase {
+                    format!("{sum:02X}")
+                } else {
+                    format!("{sum:02x}")
+                };
+                write!(line, " {}", sum.bright_black())?;
+            }
+        }
+        if let Some(hash) = self.options.row_hash {
+            let hash = hash.compute(&self.state.row);
+            let hash = if self.options.uppercase {
+                format!("{hash:08X}")
+            } else {
+                format!("{hash:08x}")
+            };
+            write!(line, " {}", hash.bright_black())?;
+        }
+        for note in self.state.annotations.drain(..) {
+            write!(line, " {note}")?;
+        }
+
+        let line = match self.options.min_string {
+            Some(min) => self.match_strings(line, min)?,
+            None => Some(line),
+        };
+        if let Some(line) = line {
+            let row = std::mem::take(&mut self.state.row);
+            self.print_data_row(&row, line)?;
+            self.state.row = row;
+        }
+
+        self.state.column = 0;
+        self.state.addr = self.state.addr.wrapping_add(self.width);
+        self.state.hex.clear();
+        self.state.table.clear();
+        self.state.row.clear();
+
+        Ok(())
+    }
+
+    // Print a ruler above the first row of each input, and check that the hex column has the
+    // width expected from `padding`, which puts the table separator in the same column on every row.
+    fn check_layout(&mut self, label: &str, hex_width: usize) -> Result<(), Error> {
+        let label_width = visible_width(label);
+        if self.state.rows == 1 {
+            let mut ruler = " ".repeat(label_width);
+            for column in 0..self.width {
+                ruler.push_str(self.hex_separator(column));
+                write!(ruler, "{:02x}", column % 256)?;
+            }
+            self.print_line(&ruler.bright_black().to_string())?;
+        }
+
+        let expected = label_width + self.max;
+        let actual = label_width + visible_width(&self.state.hex) + self.max - hex_width;
+        if actual != expected {
+            writeln!(
+                io::stderr(),
+                "{}: Table separator is in column {actual}, expected column {expected}",
+                stderr_label("Layout", AnsiColor::BrightRed),
+            )?;
+        }
+        debug_assert_eq!(actual, expected, "Table separator is misaligned");
+
+        Ok(())
+    }
+
+    // Print the line of a row, or hold it back when rows are squeezed and it repeats the previous
+    // row.
+    fn print_data_row(&mut self, row: &[u8], line: String) -> Result<(), Error> {
+        match self.options.squeeze {
+            Some(min_run) if row == self.state.prev_row => self.squeeze_row(line, min_run),
+            _ => {
+                self.flush_repeats()?;
+                self.print_line(&line)?;
+                self.state.prev_row.clear();
+                self.state.prev_row.extend_from_slice(row);
+
+                Ok(())
+            }
+        }
+    }
+
+    // Hold back a row that repeats the previous row, and replace the run with `*` once it is long
+    // enough. The `*` line counts as one row.
+    fn squeeze_row(&mut self, line: String, min_run: usize) -> Result<(), Error> {
+        if self.state.squeezing {
+            self.state.rows -= 1;
+            return Ok(());
+        }
+
+        self.state.repeats.push(line);
+        if self.state.repeats.len() + 1 >= min_run {
+            self.print_line("*")?;
+            self.state.rows -= self.state.repeats.len() - 1;
+            self.state.repeats.clear();
+            self.state.squeezing = true;
+        }
+
+        Ok(())
+    }
+
+    // Check if the current row has part of a printable run of at least `min` bytes, and return its
+    // line if it should be printed.
+    //
+    // Rows ending in a shorter run are held back until the run reaches `min` bytes in a later row,
+    // or ends. Rows that are not printed are collapsed into a `...` line before the next printed
+    // row.
+    fn match_strings(&mut self, line: String, min: usize) -> Result<Option<String>, Error> {
+        let mut run = self.state.string_run;
+        let mut matched = false;
+        // Whether the run continued from earlier rows reaches `min` bytes.
+        let mut carried = false;
+        let mut printable = true;
+        for &byte in &self.state.row {
+            if Kind::classify(byte, Numeric::None) == Kind::Printable {
+                run += 1;
+                matched |= run >= min;
+                carried |= printable && run >= min;
+            } else {
+                run = 0;
+                printable = false;
+            }
+        }
+        self.state.string_run = run;
+
+        // Held rows are kept while their run continues through this row.
+        let continues = carried || (printable && run > 0);
+        let mut held = std::mem::take(&mut self.state.held);
+        if !continues && !held.is_empty() {
+            held.clear();
+            self.state.hidden = true;
+        }
+        if matched {
+            if std::mem::take(&mut self.state.hidden) {
+                self.flush_repeats()?;
+                self.print_meta("...")?;
+                self.state.prev_row.clear();
+            }
+            // Held rows may repeat each other, so they are squeezed like any other row.
+            for (row, line) in held {
+                self.print_data_row(&row, line)?;
+            }
+
+            Ok(Some(line))
+        } else {
+            if run > 0 {
+                held.push((self.state.row.clone(), line));
+            } else {
+                self.state.hidden = true;
+            }
+            self.state.held = held;
+
+            Ok(None)
+        }
+    }
+
+    // Collapse rows held back by `--min-string` at the end of an input, where runs end.
+    fn end_strings(&mut self) -> Result<(), Error> {
+        let held = std::mem::take(&mut self.state.held);
+        if std::mem::take(&mut self.state.hidden) || !held.is_empty() {
+            self.flush_repeats()?;
+            self.print_meta("...")?;
+        }
+        self.state.string_run = 0;
+
+        Ok(())
+    }
+
+    // Print any repeated rows that were held back without reaching the minimum run length.
+    fn flush_repeats(&mut self) -> Result<(), Error> {
+        for line in std::mem::take(&mut self.state.repeats) {
+            self.print_line(&line)?;
+        }
+        self.state.squeezing = false;
+
+        Ok(())
+    }
+
+    // Print a line of data. Without a final newline, each newline is deferred until the next line.
+    fn print_line(&mut self, line: &str) -> Result<(), Error> {
+        if self.options.no_final_newline {
+            let newline = if self.state.newline { "\n" } else { "" };
+            self.state.newline = true;
+            write!(self.writer, "{newline}{line}")?;
+        } else {
+            writeln!(self.writer, "{line}")?;
+        }
+
+        Ok(())
+    }
+
+    // Return the address as a formatted and colorized string.
+    fn pretty_addr(&mut self) -> String {
+        let a = self.state.addr >> 48;
+        let b = (self.state.addr >> 32) & 0xffff;
+        let c = (self.state.addr >> 16) & 0xffff;
+        let d = self.state.addr & 0xffff;
+
+        let addr = if self.options.raw_offsets && self.options.uppercase {
+            format!("{:08X}", self.state.addr)
+        } else if self.options.raw_offsets {
+            format!("{:08x}", self.state.addr)
+        } else if self.options.uppercase {
+            format!("{:04X}_{:04X}_{:04X}_{:04X}", a, b, c, d)
+        } else {
+            format!("{:04x}_{:04x}_{:04x}_{:04x}", a, b, c, d)
+        };
+        let repeats = if self.options.dim_addr_repeats {
+            common_prefix(&self.state.prev_addr, &addr)
+        } else {
+            0
+        };
+        let pretty = if self.options.no_color_addr {
+            addr.clone()
+        } else if repeats > 0 {
+            let (same, changed) = addr.split_at(repeats);
+            format!("{}{}", same.bright_blue().dimmed(), changed.bright_blue())
+        } else {
+            addr.bright_blue().to_string()
+        };
+        self.state.prev_addr = addr;
+
+        pretty
+    }
+}
+
+/// Compare the bytes reversed from a dump with the input, stopping at the first difference.
+fn verify<D, I>(dump: &mut D, input: &mut I) -> Result<(), Error>
+where
+    D: Read,
+    I: Read,
+{
+    let describe = |byte: Option<&u8>| match byte {
+        Some(byte) => format!("{byte:#04x}"),
+        None => "no more bytes".to_string(),
+    };
+    let mut expected = [0; 4096];
+    let mut actual = [0; 4096];
+    let mut offset = 0;
+
+    loop {
+        let size = read_full(dump, &mut expected)?;
+        let input_size = read_full(input, &mut actual)?;
+        let expected = &expected[..size];
+        let actual = &actual[..input_size];
+
+        if expected != actual {
+            let i = expected
+                .iter()
+                .zip(actual)
+                .take_while(|(a, b)| a == b)
+                .count();
+            return Err(Error::Mismatch(
+                format!("{:#x}", offset + i),
+                describe(expected.get(i)),
+                describe(actual.get(i)),
+            ));
+        }
+        if size == 0 {
+            return Ok(());
+        }
+        offset += size;
+    }
+}
+
+/// Apply byte transforms to a reader, if there are any, starting at `offset` in the input.
+fn transform<'a>(
+    reader: Box<dyn Read + 'a>,
+    transforms: &[Transform],
+    offset: u64,
+) -> Box<dyn Read + 'a> {
+    if transforms.is_empty() {
+        reader
+    } else {
+        Box::new(Transformed::new(reader, transforms.to_vec(), offset))
+    }
+}
+
+/// Read the byte after discarding `skip` bytes from a reader, or `None` at the end of the input.
+fn byte_at<R: Read>(reader: &mut R, skip: u64) -> io::Result<Option<u8>> {
+    io::copy(&mut reader.by_ref().take(skip), &mut io::sink())?;
+    let mut byte = [0];
+
+    Ok((read_full(reader, &mut byte)? == 1).then_some(byte[0]))
+}
+
+/// Read until the buffer is full or the reader is exhausted.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut size = 0;
+    while size < buf.len() {
+        match reader.read(&mut buf[size..])? {
+            0 => break,
+            n => size += n,
+        }
+    }
+
+    Ok(size)
+}
+
+/// Format an address as a record index and a hex offset within the record, like `3:0x04`.
+///
+/// The index is right-aligned, and the offset is padded to the number of digits in the record size.
+fn record_addr(addr: usize, size: usize) -> String {
+    let digits = format!("{:x}", size - 1).len().max(2);
+
+    format!("{:>6}:0x{:0digits$x}", addr / size, addr % size)
+}
+
+/// Get the encoding named by a byte-order mark at the start of a byte slice.
+fn bom_encoding(bytes: &[u8]) -> Option<&'static str> {
+    // UTF-32 is checked first, because its little-endian mark starts with the UTF-16 mark.
+    const BOMS: [(&[u8], &str); 5] = [
+        (b"\xef\xbb\xbf", "UTF-8"),
+        (b"\xff\xfe\0\0", "UTF-32LE"),
+        (b"\0\0\xfe\xff", "UTF-32BE"),
+        (b"\xff\xfe", "UTF-16LE"),
+        (b"\xfe\xff", "UTF-16BE"),
+    ];
+
+    BOMS.iter()
+        .find(|(bom, _)| bytes.starts_with(bom))
+        .map(|&(_, encoding)| encoding)
+}
+
+/// Get the length of the common prefix of two ASCII strings.
+fn common_prefix(a: &str, b: &str) -> usize {
+    a.bytes().zip(b.bytes()).take_while(|(a, b)| a == b).count()
+}
+
+/// Append text to a string with an optional color.
+fn paint(output: &mut String, text: &str, color: Option<Color>, bold: bool) -> fmt::Result {
+    match color {
+        Some(color) if bold => write!(output, "{}", text.fg(color).bold()),
+        Some(color) => write!(output, "{}", text.fg(color)),
+        None => output.write_str(text),
+    }
+}
+
+/// Check if messages on stderr are colored.
+///
+/// HTML output forces colors on, since the dump is translated from them, but messages on stderr
+/// still follow the environment.
+fn stderr_color() -> bool {
+    colorz::mode::Mode::from_env() != Some(colorz::mode::Mode::Never)
+}
+
+/// Color the label of a message on stderr, like `Warning`.
+fn stderr_label(label: &str, color: AnsiColor) -> String {
+    if stderr_color() {
+        label.fg(color).to_string()
+    } else {
+        label.to_string()
+    }
+}
+
+/// Write highlighted text, with reversed and underlined style.
+fn paint_marked(output: &mut String, text: &str) -> fmt::Result {
+    write!(output, "{}", text.reverse().underline())
+}
+
+/// Get the C escape for a control character, if it has a common one.
+fn escape(byte: u8) -> Option<&'static str> {
+    match byte {
+        b'\0' => Some("\\0"),
+        b'\t' => Some("\\t"),
+        b'\n' => Some("\\n"),
+        b'\r' => Some("\\r"),
+        _ => None,
+    }
+}
+
+/// Get the separator written before the hex byte in `column`: a space at the start of each group,
+/// with an extra space between every `gap` groups.
+fn group_separator(group: usize, gap: Option<usize>, column: usize) -> &'static str {
+    if column % group != 0 {
+        ""
+    } else if gap.is_some_and(|gap| column > 0 && (column / group) % gap == 0) {
+        "  "
+    } else {
+        " "
+    }
+}
+
+/// Get the display width of a string, ignoring ANSI escape sequences.
+fn visible_width(s: &str) -> usize {
+    let mut width = 0;
+    let mut rest = s;
+    while let Some(start) = rest.find('\x1b') {
+        width += unicode_display_width::width(&rest[..start]) as usize;
+        rest = &rest[start..];
+        let end = rest.find('m').map_or(rest.len(), |end| end + 1);
+        rest = &rest[end..];
+    }
+
+    width + unicode_display_width::width(rest) as usize
+}
+
+/// Remove ANSI escape sequences from a string.
+fn strip_colors(s: &str) -> String {
+    let mut text = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('\x1b') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find('m').map_or(rest.len(), |end| end + 1);
+        rest = &rest[end..];
+    }
+    text.push_str(rest);
+
+    text
+}
+
+/// Get the terminal width from `$COLUMNS`.
+///
+/// `hd` forbids unsafe code, so the terminal is not queried directly.
+fn terminal_columns() -> Option<usize> {
+    std::env::var("COLUMNS").ok()?.trim().parse().ok()
+}
+
+/// Compute the number of columns needed to print a byte slice of the given length as grouped hex
+/// bytes, with an extra space between every `gap` groups.
+fn padding(group: usize, gap: Option<usize>, length: usize) -> usize {
+    let groups = length.div_ceil(group);
+    let gaps = gap.map_or(0, |gap| groups.saturating_sub(1) / gap);
+
+    length * 2 + groups + gaps
+}
+
+/// Extract a hex address from a file name with a pattern.
+///
+/// The address is taken from the first capture group, or the whole match if the pattern has no
+/// capture groups.
+fn addr_from_name(pattern: &Regex, name: &str) -> Option<usize> {
+    let captures = pattern.captures(name)?;
+    let digits = captures.get(1).or_else(|| captures.get(0))?.as_str();
+    let digits = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+        .unwrap_or(digits);
+
+    usize::from_str_radix(digits, 16).ok()
+}
+
+/// Add an offset into the input to an address, failing if it does not fit in the address space.
+fn offset_addr(addr: usize, offset: u64) -> Result<usize, Error> {
+    usize::try_from(offset)
+        .ok()
+        .and_then(|offset| addr.checked_add(offset))
+        .ok_or(Error::AddrOverflow(addr, offset))
+}
+
+/// Create a `file://` URI for a path.
+fn file_uri(path: &Path) -> Option<String> {
+    let path = std::fs::canonicalize(path).ok()?;
+
+    Some(format!("file://{}", percent_encode_path(path.to_str()?)))
+}
+
+/// Percent-encode a path for use in a URI.
+fn percent_encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create a printer which discards its output.
+    fn printer(
+        width: usize,
+        group: usize,
+        numeric: Numeric,
+        options: Options,
+    ) -> Result<Printer<io::Sink>, Error> {
+        Printer::with_writer(width, group, numeric, options, io::sink())
+    }
+
+    /// Dump bytes with colors disabled, returning the output.
+    fn dump(input: &[u8], width: usize, group: usize, options: Options) -> String {
+        colorz::mode::set_coloring_mode(colorz::mode::Mode::Never);
+
+        let mut output = Vec::new();
+        let mut printer =
+            Printer::with_writer(width, group, Numeric::Decimal, options, &mut output).unwrap();
+        printer.pretty_hex(&mut &input[..], None).unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_finish_stats() {
+        let mut output = Vec::new();
+        let mut printer =
+            Printer::with_writer(4, 2, Numeric::Decimal, Options::default(), &mut output).unwrap();
+        printer
+            .start_input("a".to_string(), false, None, None, true)
+            .unwrap();
+        printer.pretty_hex(&mut &b"ab12\n\xff"[..], None).unwrap();
+        printer
+            .start_input("b".to_string(), false, None, None, true)
+            .unwrap();
+        printer.pretty_hex(&mut "€".as_bytes(), None).unwrap();
+
+        let stats = printer.finish().unwrap();
+        assert_eq!(
+            stats,
+            Stats {
+                total_bytes: 9,
+                rows: 3,
+                counts_by_kind: [2, 2, 1, 3, 1],
+            }
+        );
+
+        // Finishing again returns the same totals without printing anything.
+        assert_eq!(printer.finish().unwrap(), stats);
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 3);
+    }
+
+    #[test]
+    fn test_golden_rows() {
+        assert_eq!(
+            dump(b"hello, world!\n\x00\xff", 16, 2, Options::default()),
+            "0000_0000_0000_0000: 6865 6c6c 6f2c 2077 6f72 6c64 210a 00ff | hello, world!... |\n"
+        );
+
+        // The final row is padded to align the table.
+        assert_eq!(
+            dump(b"0123456789abc", 8, 4, Options::default()),
+            "\
+                0000_0000_0000_0000: 30313233 34353637 | 01234567 |\n\
+                0000_0000_0000_0008: 38396162 63       | 89abc    |\n"
+        );
+
+        // Double-wide characters wrap to the next row.
+        assert_eq!(
+            dump("abc\u{4e2d}".as_bytes(), 4, 1, Options::default()),
+            "\
+                0000_0000_0000_0000: 61 62 63 e4 | abc  |\n\
+                0000_0000_0000_0004: b8 ad       | \u{4e2d}   |\n"
+        );
+    }
+
+    #[test]
+    fn test_golden_options() {
+        let options = Options {
+            uppercase: true,
+            no_separator: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"\xab\xcd\xef", 4, 2, options),
+            "0000_0000_0000_0000: ABCD EF   ...\n"
+        );
+
+        let options = Options {
+            line_numbers: true,
+            no_addr: true,
+            group_gap: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcdefghij", 8, 2, options),
+            "\
+                \x20    1: 6162 6364  6566 6768 | abcdefgh |\n\
+                \x20    2: 696a                 | ij       |\n"
+        );
+    }
+
+    #[test]
+    fn test_bom_encoding() {
+        assert_eq!(bom_encoding(b"\xef\xbb\xbfhello"), Some("UTF-8"));
+        assert_eq!(bom_encoding(b"\xff\xfeh\0"), Some("UTF-16LE"));
+        assert_eq!(bom_encoding(b"\xfe\xff\0h"), Some("UTF-16BE"));
+        assert_eq!(bom_encoding(b"\xff\xfe\0\0h\0\0\0"), Some("UTF-32LE"));
+        assert_eq!(bom_encoding(b"\0\0\xfe\xff\0\0\0h"), Some("UTF-32BE"));
+        assert_eq!(bom_encoding(b"\xef\xbb"), None);
+        assert_eq!(bom_encoding(b"hello"), None);
+        assert_eq!(bom_encoding(b""), None);
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        assert_eq!(
+            common_prefix("0000_0000_0000_0010", "0000_0000_0000_0020"),
+            17
+        );
+        assert_eq!(
+            common_prefix("0000_0000_0000_fff0", "0000_0000_0001_0000"),
+            13
+        );
+        assert_eq!(common_prefix("", "0000_0000_0000_0000"), 0);
+        assert_eq!(common_prefix("1234", "1234"), 4);
+    }
+
+    #[test]
+    fn test_record_addr() {
+        assert_eq!(record_addr(0, 16), "     0:0x00");
+        assert_eq!(record_addr(0x34, 16), "     3:0x04");
+        assert_eq!(record_addr(0x1234, 0x1000), "     1:0x234");
+
+        // Rows that span record boundaries show the record of their first byte.
+        let options = Options {
+            record_size: Some(20),
+            no_addr: true,
+            ..Default::default()
+        };
+        let output = dump(&[b'a'; 40], 16, 2, options);
+        let labels: Vec<_> = output.lines().map(|line| &line[..12]).collect();
+        assert_eq!(labels, ["     0:0x00:", "     0:0x10:", "     1:0x0c:"]);
+    }
+
+    #[test]
+    fn test_fit_width() {
+        let fit = |width, group, columns| {
+            let printer = printer(width, group, Numeric::Decimal, Default::default()).unwrap();
+            printer.fit_width(columns)
+        };
+
+        // The default layout is 81 columns wide.
+        assert_eq!(fit(16, 2, 81), None);
+        assert_eq!(fit(16, 2, 80), Some(14));
+        assert_eq!(fit(32, 2, 80), Some(14));
+        assert_eq!(fit(32, 2, 120), Some(26));
+        assert_eq!(fit(32, 2, 200), None);
+        assert_eq!(fit(32, 4, 80), Some(16));
+        assert_eq!(fit(32, 2, 20), Some(2));
+        assert_eq!(fit(32, 8, 20), Some(8));
+        assert_eq!(fit(2, 2, 20), None);
+
+        let mut printer = printer(32, 2, Numeric::Decimal, Default::default()).unwrap();
+        printer.set_width(14);
+        assert_eq!(printer.line_len(printer.width), 74);
+        let options = Options {
+            no_addr: true,
+            table_width: Some(8),
+            ..Default::default()
+        };
+        let printer = self::printer(32, 4, Numeric::Decimal, options).unwrap();
+        assert_eq!(printer.fit_width(80), Some(28));
+    }
+
+    #[test]
+    fn test_east_asian_width() {
+        let wide = Options {
+            east_asian_width: EastAsianWidth::Wide,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump("1\u{b0}C".as_bytes(), 4, 2, Options::default()),
+            "0000_0000_0000_0000: 31c2 b043 | 1\u{b0} C |\n"
+        );
+        assert_eq!(
+            dump("1\u{b0}C".as_bytes(), 4, 2, wide),
+            "0000_0000_0000_0000: 31c2 b043 | 1\u{b0}C |\n"
+        );
+        assert!(matches!(
+            "full".parse::<EastAsianWidth>(),
+            Err(Error::UnknownEastAsianWidth(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_numeric_color() {
+        let columns = |s| parse_numeric_color(s).map(|columns| (columns.hex, columns.ascii));
+
+        assert_eq!(columns("ascii").unwrap(), (false, true));
+        assert_eq!(columns("HEX").unwrap(), (true, false));
+        assert_eq!(columns("both").unwrap(), (true, true));
+        assert!(matches!(
+            columns("none"),
+            Err(Error::UnknownNumericColor(_))
+        ));
+    }
+
+    #[test]
+    fn test_numeric_color_only() {
+        let options = Options {
+            numeric_color_only: true,
+            ..Default::default()
+        };
+        let printer = printer(16, 2, Numeric::Decimal, options).unwrap();
+        let group = Group::gather(b"abc123def", printer.grouping());
+        assert!(group.kind == Kind::Printable);
+        assert_eq!(group.span.bytes, b"abc123def");
+    }
+
+    #[test]
+    fn test_numeric_color_only_hex_words() {
+        let options = Options {
+            numeric_color_only: true,
+            ..Default::default()
+        };
+        let printer = printer(16, 2, Numeric::Hexadecimal, options).unwrap();
+        let group = Group::gather(b"cafebabe xyz", printer.grouping());
+        assert!(group.kind == Kind::Printable);
+        assert_eq!(group.span.bytes, b"cafebabe xyz");
+    }
+
+    #[test]
+    fn test_group_checksum() {
+        assert_eq!(Checksum::Xor.compute(&[0x12, 0x34, 0xff]), 0xd9);
+        assert_eq!(Checksum::Sum.compute(&[0x12, 0x34, 0xff]), 0x45);
+        assert_eq!(Checksum::Sum.compute(&[]), 0);
+
+        let options = Options {
+            group_checksum: Some(Checksum::Xor),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"\x12\x34\xff\x01\x02", 4, 2, options),
+            "\
+                0000_0000_0000_0000: 1234 ff01 | .4.. | 26 fe\n\
+                0000_0000_0000_0004: 02        | .    |\n"
+        );
+    }
+
+    #[test]
+    fn test_row_hash() {
+        assert_eq!(RowHash::Crc32.compute(b"123456789"), 0xcbf4_3926);
+        assert_eq!(RowHash::Crc32.compute(b""), 0);
+
+        let options = Options {
+            row_hash: Some(RowHash::Crc32),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcde", 4, 2, options),
+            "\
+                0000_0000_0000_0000: 6162 6364 | abcd | ed82cd11\n\
+                0000_0000_0000_0004: 65        | e    | efda7a5a\n"
+        );
+    }
+
+    #[test]
+    fn test_min_string() {
+        let options = || Options {
+            min_string: Some(6),
+            ..Default::default()
+        };
+
+        // The run starts in a row that is held back until the run is long enough.
+        assert_eq!(
+            dump(
+                b"\x00\x01\x02\x03\x04\x05\x06abcdefgh\x07\x08\x09\x0a\x0b",
+                4,
+                2,
+                options()
+            ),
+            "\
+                ...\n\
+                0000_0000_0000_0004: 0405 0661 | ...a |\n\
+                0000_0000_0000_0008: 6263 6465 | bcde |\n\
+                0000_0000_0000_000c: 6667 6807 | fgh. |\n\
+                ...\n"
+        );
+
+        // Held rows are squeezed with the rows after them.
+        let squeeze = Options {
+            squeeze: Some(2),
+            ..options()
+        };
+        assert_eq!(
+            dump(b"abcdabcdabcd\x00", 4, 2, squeeze),
+            "\
+                0000_0000_0000_0000: 6162 6364 | abcd |\n\
+                *\n\
+                ...\n"
+        );
+
+        // Short runs are skipped, even across rows.
+        assert_eq!(dump(b"\x00abc\x00de\xff\x01\x02", 4, 2, options()), "...\n");
+        assert_eq!(
+            dump(b"abcdef", 4, 2, options()),
+            "\
+                0000_0000_0000_0000: 6162 6364 | abcd |\n\
+                0000_0000_0000_0004: 6566      | ef   |\n"
+        );
+    }
+
+    #[test]
+    fn test_byte_count_column() {
+        let options = Options {
+            byte_count_column: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"ab\x00\x01cd12\xffe", 4, 2, options),
+            "\
+                0000_0000_0000_0000: 6162 0001 | ab.. |        0\n\
+                0000_0000_0000_0004: 6364 3132 | cd12 |        2\n\
+                0000_0000_0000_0008: ff65      | .e   |        6\n"
+        );
+
+        // Counts are exact when groups are split across reads.
+        let input = [b'a'; 5000];
+        let options = Options {
+            byte_count_column: true,
+            ..Default::default()
+        };
+        let output = dump(&input, 16, 2, options);
+        assert!(output
+            .lines()
+            .last()
+            .unwrap()
+            .ends_with(&format!("{:>8}", 4992)));
+    }
+
+    #[test]
+    fn test_invalid_as_latin1() {
+        let input = b"caf\xe9 na\xefve \x85";
+        assert_eq!(
+            dump(input, 16, 2, Options::default()),
+            "0000_0000_0000_0000: 6361 66e9 206e 61ef 7665 2085           | caf. na.ve .     |\n"
+        );
+
+        let options = Options {
+            invalid_as_latin1: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(input, 16, 2, options),
+            "0000_0000_0000_0000: 6361 66e9 206e 61ef 7665 2085           | caf\u{e9} na\u{ef}ve .     |\n"
+        );
+    }
+
+    #[test]
+    fn test_table_case() {
+        let dump = |case| {
+            let options = Options {
+                table_case: Some(case),
+                ..Default::default()
+            };
+            dump(b"Hex 0xAb!", 16, 2, options)
+        };
+
+        // Only the table changes.
+        let hex = "0000_0000_0000_0000: 4865 7820 3078 4162 21                  ";
+        assert_eq!(
+            dump(TableCase::Upper),
+            format!("{hex}| HEX 0XAB!        |\n")
+        );
+        assert_eq!(
+            dump(TableCase::Lower),
+            format!("{hex}| hex 0xab!        |\n")
+        );
+        assert_eq!(
+            dump(TableCase::Swap),
+            format!("{hex}| hEX 0XaB!        |\n")
+        );
+    }
+
+    #[test]
+    fn test_endian() {
+        let options = Options {
+            endian: Endian::Little,
+            group_gap: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"\x01\x02\x03\x04abcdefg", 12, 2, options.clone()),
+            "\
+                0000_0000_0000_0000: 0201 0403  6261 6463  6665 67   | ....abcdefg  |\n"
+        );
+        assert_eq!(
+            dump(b"\x01\x02\x03\x04\x05\x06", 8, 4, options),
+            "0000_0000_0000_0000: 04030201 0605     | ......   |\n"
+        );
+
+        // Only power-of-two groups have a byte order.
+        let options = Options {
+            endian: Endian::Little,
+            ..Default::default()
+        };
+        let printer = Printer::with_writer(12, 3, Numeric::Decimal, options, io::sink());
+        assert!(matches!(printer, Err(Error::EndianGroup(3))));
+        assert!(matches!(
+            "middle".parse::<Endian>(),
+            Err(Error::UnknownEndian(_))
+        ));
+    }
+
+    #[test]
+    fn test_pad_last_group() {
+        // Little-endian bytes in a partial last group line up with the same bytes above.
+        let options = Options {
+            endian: Endian::Little,
+            pad_last_group: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcdefghijk", 8, 4, options.clone()),
+            "\
+                0000_0000_0000_0000: 64636261 68676665 | abcdefgh |\n\
+                0000_0000_0000_0008:   6b6a69          | ijk      |\n"
+        );
+
+        // A width that is not a multiple of the group leaves a partial group in every row, which
+        // is the size the last group is padded to.
+        assert_eq!(
+            dump(b"abcdefghijk", 6, 4, options.clone()),
+            "\
+                0000_0000_0000_0000: 64636261 6665 | abcdef |\n\
+                0000_0000_0000_0006: 6a696867   6b | ghijk  |\n"
+        );
+
+        // Big-endian groups are padded on the right, where the hex column already lines up.
+        let options = Options {
+            pad_last_group: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcdefghijk", 8, 4, options),
+            dump(b"abcdefghijk", 8, 4, Options::default())
+        );
+    }
+
+    #[test]
+    fn test_mirror_groups() {
+        let options = Options {
+            mirror_groups: true,
+            group_gap: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcdefghij", 8, 2, options),
+            "\
+                0000_0000_0000_0000: 6162 6364  6566 6768 | ab cd  ef gh |\n\
+                0000_0000_0000_0008: 696a                 | ij           |\n"
+        );
+
+        // Double-wide characters keep the table aligned.
+        let options = Options {
+            mirror_groups: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump("a\u{4e2d}bcdef".as_bytes(), 4, 2, options),
+            "\
+                0000_0000_0000_0000: 61e4 b8ad | a\u{4e2d}   |\n\
+                0000_0000_0000_0004: 6263 6465 | bc de |\n\
+                0000_0000_0000_0008: 66        | f     |\n"
+        );
+
+        // Truncated tables stop at the ellipsis.
+        let options = Options {
+            mirror_groups: true,
+            table_width: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcdef", 6, 2, options),
+            "0000_0000_0000_0000: 6162 6364 6566 | ab \u{2026} |\n"
+        );
+    }
+
+    #[test]
+    fn test_table_group() {
+        let options = Options {
+            table_group: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcdefghij", 8, 2, options),
+            "\
+                0000_0000_0000_0000: 6162 6364 6566 6768 | abcd efgh |\n\
+                0000_0000_0000_0008: 696a                | ij        |\n"
+        );
+
+        // The table can use smaller groups than the hex column, and overrides mirrored groups.
+        let options = Options {
+            table_group: Some(2),
+            mirror_groups: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcdefghij", 8, 4, options),
+            "\
+                0000_0000_0000_0000: 61626364 65666768 | ab cd ef gh |\n\
+                0000_0000_0000_0008: 696a              | ij          |\n"
+        );
+    }
+
+    #[test]
+    fn test_last_group_alignment() {
+        // Bytes in a partial last group line up with the same bytes in the full groups above.
+        assert_eq!(
+            dump(b"abcdefghijklm", 8, 4, Options::default()),
+            "\
+                0000_0000_0000_0000: 61626364 65666768 | abcdefgh |\n\
+                0000_0000_0000_0008: 696a6b6c 6d       | ijklm    |\n"
+        );
+
+        let options = Options {
+            group_gap: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcdefghijklm", 8, 4, options),
+            "\
+                0000_0000_0000_0000: 61626364  65666768 | abcdefgh |\n\
+                0000_0000_0000_0008: 696a6b6c  6d       | ijklm    |\n"
+        );
+    }
+
+    #[test]
+    fn test_ascii_escape() {
+        assert_eq!(escape(b'\0'), Some("\\0"));
+        assert_eq!(escape(b'\t'), Some("\\t"));
+        assert_eq!(escape(b'\n'), Some("\\n"));
+        assert_eq!(escape(b'\r'), Some("\\r"));
+        assert_eq!(escape(0x1b), None);
+        assert_eq!(escape(b'n'), None);
+
+        let options = Options {
+            no_hex: true,
+            ascii_escape: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"a\0b\tc\nd\re\x1b\x7f", 8, 2, options),
+            "\
+                0000_0000_0000_0000: | a\\0b\\tc\\nd\\r     |\n\
+                0000_0000_0000_0008: | e..              |\n"
+        );
+
+        let options = Options {
+            ascii_escape: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            printer(16, 2, Numeric::Decimal, options),
+            Err(Error::EscapeWithHex)
+        ));
+    }
+
+    #[test]
+    fn test_no_leading_space() {
+        let options = Options {
+            no_leading_space: true,
+            group_gap: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"abcdefghijklm", 8, 2, options),
+            "\
+                0000_0000_0000_0000:6162 6364  6566 6768 | abcdefgh |\n\
+                0000_0000_0000_0008:696a 6b6c  6d        | ijklm    |\n"
+        );
+
+        // The table separator stays in the same column on every row, one column left of the default.
+        let options = Options {
+            no_leading_space: true,
+            ..Default::default()
+        };
+        let output = dump(&[b'a'; 21], 8, 4, options);
+        let plain = dump(&[b'a'; 21], 8, 4, Options::default());
+        for (line, plain) in output.lines().zip(plain.lines()) {
+            assert_eq!(line.find(" | ").unwrap() + 1, plain.find(" | ").unwrap());
+        }
+    }
+
+    #[test]
+    fn test_large_group() {
+        // A group as wide as the row only has a separator before its first byte.
+        assert_eq!(padding(16, None, 16), 33);
+        assert_eq!(padding(16, None, 32), 66);
+        assert_eq!(group_separator(16, None, 0), " ");
+        assert_eq!(group_separator(16, None, 8), "");
+        assert_eq!(group_separator(16, None, 16), " ");
+
+        let input = b"abcdefghijklmnopqrstuvwxyz0123456789AB";
+        assert_eq!(
+            dump(input, 16, 16, Options::default()),
+            "\
+                0000_0000_0000_0000: 6162636465666768696a6b6c6d6e6f70 | abcdefghijklmnop |\n\
+                0000_0000_0000_0010: 7172737475767778797a303132333435 | qrstuvwxyz012345 |\n\
+                0000_0000_0000_0020: 363738394142                     | 6789AB           |\n"
+        );
+        assert_eq!(
+            dump(input, 32, 16, Options::default()),
+            "\
+                0000_0000_0000_0000: 6162636465666768696a6b6c6d6e6f70 7172737475767778797a303132333435 | abcdefghijklmnopqrstuvwxyz012345 |\n\
+                0000_0000_0000_0020: 363738394142                                                      | 6789AB                           |\n"
+        );
+
+        assert!(matches!(
+            printer(16, 17, Numeric::Decimal, Options::default()),
+            Err(Error::Grouping(17, 16))
+        ));
+    }
+
+    #[test]
+    fn test_align_width() {
+        let unaligned = printer(15, 4, Numeric::Decimal, Options::default()).unwrap();
+        assert_eq!(unaligned.width, 15);
+        assert_eq!(unaligned.max, 34);
+
+        let options = Options {
+            align_width: true,
+            ..Default::default()
+        };
+        let aligned = printer(15, 4, Numeric::Decimal, options).unwrap();
+        assert_eq!(aligned.width, 16);
+        assert_eq!(aligned.max, 36);
+
+        // Widths that are already aligned are unchanged.
+        let options = Options {
+            align_width: true,
+            ..Default::default()
+        };
+        let aligned = printer(16, 4, Numeric::Decimal, options).unwrap();
+        assert_eq!(aligned.width, 16);
+        assert_eq!(aligned.max, 36);
+    }
+
+    #[test]
+    fn test_printer_errors() {
+        let error = |width, group| {
+            let result = printer(width, group, Numeric::Decimal, Options::default());
+            result.err().unwrap().to_string()
+        };
+
+        assert_eq!(error(1, 1), "Width must be in range `2..=4096`, got 1");
+        assert_eq!(
+            error(5000, 2),
+            "Width must be in range `2..=4096`, got 5000"
+        );
+        assert_eq!(error(16, 100), "Group must be in range `1..=16`, got 100");
+        assert_eq!(error(16, 0), "Group must be in range `1..=16`, got 0");
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("100").unwrap(), 100);
+        assert_eq!(parse_size("0x400000").unwrap(), 0x400000);
+        assert_eq!(parse_size("0XfF").unwrap(), 0xff);
+        assert_eq!(parse_size("4k").unwrap(), 4096);
+        assert_eq!(parse_size("2M").unwrap(), 2 << 20);
+        assert_eq!(parse_size("0x10G").unwrap(), 16 << 30);
+        assert!(matches!(parse_size(""), Err(Error::InvalidSize(_))));
+        assert!(matches!(parse_size("12q"), Err(Error::InvalidSize(_))));
+        assert!(matches!(parse_size("0x"), Err(Error::InvalidSize(_))));
+        assert!(matches!(
+            parse_size("0xffffffffffT"),
+            Err(Error::InvalidSize(_))
+        ));
+    }
+
+    #[test]
+    fn test_samples() {
+        let samples = |len, skip, size, count| samples(len, skip, size, count).collect::<Vec<_>>();
+
+        assert_eq!(
+            samples(100, 0, 10, 4),
+            [(0, 10), (30, 10), (60, 10), (90, 10)]
+        );
+        assert_eq!(samples(100, 20, 10, 3), [(20, 10), (55, 10), (90, 10)]);
+        assert_eq!(samples(100, 0, 10, 1), [(0, 10)]);
+
+        // Small files are a single sample.
+        assert_eq!(samples(40, 0, 10, 4), [(0, 40)]);
+        assert_eq!(samples(40, 10, 10, 4), [(10, 30)]);
+        assert_eq!(samples(0, 0, 10, 4), [(0, 0)]);
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!(parse_pattern("ffd8ff").unwrap(), [0xff, 0xd8, 0xff]);
+        assert_eq!(parse_pattern("0x7f 45 4c 46").unwrap(), b"\x7fELF");
+        assert!(matches!(parse_pattern(""), Err(Error::InvalidPattern(_))));
+        assert!(matches!(
+            parse_pattern("fff"),
+            Err(Error::InvalidPattern(_))
+        ));
+        assert!(matches!(parse_pattern("zz"), Err(Error::InvalidPattern(_))));
+    }
+
+    #[test]
+    fn test_table_delimiters() {
+        let options = Options {
+            no_addr: true,
+            table_delimiters: Some((" <<< ".to_string(), " >>>".to_string())),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"0123456789", 8, 2, options),
+            "\
+                \x203031 3233 3435 3637 <<< 01234567 >>>\n\
+                \x203839                <<< 89       >>>\n"
+        );
+
+        // Without a closing delimiter, the table is not padded.
+        let options = Options {
+            no_addr: true,
+            table_delimiters: Some((" \u{ab}".to_string(), String::new())),
+            ..Default::default()
+        };
+        assert_eq!(
+            dump(b"0123456789", 8, 2, options),
+            "\
+                \x203031 3233 3435 3637 \u{ab}01234567\n\
+                \x203839                \u{ab}89\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_delimiters() {
+        let parse = |s: &str| parse_delimiters(s.to_string());
+        assert_eq!(
+            parse(" | , |").unwrap(),
+            (" | ".to_string(), " |".to_string())
+        );
+        assert_eq!(
+            parse("\u{ab},").unwrap(),
+            ("\u{ab}".to_string(), String::new())
+        );
+        assert_eq!(
+            parse("x,y,z").unwrap(),
+            ("x".to_string(), "y,z".to_string())
+        );
+        for s in ["|", " ,|", "ab,", "|\t,|"] {
+            assert!(matches!(parse(s), Err(Error::InvalidDelimiters(_))));
+        }
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("red").unwrap(), Color::Ansi(AnsiColor::Red));
+        assert_eq!(
+            parse_color("Bright_Black").unwrap(),
+            Color::Ansi(AnsiColor::BrightBlack)
+        );
+        assert!(matches!(parse_color("pink"), Err(Error::UnknownColor(_))));
+        assert!(matches!(parse_color(""), Err(Error::UnknownColor(_))));
+    }
+
+    #[test]
+    fn test_parse_separator() {
+        for separator in [":", " ", "\u{2502}", "e\u{301}"] {
+            assert_eq!(parse_separator(separator.to_string()).unwrap(), separator);
+        }
+        for separator in ["", "::", "\u{ff1a}", "\t"] {
+            assert!(matches!(
+                parse_separator(separator.to_string()),
+                Err(Error::InvalidSeparator(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_parse_mark() {
+        assert_eq!(parse_mark("0x10:0x20").unwrap(), 0x10..0x20);
+        assert_eq!(parse_mark("4:1k").unwrap(), 4..1024);
+        assert_eq!(parse_mark("8:8").unwrap(), 8..8);
+        assert!(matches!(parse_mark("8:4"), Err(Error::InvalidMark(_))));
+        assert!(matches!(parse_mark("0x10"), Err(Error::InvalidMark(_))));
+        assert!(matches!(parse_mark("a:b"), Err(Error::InvalidMark(_))));
+    }
+
+    #[test]
+    fn test_parse_dump_range() {
+        let range = |s| parse_dump_range(s).map(|window| (window.skip, window.length));
+
+        assert_eq!(range("0x100:0x180").unwrap(), (0x100, Some(0x80)));
+        assert_eq!(range("0x100:").unwrap(), (0x100, None));
+        assert_eq!(range(":1k").unwrap(), (0, Some(1024)));
+        assert_eq!(range(":").unwrap(), (0, None));
+        assert_eq!(range("8:8").unwrap(), (8, Some(0)));
+        assert!(matches!(range("8:4"), Err(Error::InvalidDumpRange(_))));
+        assert!(matches!(range("0x10"), Err(Error::InvalidDumpRange(_))));
+        assert!(matches!(range("a:"), Err(Error::InvalidDumpRange(_))));
+    }
+
+    #[test]
+    fn test_numeric_detect() {
+        let detect = |sample: &[u8]| match Numeric::detect(sample) {
+            Numeric::Octal => "octal",
+            Numeric::Decimal => "decimal",
+            Numeric::Hexadecimal => "hex",
+            _ => unreachable!(),
+        };
+
+        assert_eq!(detect(b"mov eax, 0x1f; jmp 4a2c"), "hex");
+        assert_eq!(detect(b"de ad be ef 00 1f 2e"), "hex");
+        assert_eq!(detect(b"chmod 0755 a; chmod 0644 b"), "octal");
+        assert_eq!(detect(b"total: 1989, count: 42"), "decimal");
+
+        // Ambiguous samples are decimal.
+        assert_eq!(detect(b"a cafe bed"), "decimal");
+        assert_eq!(detect(b"12 1f"), "decimal");
+        assert_eq!(detect(b""), "decimal");
+    }
+
+    #[test]
+    fn test_parse_widths() {
+        assert_eq!(parse_widths("16").unwrap(), [16]);
+        assert_eq!(parse_widths("8,16, 32").unwrap(), [8, 16, 32]);
+        assert!(matches!(parse_widths("8,"), Err(Error::InvalidWidth(_))));
+        assert!(matches!(parse_widths("wide"), Err(Error::InvalidWidth(_))));
+        assert!(matches!(
+            parse_widths("1,2,3,4,5,6,7,8,9"),
+            Err(Error::TooManyWidths(8))
+        ));
+    }
+
+    #[test]
+    fn test_parse_group() {
+        assert_eq!(parse_group("byte").unwrap(), 1);
+        assert_eq!(parse_group("word").unwrap(), 2);
+        assert_eq!(parse_group("dword").unwrap(), 4);
+        assert_eq!(parse_group("QWORD").unwrap(), 8);
+        assert_eq!(parse_group("3").unwrap(), 3);
+        assert!(matches!(parse_group("nibble"), Err(Error::UnknownGroup(_))));
+    }
+
+    #[test]
+    fn test_padding_group_gap() {
+        assert_eq!(padding(2, None, 32), 80);
+        assert_eq!(padding(2, Some(4), 32), 83);
+        assert_eq!(padding(2, Some(4), 16), 41);
+
+        // Gaps are only counted once the group after them is started.
+        assert_eq!(padding(2, Some(4), 8), 20);
+        assert_eq!(padding(2, Some(4), 9), 24);
+        assert_eq!(padding(2, Some(4), 0), 0);
+    }
+
+    #[test]
+    fn test_visible_width() {
+        assert_eq!(visible_width("abc"), 3);
+        assert_eq!(visible_width("\x1b[94mabc\x1b[39m:"), 4);
+        assert_eq!(visible_width("\x1b[92m\u{1f600}\x1b[39m"), 2);
+        assert_eq!(visible_width(""), 0);
+    }
+
+    #[test]
+    fn test_strip_colors() {
+        assert_eq!(strip_colors("abc"), "abc");
+        assert_eq!(strip_colors("\x1b[94mabc\x1b[39m:"), "abc:");
+        assert_eq!(strip_colors("\x1b[2m\x1b[90m...\x1b[39m\x1b[22m"), "...");
+    }
+
+    #[test]
+    fn test_addr_from_name() {
+        let pattern = Regex::new(r"_(0x[[:xdigit:]]+)\.").unwrap();
+        assert_eq!(addr_from_name(&pattern, "file_0x1A2B.bin"), Some(0x1a2b));
+        assert_eq!(
+            addr_from_name(&pattern, "dir/carved_0x400000.raw"),
+            Some(0x400000)
+        );
+        assert_eq!(addr_from_name(&pattern, "file_1A2B.bin"), None);
+
+        // Without a capture group, the whole match is used.
+        let pattern = Regex::new(r"[[:xdigit:]]{8}").unwrap();
+        assert_eq!(addr_from_name(&pattern, "dump-0000ff00.bin"), Some(0xff00));
+        assert_eq!(addr_from_name(&pattern, "dump.bin"), None);
+    }
+
+    #[test]
+    fn test_offset_addr() {
+        assert_eq!(offset_addr(0x1000, 0x20).unwrap(), 0x1020);
+        assert_eq!(offset_addr(usize::MAX - 1, 1).unwrap(), usize::MAX);
+        assert!(matches!(
+            offset_addr(usize::MAX, 2),
+            Err(Error::AddrOverflow(usize::MAX, 2))
+        ));
+        assert!(matches!(
+            offset_addr(1, u64::MAX),
+            Err(Error::AddrOverflow(1, u64::MAX))
+        ));
+    }
+
+    #[test]
+    fn test_verify() {
+        let dump = "0000_0000_0000_0000: 6869 210a | hi!. |\n";
+        let verify = |input: &[u8]| {
+            let mut dump = InputFormat::Dump.decoder(dump.as_bytes(), &DumpLayout::default());
+            verify(&mut dump, &mut &input[..]).map_err(|err| err.to_string())
+        };
+
+        assert!(verify(b"hi!\n").is_ok());
+        assert_eq!(
+            verify(b"hi?\n").unwrap_err(),
+            "Dump differs from input at offset 0x2: dump has 0x21, input has 0x3f"
+        );
+        assert_eq!(
+            verify(b"hi!\n!").unwrap_err(),
+            "Dump differs from input at offset 0x4: dump has no more bytes, input has 0x21"
+        );
+        assert_eq!(
+            verify(b"hi").unwrap_err(),
+            "Dump differs from input at offset 0x2: dump has 0x21, input has no more bytes"
+        );
+    }
+
+    #[test]
+    fn test_byte_at() {
+        let input = b"\x00\x1a\xff";
+        assert_eq!(byte_at(&mut &input[..], 0).unwrap(), Some(0x00));
+        assert_eq!(byte_at(&mut &input[..], 2).unwrap(), Some(0xff));
+        assert_eq!(byte_at(&mut &input[..], 3).unwrap(), None);
+        assert_eq!(byte_at(&mut &b""[..], 0).unwrap(), None);
+    }
+
+    #[test]
+    fn test_percent_encode_path() {
+        assert_eq!(percent_encode_path("/tmp/file.bin"), "/tmp/file.bin");
+        assert_eq!(
+            percent_encode_path("/tmp/a b/\u{fc}#1.bin"),
+            "/tmp/a%20b/%C3%BC%231.bin"
+        );
+    }
 }
@@ -0,0 +1,96 @@
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::rc::Rc;
+
+/// Pager command used when `$PAGER` is not set.
+///
+/// `-R` passes colors through, `-F` quits when the output fits on one screen, and `-X` leaves the
+/// output on screen after quitting.
+const DEFAULT_PAGER: &str = "less -FRX";
+
+/// A pager process reading dump output from a pipe.
+///
+/// Dropping the pager closes the pipe and waits for the pager to exit, so the terminal is not
+/// shared with it after `hd` exits.
+pub(crate) struct Pager {
+    /// The pager process.
+    child: Child,
+
+    /// The write end of the pipe, shared with every [`Output`].
+    stdin: Rc<RefCell<Option<ChildStdin>>>,
+}
+
+impl Pager {
+    /// Spawn the user's `$PAGER`, or `less` by default.
+    ///
+    /// Returns `None` when `$PAGER` is set to an empty string, which disables paging.
+    pub(crate) fn spawn() -> io::Result<Option<Self>> {
+        let command = std::env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string());
+        let mut words = command.split_whitespace();
+        let Some(program) = words.next() else {
+            return Ok(None);
+        };
+
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take();
+
+        Ok(Some(Self {
+            child,
+            stdin: Rc::new(RefCell::new(stdin)),
+        }))
+    }
+
+    /// Get an output that writes to the pager.
+    pub(crate) fn output(&self) -> Output {
+        Output::Pager(Rc::clone(&self.stdin))
+    }
+}
+
+impl Drop for Pager {
+    fn drop(&mut self) {
+        // Closing the pipe tells the pager that there is no more output.
+        self.stdin.borrow_mut().take();
+        let _ = self.child.wait();
+    }
+}
+
+/// Destination for dump output. All clones write to the same stream.
+#[derive(Clone)]
+pub(crate) enum Output {
+    /// Write to stdout.
+    Stdout,
+
+    /// Write to a pager. Writes fail with a broken pipe once the pager is closed.
+    Pager(Rc<RefCell<Option<ChildStdin>>>),
+}
+
+impl Output {
+    /// Run a function with the underlying writer.
+    fn with<T>(&mut self, f: impl FnOnce(&mut dyn Write) -> io::Result<T>) -> io::Result<T> {
+        match self {
+            Self::Stdout => f(&mut io::stdout()),
+            Self::Pager(stdin) => match stdin.borrow_mut().as_mut() {
+                Some(stdin) => f(stdin),
+                None => Err(io::ErrorKind::BrokenPipe.into()),
+            },
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with(|writer| writer.write(buf))
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.with(|writer| writer.write_all(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.with(|writer| writer.flush())
+    }
+}
@@ -0,0 +1,54 @@
+use std::ffi::OsString;
+
+/// Named bundles of options for common structures.
+///
+/// Each preset is a list of command line arguments, expanded in place of `--preset`. Add a preset
+/// by adding its name and arguments here.
+const PRESETS: &[(&str, &[&str])] = &[
+    // One 32-bit header word per row, so the 20-byte header without options takes five rows.
+    ("ipv4", &["--width", "4"]),
+    // The first and third partition table entries are marked, so adjacent entries alternate
+    // between marked and unmarked, and the boot signature is marked.
+    (
+        "mbr",
+        &[
+            "--width",
+            "16",
+            "--mark",
+            "0x1be:0x1ce",
+            "--mark",
+            "0x1de:0x1ee",
+            "--mark",
+            "0x1fe:0x200",
+        ],
+    ),
+];
+
+/// Get the command line arguments for a preset by name.
+pub(crate) fn args(name: &str) -> Option<Vec<OsString>> {
+    let (_, args) = PRESETS.iter().find(|(preset, _)| *preset == name)?;
+
+    Some(args.iter().map(OsString::from).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Args;
+    use onlyargs::OnlyArgs as _;
+
+    #[test]
+    fn test_presets_parse() {
+        for (name, _) in PRESETS {
+            let args = Args::parse(args(name).unwrap()).unwrap();
+            assert!(args.preset.is_none(), "{name}");
+        }
+
+        assert_eq!(args("ipv4").unwrap(), ["--width", "4"]);
+
+        // Marked and unmarked partition table entries alternate.
+        let mbr = Args::parse(args("mbr").unwrap()).unwrap();
+        assert_eq!(mbr.mark, ["0x1be:0x1ce", "0x1de:0x1ee", "0x1fe:0x200"]);
+        assert_eq!(args("ipv6"), None);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::Error;
+use std::io;
+#[cfg(target_os = "linux")]
+use std::{fs::File, io::Read, io::Seek as _, io::SeekFrom};
+
+/// Error code returned when reading unmapped process memory.
+#[cfg(target_os = "linux")]
+const EIO: i32 = 5;
+
+/// Reader for the memory of another process, through `/proc/PID/mem`.
+#[cfg(target_os = "linux")]
+pub(crate) struct Memory {
+    /// The open memory file.
+    file: File,
+
+    /// Virtual address of the next byte.
+    addr: u64,
+}
+
+/// Open the memory of a process for reading, starting at a virtual address.
+#[cfg(target_os = "linux")]
+pub(crate) fn open(pid: u32, addr: u64) -> Result<Memory, Error> {
+    let mut file = File::open(path(pid)).map_err(|err| Error::Process(err, pid))?;
+    file.seek(SeekFrom::Start(addr))
+        .map_err(|err| Error::Process(err, pid))?;
+
+    Ok(Memory { file, addr })
+}
+
+/// Open the memory of a process for reading, starting at a virtual address.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn open(pid: u32, _addr: u64) -> Result<io::Empty, Error> {
+    let err = io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Process memory can only be read on Linux",
+    );
+
+    Err(Error::Process(err, pid))
+}
+
+/// Get the path to the memory file of a process.
+pub(crate) fn path(pid: u32) -> String {
+    format!("/proc/{pid}/mem")
+}
+
+#[cfg(target_os = "linux")]
+impl Read for Memory {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.file.read(buf) {
+            Ok(size) => {
+                self.addr += size as u64;
+                Ok(size)
+            }
+            Err(err) if err.raw_os_error() == Some(EIO) => Err(io::Error::new(
+                err.kind(),
+                format!("Unreadable memory at address {:#x}", self.addr),
+            )),
+            Err(err) => Err(err),
+        }
+    }
+}
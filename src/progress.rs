@@ -0,0 +1,94 @@
+use std::io::{self, Write as _};
+use std::time::{Duration, Instant};
+
+/// Minimum time between progress reports.
+const INTERVAL: Duration = Duration::from_millis(100);
+
+/// Progress indicator for inputs with a known length, updated in place on stderr.
+pub(crate) struct Progress {
+    /// Total number of bytes expected.
+    total: u64,
+
+    /// Number of bytes consumed so far.
+    done: u64,
+
+    /// Time when the progress indicator was created.
+    start: Instant,
+
+    /// Time of the last progress report.
+    last: Instant,
+}
+
+impl Progress {
+    /// Create a progress indicator for an input with `total` bytes.
+    pub(crate) fn new(total: u64) -> Self {
+        let now = Instant::now();
+
+        Self {
+            total,
+            done: 0,
+            start: now,
+            last: now,
+        }
+    }
+
+    /// Record that `size` more bytes have been consumed, and periodically report progress.
+    pub(crate) fn update(&mut self, size: usize) {
+        self.done += size as u64;
+
+        let now = Instant::now();
+        if now.duration_since(self.last) >= INTERVAL {
+            self.last = now;
+            let report = format_progress(self.done, self.total, now.duration_since(self.start));
+            let _ = write!(io::stderr(), "\r{report}\x1b[K");
+        }
+    }
+
+    /// Erase the progress indicator.
+    pub(crate) fn finish(self) {
+        let _ = write!(io::stderr(), "\r\x1b[K");
+    }
+}
+
+/// Format a progress report as a percentage and byte rate.
+fn format_progress(done: u64, total: u64, elapsed: Duration) -> String {
+    let percent = (done.min(total) * 100).checked_div(total).unwrap_or(100);
+    let secs = elapsed.as_secs_f64();
+    let rate = if secs > 0.0 { done as f64 / secs } else { 0.0 };
+
+    format!("{percent:>3}% {}/s", format_bytes(rate))
+}
+
+/// Format a byte count with binary unit prefixes.
+fn format_bytes(bytes: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut value = bytes;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{value:.0} {}", UNITS[unit])
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_progress() {
+        let second = Duration::from_secs(1);
+
+        assert_eq!(format_progress(0, 100, Duration::ZERO), "  0% 0 B/s");
+        assert_eq!(format_progress(50, 100, second), " 50% 50 B/s");
+        assert_eq!(format_progress(4096, 8192, second * 2), " 50% 2.0 KiB/s");
+        assert_eq!(format_progress(3 << 20, 3 << 20, second), "100% 3.0 MiB/s");
+        assert_eq!(format_progress(0, 0, second), "100% 0 B/s");
+    }
+}
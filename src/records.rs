@@ -0,0 +1,94 @@
+use crate::{group::read_groups, Numeric};
+use std::io::{self, Read, Write};
+
+/// Writes one line for each classified [`Group`] instead of a dump layout.
+///
+/// Each record is `offset kind hexbytes`, where `offset` is the hex byte offset of the start of the
+/// group and `kind` is one of the stable names from [`Kind::name`]. Groups are read with
+/// [`read_groups`], so groups longer than its buffer are split into multiple records.
+///
+/// [`Group`]: crate::group::Group
+/// [`Kind::name`]: crate::group::Kind::name
+pub(crate) struct Records<W: Write> {
+    /// The writer receiving records.
+    writer: W,
+
+    /// Numeric classification for groups.
+    numeric: Numeric,
+
+    /// Offset of the next byte.
+    offset: usize,
+}
+
+impl<W: Write> Records<W> {
+    /// Create a record writer wrapping a writer.
+    pub(crate) fn new(writer: W, numeric: Numeric) -> Self {
+        Self {
+            writer,
+            numeric,
+            offset: 0,
+        }
+    }
+
+    /// Write records for all bytes from a reader.
+    ///
+    /// Offsets continue across multiple readers.
+    pub(crate) fn write<R>(&mut self, reader: &mut R) -> io::Result<()>
+    where
+        R: Read,
+    {
+        read_groups(reader, &mut self.numeric, |group| {
+            write!(self.writer, "{:08x} {}", self.offset, group.kind.name())?;
+            for byte in group.span.bytes {
+                write!(self.writer, " {byte:02x}")?;
+            }
+            writeln!(self.writer)?;
+            self.offset += group.span.bytes.len();
+
+            Ok(())
+        })
+    }
+
+    /// Flush the writer.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records() {
+        let mut output = Vec::new();
+        let mut records = Records::new(&mut output, Numeric::Decimal);
+        records.write(&mut &b"ab12\n\xff"[..]).unwrap();
+        records.write(&mut "€".as_bytes()).unwrap();
+        records.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "00000000 printable 61 62\n\
+             00000002 numeric 31 32\n\
+             00000004 control 0a\n\
+             00000005 invalid ff\n\
+             00000006 graphemes e2 82 ac\n"
+        );
+    }
+
+    #[test]
+    fn test_records_across_reads() {
+        let mut output = Vec::new();
+        let mut records = Records::new(&mut output, Numeric::Decimal);
+        records.write(&mut b"12ab".chain(&b"cd34"[..])).unwrap();
+        records.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "00000000 numeric 31 32\n\
+             00000002 printable 61 62 63 64\n\
+             00000006 numeric 33 34\n"
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::rows::read_rows;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+/// Number of offsets listed for each repeated row.
+const MAX_OFFSETS: usize = 8;
+
+/// Finds rows that repeat anywhere in the input, instead of printing a dump.
+///
+/// The input is split into rows of `width` bytes, and each distinct row is counted in a hash map.
+/// [`Repeats::finish`] writes one line for each row that appears more than once, most repeated
+/// first, like `3x 0x0 0x40 0x80`. Only the first [`MAX_OFFSETS`] offsets of a row are kept, so
+/// the rest are only counted, ending the line with `… and 4 more`. A final partial row is ignored.
+///
+/// Memory is bounded by `limit`, the number of distinct rows tracked. Once it is reached, rows that
+/// have not been seen before are no longer tracked, so later repeats of them are not reported.
+/// Repeats of rows that are already tracked are still found, and the summary notes the offset
+/// where tracking stopped.
+pub(crate) struct Repeats<W: Write> {
+    /// The writer receiving the summary.
+    writer: W,
+
+    /// Number of bytes per row.
+    width: usize,
+
+    /// Maximum number of distinct rows tracked.
+    limit: usize,
+
+    /// Offset of the current row.
+    offset: u64,
+
+    /// Bytes in the current row.
+    row: Vec<u8>,
+
+    /// Occurrences of each distinct row.
+    rows: HashMap<Vec<u8>, Occurrences>,
+
+    /// Offset of the first row that was not tracked because of the limit.
+    untracked: Option<u64>,
+}
+
+impl<W: Write> Repeats<W> {
+    /// Create a repeat finder wrapping a writer, with `width` bytes per row.
+    pub(crate) fn new(writer: W, width: usize, limit: usize) -> Self {
+        Self {
+            writer,
+            width,
+            limit,
+            offset: 0,
+            row: Vec::with_capacity(width),
+            rows: HashMap::new(),
+            untracked: None,
+        }
+    }
+
+    /// Collect rows for all bytes from a reader.
+    ///
+    /// Rows and offsets continue across multiple readers.
+    pub(crate) fn write<R>(&mut self, reader: &mut R) -> io::Result<()>
+    where
+        R: Read,
+    {
+        let mut row = std::mem::take(&mut self.row);
+        let result = read_rows(reader, self.width, &mut row, |row| {
+            self.add_row(row);
+            Ok(())
+        });
+        self.row = row;
+
+        result
+    }
+
+    /// Write the summary of repeated rows and flush the writer.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        let mut repeats: Vec<_> = self
+            .rows
+            .into_values()
+            .filter(|occurrences| occurrences.count > 1)
+            .collect();
+        repeats.sort_by_key(|occurrences| {
+            (std::cmp::Reverse(occurrences.count), occurrences.offsets[0])
+        });
+
+        if repeats.is_empty() {
+            writeln!(self.writer, "No repeated rows")?;
+        }
+        for occurrences in repeats {
+            let offsets: Vec<_> = occurrences
+                .offsets
+                .iter()
+                .map(|offset| format!("{offset:#x}"))
+                .collect();
+            write!(self.writer, "{}x {}", occurrences.count, offsets.join(" "))?;
+            let more = occurrences.count - offsets.len() as u64;
+            if more > 0 {
+                write!(self.writer, " \u{2026} and {more} more")?;
+            }
+            writeln!(self.writer)?;
+        }
+        if let Some(offset) = self.untracked {
+            writeln!(
+                self.writer,
+                "Stopped tracking new rows at {offset:#x} after {} distinct rows",
+                self.limit
+            )?;
+        }
+
+        self.writer.flush()
+    }
+
+    /// Record the offset of a row.
+    fn add_row(&mut self, row: &[u8]) {
+        if let Some(occurrences) = self.rows.get_mut(row) {
+            occurrences.count += 1;
+            if occurrences.offsets.len() < MAX_OFFSETS {
+                occurrences.offsets.push(self.offset);
+            }
+        } else if self.rows.len() < self.limit {
+            let occurrences = Occurrences {
+                count: 1,
+                offsets: vec![self.offset],
+            };
+            self.rows.insert(row.to_vec(), occurrences);
+        } else if self.untracked.is_none() {
+            self.untracked = Some(self.offset);
+        }
+
+        self.offset += self.width as u64;
+    }
+}
+
+/// Where a distinct row occurs in the input.
+struct Occurrences {
+    /// Number of times the row occurs.
+    count: u64,
+
+    /// Offsets of the first occurrences, up to [`MAX_OFFSETS`].
+    offsets: Vec<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repeats(input: &[u8], width: usize, limit: usize) -> String {
+        let mut output = Vec::new();
+        let mut repeats = Repeats::new(&mut output, width, limit);
+        repeats.write(&mut &input[..]).unwrap();
+        repeats.finish().unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_repeats() {
+        assert_eq!(
+            repeats(b"abcdabcdxyzwabcdxyzwab", 4, 100),
+            "3x 0x0 0x4 0xc\n2x 0x8 0x10\n"
+        );
+
+        // Rows are aligned to the width, and partial rows are ignored.
+        assert_eq!(repeats(b"xabcdabcd", 4, 100), "No repeated rows\n");
+        assert_eq!(repeats(b"abcdabc", 4, 100), "No repeated rows\n");
+        assert_eq!(repeats(b"", 4, 100), "No repeated rows\n");
+
+        // Only the first offsets are listed, and the rest are counted.
+        assert_eq!(
+            repeats(&[0; 24], 2, 100),
+            "12x 0x0 0x2 0x4 0x6 0x8 0xa 0xc 0xe \u{2026} and 4 more\n"
+        );
+    }
+
+    #[test]
+    fn test_repeats_limit() {
+        // Only the first two distinct rows are tracked, so the repeats of `cc` are not reported.
+        assert_eq!(
+            repeats(b"aabbccaaccbb", 2, 2),
+            "2x 0x0 0x6\n\
+             2x 0x2 0xa\n\
+             Stopped tracking new rows at 0x4 after 2 distinct rows\n"
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::group::{read_groups, Kind};
+use crate::Numeric;
+use std::io::{self, Read};
+
+/// A row of classified bytes, for writers that format a whole row at a time.
+#[derive(Default)]
+pub(crate) struct Row {
+    /// Bytes in the row.
+    pub(crate) bytes: Vec<u8>,
+
+    /// Classification of each byte in the row.
+    pub(crate) kinds: Vec<Kind>,
+
+    /// Character table of the row, with `.` for control and invalid bytes. Grapheme clusters are
+    /// shown in the row where they start.
+    pub(crate) ascii: String,
+}
+
+impl Row {
+    /// Check if the row has no bytes.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Remove all bytes from the row.
+    fn clear(&mut self) {
+        self.bytes.clear();
+        self.kinds.clear();
+        self.ascii.clear();
+    }
+}
+
+/// Split all bytes from a reader into rows of `width` bytes, calling `write_row` with each
+/// complete row.
+///
+/// `row` holds the bytes of a partial row, which is continued by the reader and left with the
+/// bytes of the last partial row, so rows continue across multiple readers.
+pub(crate) fn read_rows<R, F>(
+    reader: &mut R,
+    width: usize,
+    row: &mut Vec<u8>,
+    mut write_row: F,
+) -> io::Result<()>
+where
+    R: Read,
+    F: FnMut(&[u8]) -> io::Result<()>,
+{
+    let mut buf = [0; 4096];
+    loop {
+        let size = reader.read(&mut buf)?;
+        if size == 0 {
+            break;
+        }
+
+        let mut bytes = &buf[..size];
+        while !bytes.is_empty() {
+            let take = bytes.len().min(width - row.len());
+            row.extend_from_slice(&bytes[..take]);
+            bytes = &bytes[take..];
+            if row.len() == width {
+                write_row(row)?;
+                row.clear();
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Like [`read_rows`], with each byte classified by [`read_groups`].
+pub(crate) fn read_classified_rows<R, F>(
+    reader: &mut R,
+    numeric: &mut Numeric,
+    width: usize,
+    row: &mut Row,
+    mut write_row: F,
+) -> io::Result<()>
+where
+    R: Read,
+    F: FnMut(&Row) -> io::Result<()>,
+{
+    read_groups(reader, numeric, |group| {
+        for (i, &byte) in group.span.bytes.iter().enumerate() {
+            row.bytes.push(byte);
+            row.kinds.push(group.kind);
+            match group.kind {
+                Kind::Printable | Kind::Numeric => row.ascii.push(byte as char),
+                Kind::Graphemes if i == 0 => row.ascii.push_str(group.span.parsed.unwrap()),
+                Kind::Graphemes => (),
+                Kind::Control | Kind::Invalid => row.ascii.push('.'),
+            }
+
+            if row.bytes.len() == width {
+                write_row(row)?;
+                row.clear();
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_rows() {
+        let mut rows = Vec::new();
+        let mut row = Vec::new();
+        let mut write_row = |bytes: &[u8]| {
+            rows.push(bytes.to_vec());
+            Ok(())
+        };
+        read_rows(&mut &b"abcde"[..], 2, &mut row, &mut write_row).unwrap();
+        read_rows(&mut &b"fg"[..], 2, &mut row, &mut write_row).unwrap();
+
+        // Rows continue across readers, and the last partial row is left over.
+        assert_eq!(rows, [&b"ab"[..], b"cd", b"ef"]);
+        assert_eq!(row, b"g");
+    }
+
+    #[test]
+    fn test_read_classified_rows() {
+        let mut rows = Vec::new();
+        let mut row = Row::default();
+        let mut numeric = Numeric::Decimal;
+        read_classified_rows(&mut "a1€".as_bytes(), &mut numeric, 3, &mut row, |row| {
+            rows.push((row.bytes.clone(), row.ascii.clone()));
+            Ok(())
+        })
+        .unwrap();
+
+        // Grapheme clusters are shown in the row where they start.
+        assert_eq!(rows, [(b"a1\xe2".to_vec(), "a1€".to_string())]);
+        assert_eq!(row.bytes, b"\x82\xac");
+        assert_eq!(row.ascii, "");
+        assert!(row.kinds == [Kind::Graphemes, Kind::Graphemes]);
+    }
+}
@@ -0,0 +1,98 @@
+use colorz::{ansi::AnsiColor, rgb::RgbColor, xterm::XtermColor, Color};
+
+/// A value gradient for coloring bytes, limited to the colors supported by the terminal.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) enum ColorScale {
+    /// Three shades of gray from the basic ANSI colors.
+    Ansi,
+
+    /// The 24-step grayscale ramp from the 256-color palette.
+    Xterm,
+
+    /// A full 256-step grayscale gradient with 24-bit color.
+    Rgb,
+}
+
+impl ColorScale {
+    /// Detect the color scale supported by the terminal from the `COLORTERM` and `TERM`
+    /// environment variables.
+    pub(crate) fn detect() -> Self {
+        let var = |name| std::env::var(name).unwrap_or_default();
+
+        Self::from_env(&var("COLORTERM"), &var("TERM"))
+    }
+
+    fn from_env(colorterm: &str, term: &str) -> Self {
+        if matches!(colorterm, "truecolor" | "24bit") {
+            Self::Rgb
+        } else if term.contains("256") {
+            Self::Xterm
+        } else {
+            Self::Ansi
+        }
+    }
+
+    /// Get the color for a byte, from dark (`0x00`) to bright (`0xff`).
+    pub(crate) fn color(self, byte: u8) -> Color {
+        let byte = usize::from(byte);
+
+        match self {
+            Self::Ansi => {
+                const SHADES: [AnsiColor; 3] = [
+                    AnsiColor::BrightBlack,
+                    AnsiColor::White,
+                    AnsiColor::BrightWhite,
+                ];
+                Color::Ansi(SHADES[byte * SHADES.len() / 256])
+            }
+            Self::Xterm => Color::Xterm(XtermColor::from_code(232 + (byte * 24 / 256) as u8)),
+            Self::Rgb => {
+                // Keep the darkest bytes visible on a dark background.
+                let level = (0x30 + byte * (0xff - 0x30) / 0xff) as u8;
+                Color::Rgb(RgbColor {
+                    red: level,
+                    green: level,
+                    blue: level,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(ColorScale::from_env("truecolor", "xterm"), ColorScale::Rgb);
+        assert_eq!(ColorScale::from_env("24bit", ""), ColorScale::Rgb);
+        assert_eq!(
+            ColorScale::from_env("", "xterm-256color"),
+            ColorScale::Xterm
+        );
+        assert_eq!(ColorScale::from_env("", "xterm"), ColorScale::Ansi);
+    }
+
+    #[test]
+    fn test_color() {
+        let ansi = |byte| ColorScale::Ansi.color(byte);
+        assert_eq!(ansi(0x00), Color::Ansi(AnsiColor::BrightBlack));
+        assert_eq!(ansi(0x80), Color::Ansi(AnsiColor::White));
+        assert_eq!(ansi(0xff), Color::Ansi(AnsiColor::BrightWhite));
+
+        let xterm = |byte| ColorScale::Xterm.color(byte);
+        assert_eq!(xterm(0x00), Color::Xterm(XtermColor::from_code(232)));
+        assert_eq!(xterm(0xff), Color::Xterm(XtermColor::from_code(255)));
+
+        let gray = |level| {
+            Color::Rgb(RgbColor {
+                red: level,
+                green: level,
+                blue: level,
+            })
+        };
+        assert_eq!(ColorScale::Rgb.color(0x00), gray(0x30));
+        assert_eq!(ColorScale::Rgb.color(0xff), gray(0xff));
+    }
+}
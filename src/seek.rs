@@ -0,0 +1,84 @@
+use std::io::{self, Cursor, Read};
+
+/// Size of each read while scanning.
+const CHUNK: usize = 4096;
+
+/// Scan a reader for the first occurrence of a byte pattern.
+///
+/// Returns the offset of the match, and a reader starting with the matched pattern. Returns `None`
+/// when the pattern is not found. The last `pattern.len() - 1` bytes of each read are kept as
+/// lookback, so matches that span reads are found.
+pub(crate) fn seek_to<'a>(
+    mut reader: Box<dyn Read + 'a>,
+    pattern: &[u8],
+) -> io::Result<Option<(u64, Box<dyn Read + 'a>)>> {
+    debug_assert!(!pattern.is_empty(), "Cannot seek to an empty pattern");
+
+    let lookback = pattern.len() - 1;
+    let mut buf = Vec::with_capacity(lookback + CHUNK);
+    let mut offset = 0;
+
+    loop {
+        let start = buf.len();
+        buf.resize(start + CHUNK, 0);
+        let size = reader.read(&mut buf[start..])?;
+        buf.truncate(start + size);
+        if size == 0 {
+            return Ok(None);
+        }
+
+        if let Some(pos) = buf
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+        {
+            buf.drain(..pos);
+            let found = Cursor::new(buf).chain(reader);
+
+            return Ok(Some((offset + pos as u64, Box::new(found))));
+        }
+
+        let keep = buf.len().min(lookback);
+        offset += (buf.len() - keep) as u64;
+        buf.drain(..buf.len() - keep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seek(input: &[u8], pattern: &[u8]) -> Option<(u64, Vec<u8>)> {
+        let (offset, mut reader) = seek_to(Box::new(input), pattern).unwrap()?;
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+
+        Some((offset, output))
+    }
+
+    #[test]
+    fn test_seek_to() {
+        assert_eq!(seek(b"abcdef", b"cd"), Some((2, b"cdef".to_vec())));
+        assert_eq!(seek(b"abcdef", b"a"), Some((0, b"abcdef".to_vec())));
+        assert_eq!(seek(b"abcdef", b"ef"), Some((4, b"ef".to_vec())));
+        assert_eq!(seek(b"abcdef", b"fg"), None);
+        assert_eq!(seek(b"", b"a"), None);
+    }
+
+    #[test]
+    fn test_seek_to_across_reads() {
+        // The pattern straddles the end of the first read.
+        let mut input = vec![0; CHUNK - 2];
+        input.extend([0xff, 0xd8, 0xff, 0xe0]);
+        input.extend(vec![1; CHUNK * 2]);
+        input.extend([0xff, 0xd9]);
+
+        let (offset, output) = seek(&input, &[0xff, 0xd8, 0xff]).unwrap();
+        assert_eq!(offset, CHUNK as u64 - 2);
+        assert_eq!(output, input[CHUNK - 2..]);
+
+        // The lookback is counted in later offsets.
+        let (offset, output) = seek(&input, &[0xff, 0xd9]).unwrap();
+        assert_eq!(offset, input.len() as u64 - 2);
+        assert_eq!(output, [0xff, 0xd9]);
+    }
+}
@@ -0,0 +1,83 @@
+/// Number of bytes sampled from the start of the input.
+pub(crate) const SAMPLE_SIZE: u64 = 64 * 1024;
+
+/// Largest stride that can be detected.
+const MAX_STRIDE: usize = 1024;
+
+/// Detect the size of fixed-size records from a sample of bytes.
+///
+/// This uses autocorrelation: for each lag (stride candidate) in `2..=1024`, the score is the
+/// fraction of bytes equal to the byte one lag later. Arrays of records score highly at the record
+/// size and its multiples, because fields with the same purpose line up. Only lags with at least
+/// four repetitions in the sample are considered.
+///
+/// The best score must exceed the average score of all lags by `0.25`, so uniform data (like
+/// all zeros) and data without periodicity detect nothing. The smallest lag scoring within 90% of
+/// the best is chosen, to prefer the record size over its multiples.
+pub(crate) fn detect(sample: &[u8]) -> Option<usize> {
+    let max = MAX_STRIDE.min(sample.len() / 4);
+    if max < 2 {
+        return None;
+    }
+
+    let scores: Vec<_> = (2..=max)
+        .map(|lag| {
+            let matches = sample
+                .iter()
+                .zip(&sample[lag..])
+                .filter(|(a, b)| a == b)
+                .count();
+
+            matches as f64 / (sample.len() - lag) as f64
+        })
+        .collect();
+    let best = scores.iter().copied().fold(0.0, f64::max);
+    let mean = scores.iter().sum::<f64>() / scores.len() as f64;
+    if best - mean < 0.25 {
+        return None;
+    }
+
+    let index = scores.iter().position(|&score| score >= best * 0.9)?;
+
+    Some(index + 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pseudorandom bytes from a linear congruential generator.
+    fn noise(len: usize) -> Vec<u8> {
+        let mut state = 1_u32;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                (state >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_detect() {
+        // 24-byte records: a counter, a magic number, random data, and padding.
+        let mut records = Vec::new();
+        for (i, data) in noise(100 * 12).chunks(12).enumerate() {
+            records.extend((i as u32).to_le_bytes());
+            records.extend(b"REC\x01");
+            records.extend(data);
+            records.extend([0; 4]);
+        }
+        assert_eq!(detect(&records), Some(24));
+
+        let text = "name=alpha;\n".repeat(50);
+        assert_eq!(detect(text.as_bytes()), Some(12));
+    }
+
+    #[test]
+    fn test_detect_none() {
+        assert_eq!(detect(&noise(4096)), None);
+        assert_eq!(detect(&[0; 4096]), None);
+        assert_eq!(detect(b"abc"), None);
+        assert_eq!(detect(b""), None);
+    }
+}
@@ -0,0 +1,213 @@
+use std::ops::Range;
+
+/// Largest raw bytes field. Fields are collected in memory and shown in full, like a row at the
+/// largest width.
+const MAX_BYTES: usize = 4096;
+
+/// Type of a struct field.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum FieldType {
+    /// A number of raw bytes.
+    Bytes(usize),
+
+    /// An integer with a size of 1, 2, 4, or 8 bytes.
+    Int {
+        size: usize,
+        signed: bool,
+        big_endian: bool,
+    },
+}
+
+/// A named field in a struct spec.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Field {
+    /// Name shown in annotations.
+    pub(crate) name: String,
+
+    /// Offset of the field from the start of the struct.
+    pub(crate) offset: usize,
+
+    /// Type of the field.
+    pub(crate) ty: FieldType,
+}
+
+impl Field {
+    /// Get the range of offsets covered by the field.
+    pub(crate) fn range(&self) -> Range<usize> {
+        let size = match self.ty {
+            FieldType::Bytes(size) | FieldType::Int { size, .. } => size,
+        };
+
+        self.offset..self.offset + size
+    }
+
+    /// Decode the value of the field from its bytes.
+    ///
+    /// Integers are decimal. Bytes are a quoted string when they are printable ASCII followed by
+    /// optional zero padding, and hex otherwise.
+    pub(crate) fn decode(&self, bytes: &[u8]) -> String {
+        match self.ty {
+            FieldType::Bytes(_) => {
+                let end = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+                let text = &bytes[..end];
+                if !text.is_empty() && text.iter().all(|b| (0x20..=0x7e).contains(b)) {
+                    format!("{:?}", String::from_utf8_lossy(text))
+                } else {
+                    bytes.iter().map(|b| format!("{b:02x}")).collect()
+                }
+            }
+            FieldType::Int {
+                size,
+                signed,
+                big_endian,
+            } => {
+                let fold = |value: u64, &b: &u8| value << 8 | u64::from(b);
+                let value = if big_endian {
+                    bytes.iter().fold(0, fold)
+                } else {
+                    bytes.iter().rev().fold(0, fold)
+                };
+
+                if signed {
+                    let shift = 64 - size * 8;
+                    ((value << shift) as i64 >> shift).to_string()
+                } else {
+                    value.to_string()
+                }
+            }
+        }
+    }
+}
+
+/// Parse a struct spec into fields, laid out one after another.
+///
+/// The spec is a comma-separated list of `name:type` fields, where `type` is one of:
+///  - A byte count, like `16`: Raw bytes, shown as a string or hex, up to 4096
+///  - `u8` or `i8`: An unsigned or signed byte
+///  - `u16`, `u32`, `u64`, `i16`, `i32`, or `i64`: A little-endian integer; add a `be` suffix
+///    (e.g. `u32be`) for big-endian, or `le` to be explicit
+pub(crate) fn parse(spec: &str) -> Option<Vec<Field>> {
+    let mut fields = Vec::new();
+    let mut offset = 0;
+
+    for field in spec.split(',') {
+        let (name, ty) = field.trim().split_once(':')?;
+        if name.is_empty() {
+            return None;
+        }
+        let ty = parse_type(ty)?;
+
+        let size = match ty {
+            FieldType::Bytes(size) | FieldType::Int { size, .. } => size,
+        };
+
+        fields.push(Field {
+            name: name.to_string(),
+            offset,
+            ty,
+        });
+        offset = offset.checked_add(size)?;
+    }
+
+    Some(fields)
+}
+
+/// Parse the type of a field.
+fn parse_type(ty: &str) -> Option<FieldType> {
+    if let Ok(size) = ty.parse() {
+        return (1..=MAX_BYTES)
+            .contains(&size)
+            .then_some(FieldType::Bytes(size));
+    }
+
+    let (ty, big_endian) = match (ty.strip_suffix("le"), ty.strip_suffix("be")) {
+        (Some(ty), _) => (ty, false),
+        (_, Some(ty)) => (ty, true),
+        _ => (ty, false),
+    };
+    let (signed, bits) = match ty.split_at(ty.len().min(1)) {
+        ("u", bits) => (false, bits),
+        ("i", bits) => (true, bits),
+        _ => return None,
+    };
+    let size = match bits {
+        "8" => 1,
+        "16" => 2,
+        "32" => 4,
+        "64" => 8,
+        _ => return None,
+    };
+
+    Some(FieldType::Int {
+        size,
+        signed,
+        big_endian,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let fields = parse("magic:4,version:u16be, flags:u8,offset:i32").unwrap();
+        let ranges: Vec<_> = fields.iter().map(Field::range).collect();
+        assert_eq!(ranges, [0..4, 4..6, 6..7, 7..11]);
+        assert_eq!(fields[0].name, "magic");
+        assert_eq!(fields[0].ty, FieldType::Bytes(4));
+        assert_eq!(
+            fields[1].ty,
+            FieldType::Int {
+                size: 2,
+                signed: false,
+                big_endian: true
+            }
+        );
+        assert_eq!(
+            fields[3].ty,
+            FieldType::Int {
+                size: 4,
+                signed: true,
+                big_endian: false
+            }
+        );
+
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("magic"), None);
+        assert_eq!(parse(":4"), None);
+        assert_eq!(parse("magic:0"), None);
+        assert_eq!(parse("magic:u24"), None);
+        assert_eq!(parse("magic:f32"), None);
+        assert_eq!(parse("magic:4,"), None);
+        assert_eq!(parse("magic:4097"), None);
+        assert_eq!(parse("a:u8,b:18446744073709551615"), None);
+
+        // Offsets of many large fields do not overflow.
+        let spec = vec!["a:4096"; 1 << 10].join(",");
+        assert_eq!(
+            parse(&spec).unwrap().last().unwrap().range(),
+            0x3ff000..0x400000
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        let decode = |ty, bytes: &[u8]| {
+            let field = parse(&format!("field:{ty}")).unwrap().remove(0);
+            field.decode(bytes)
+        };
+
+        assert_eq!(decode("u16le", b"\x01\x02"), "513");
+        assert_eq!(decode("u16be", b"\x01\x02"), "258");
+        assert_eq!(decode("i8", b"\xff"), "-1");
+        assert_eq!(decode("i32be", b"\xff\xff\xff\xfe"), "-2");
+        assert_eq!(
+            decode("u64", b"\xff\xff\xff\xff\xff\xff\xff\xff"),
+            "18446744073709551615"
+        );
+        assert_eq!(decode("8", b"name\0\0\0\0"), "\"name\"");
+        assert_eq!(decode("4", b"\x7fELF"), "7f454c46");
+        assert_eq!(decode("2", b"\0\0"), "0000");
+    }
+}
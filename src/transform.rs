@@ -0,0 +1,170 @@
+use std::io::{self, Read};
+
+/// A byte transform for reading obfuscated data.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Transform {
+    /// XOR with a repeating key, aligned to input offsets.
+    Xor(Vec<u8>),
+
+    /// Add a constant, wrapping at 256.
+    Add(u8),
+
+    /// Rotate ASCII letters by 13 places.
+    Rot13,
+}
+
+impl Transform {
+    /// Apply the transform to the byte at `offset` in the input.
+    fn apply(&self, byte: u8, offset: u64) -> u8 {
+        match self {
+            Self::Xor(key) => byte ^ key[(offset % key.len() as u64) as usize],
+            Self::Add(value) => byte.wrapping_add(*value),
+            Self::Rot13 => match byte {
+                b'a'..=b'z' => (byte - b'a' + 13) % 26 + b'a',
+                b'A'..=b'Z' => (byte - b'A' + 13) % 26 + b'A',
+                _ => byte,
+            },
+        }
+    }
+}
+
+/// A reader that applies transforms to each byte of its input, in order.
+///
+/// Offsets are absolute in the input, so a reader that starts partway through the input continues
+/// a repeating XOR key where it would be at that offset.
+pub(crate) struct Transformed<R: Read> {
+    /// The wrapped reader.
+    reader: R,
+
+    /// Transforms applied to each byte, in order.
+    transforms: Vec<Transform>,
+
+    /// Offset of the next byte in the input.
+    offset: u64,
+}
+
+impl<R: Read> Transformed<R> {
+    /// Create a reader that transforms bytes, starting at `offset` in the input.
+    pub(crate) fn new(reader: R, transforms: Vec<Transform>, offset: u64) -> Self {
+        Self {
+            reader,
+            transforms,
+            offset,
+        }
+    }
+}
+
+impl<R: Read> Read for Transformed<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let size = self.reader.read(buf)?;
+        for byte in &mut buf[..size] {
+            *byte = self
+                .transforms
+                .iter()
+                .fold(*byte, |byte, transform| transform.apply(byte, self.offset));
+            self.offset += 1;
+        }
+
+        Ok(size)
+    }
+}
+
+/// Parse a hex XOR key, like `5a` or `deadbeef`, with an optional `0x` prefix.
+pub(crate) fn parse_key(s: &str) -> Option<Vec<u8>> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    if digits.is_empty() || digits.len() % 2 != 0 || !digits.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return None;
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transform(input: &[u8], transforms: &[Transform], offset: u64) -> Vec<u8> {
+        let mut output = Vec::new();
+        Transformed::new(input, transforms.to_vec(), offset)
+            .read_to_end(&mut output)
+            .unwrap();
+
+        output
+    }
+
+    #[test]
+    fn test_xor() {
+        let xor = |key: &[u8]| [Transform::Xor(key.to_vec())];
+        assert_eq!(
+            transform(b"\x00\x01\xff", &xor(b"\x5a"), 0),
+            b"\x5a\x5b\xa5"
+        );
+        assert_eq!(
+            transform(b"\x00\x00\x00\x00\x00", &xor(b"\xde\xad\xbe\xef"), 0),
+            b"\xde\xad\xbe\xef\xde"
+        );
+
+        // The key is aligned to the starting offset.
+        assert_eq!(
+            transform(b"\x00\x00\x00", &xor(b"\xde\xad\xbe\xef"), 6),
+            b"\xbe\xef\xde"
+        );
+
+        // XOR is its own inverse.
+        let key = xor(b"key");
+        assert_eq!(
+            transform(&transform(b"secret data", &key, 0), &key, 0),
+            b"secret data"
+        );
+    }
+
+    #[test]
+    fn test_add() {
+        assert_eq!(
+            transform(b"\x00\x7f\xff", &[Transform::Add(1)], 0),
+            b"\x01\x80\x00"
+        );
+        assert_eq!(transform(b"b", &[Transform::Add(255)], 0), b"a");
+    }
+
+    #[test]
+    fn test_rot13() {
+        let rot13 = [Transform::Rot13];
+        assert_eq!(
+            transform(b"Hello, World! 123 xyz\xff", &rot13, 0),
+            b"Uryyb, Jbeyq! 123 klm\xff"
+        );
+        assert_eq!(
+            transform(&transform(b"Round Trip", &rot13, 0), &rot13, 0),
+            b"Round Trip"
+        );
+    }
+
+    #[test]
+    fn test_transform_order() {
+        // XOR with 0x20 swaps the case of `A`, then adding 1 makes it `b`, and ROT13 makes it `o`.
+        let transforms = [
+            Transform::Xor(vec![0x20]),
+            Transform::Add(1),
+            Transform::Rot13,
+        ];
+        assert_eq!(transform(b"A", &transforms, 0), b"o");
+    }
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(parse_key("5a"), Some(vec![0x5a]));
+        assert_eq!(parse_key("DEADbeef"), Some(vec![0xde, 0xad, 0xbe, 0xef]));
+        assert_eq!(parse_key("0x01ff"), Some(vec![0x01, 0xff]));
+        assert_eq!(parse_key(""), None);
+        assert_eq!(parse_key("0x"), None);
+        assert_eq!(parse_key("abc"), None);
+        assert_eq!(parse_key("zz"), None);
+        assert_eq!(parse_key("+1"), None);
+        assert_eq!(parse_key("€a"), None);
+    }
+}
@@ -0,0 +1,123 @@
+use std::cell::Cell;
+use std::io::{self, Read};
+
+/// A reader that drops the zero bytes at the end of its input.
+///
+/// Runs of zeros are held back until a non-zero byte follows them, so this works on streams with
+/// no known length. The number of dropped bytes is added to a shared count when the input ends.
+pub(crate) struct TrimZeros<'a, R: Read> {
+    /// The wrapped reader.
+    reader: R,
+
+    /// Total number of trimmed zero bytes.
+    trimmed: &'a Cell<u64>,
+
+    /// Zeros that are followed by data, and must be returned before it.
+    leading: u64,
+
+    /// Zeros at the end of the data read so far, held back until more data follows.
+    held: u64,
+
+    /// Data read ahead, which does not end with a zero.
+    data: Vec<u8>,
+
+    /// Position of the next byte in `data`.
+    pos: usize,
+}
+
+impl<'a, R: Read> TrimZeros<'a, R> {
+    /// Create a reader that trims trailing zeros, adding the number trimmed to `trimmed`.
+    pub(crate) fn new(reader: R, trimmed: &'a Cell<u64>) -> Self {
+        Self {
+            reader,
+            trimmed,
+            leading: 0,
+            held: 0,
+            data: vec![0; 4096],
+            pos: 4096,
+        }
+    }
+}
+
+impl<R: Read> Read for TrimZeros<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if self.leading > 0 {
+                let size = buf
+                    .len()
+                    .min(usize::try_from(self.leading).unwrap_or(usize::MAX));
+                buf[..size].fill(0);
+                self.leading -= size as u64;
+
+                return Ok(size);
+            }
+            if self.pos < self.data.len() {
+                let size = buf.len().min(self.data.len() - self.pos);
+                buf[..size].copy_from_slice(&self.data[self.pos..self.pos + size]);
+                self.pos += size;
+
+                return Ok(size);
+            }
+
+            self.data.resize(self.data.capacity(), 0);
+            let size = self.reader.read(&mut self.data)?;
+            if size == 0 {
+                self.trimmed.set(self.trimmed.get() + self.held);
+                self.held = 0;
+                self.data.clear();
+
+                return Ok(0);
+            }
+
+            // The held zeros are followed by data when this read has a non-zero byte.
+            match self.data[..size].iter().rposition(|&byte| byte != 0) {
+                Some(last) => {
+                    self.leading = self.held;
+                    self.held = (size - last - 1) as u64;
+                    self.data.truncate(last + 1);
+                }
+                None => {
+                    self.held += size as u64;
+                    self.data.clear();
+                }
+            }
+            self.pos = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trim(input: &[u8]) -> (Vec<u8>, u64) {
+        let trimmed = Cell::new(0);
+        let mut output = Vec::new();
+        TrimZeros::new(input, &trimmed)
+            .read_to_end(&mut output)
+            .unwrap();
+
+        (output, trimmed.get())
+    }
+
+    #[test]
+    fn test_trim_zeros() {
+        assert_eq!(trim(b"ab\0c\0\0\0"), (b"ab\0c".to_vec(), 3));
+        assert_eq!(trim(b"\0\0\0"), (vec![], 3));
+        assert_eq!(trim(b"abc"), (b"abc".to_vec(), 0));
+        assert_eq!(trim(b""), (vec![], 0));
+    }
+
+    #[test]
+    fn test_trim_zeros_across_reads() {
+        // Zeros longer than the read buffer are returned once data follows them.
+        let mut input = b"a".to_vec();
+        input.extend([0; 10000]);
+        input.push(b'b');
+        input.extend([0; 5000]);
+
+        let (output, trimmed) = trim(&input);
+        assert_eq!(output, input[..10002]);
+        assert_eq!(trimmed, 5000);
+    }
+}
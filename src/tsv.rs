@@ -0,0 +1,150 @@
+use crate::rows::{read_classified_rows, Row};
+use crate::Numeric;
+use std::io::{self, Read, Write};
+
+/// Writes rows of bytes as tab-separated values, for importing into spreadsheets.
+///
+/// The first line is a header naming the columns, and each following line is one row:
+///
+/// - `offset`: Byte offset of the start of the row, in decimal
+/// - `00`, `01`, ...: One column for each byte in the row, as two lowercase hex digits. Cells past
+///   the end of a short final row are empty.
+/// - `ascii`: The row's character table, with `.` for control and invalid bytes. Grapheme clusters
+///   are shown in the row where they start.
+///
+/// Cells containing a `"` are quoted, with the quotes doubled.
+pub(crate) struct Tsv<W: Write> {
+    /// The writer receiving TSV.
+    writer: W,
+
+    /// Number of bytes per row.
+    width: usize,
+
+    /// Numeric classification for groups.
+    numeric: Numeric,
+
+    /// Offset of the current row.
+    offset: usize,
+
+    /// Number of rows written.
+    rows: usize,
+
+    /// The current row.
+    row: Row,
+}
+
+impl<W: Write> Tsv<W> {
+    /// Create a TSV writer wrapping a writer, with `width` bytes per row.
+    pub(crate) fn new(writer: W, width: usize, numeric: Numeric) -> Self {
+        Self {
+            writer,
+            width,
+            numeric,
+            offset: 0,
+            rows: 0,
+            row: Row::default(),
+        }
+    }
+
+    /// Write rows for all bytes from a reader.
+    ///
+    /// Rows and offsets continue across multiple readers.
+    pub(crate) fn write<R>(&mut self, reader: &mut R) -> io::Result<()>
+    where
+        R: Read,
+    {
+        let mut numeric = self.numeric;
+        let mut row = std::mem::take(&mut self.row);
+        let result = read_classified_rows(reader, &mut numeric, self.width, &mut row, |row| {
+            self.write_row(row)
+        });
+        self.numeric = numeric;
+        self.row = row;
+
+        result
+    }
+
+    /// Write the last partial row and flush the writer.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        let row = std::mem::take(&mut self.row);
+        if !row.is_empty() {
+            self.write_row(&row)?;
+        }
+
+        self.writer.flush()
+    }
+
+    /// Write a row. The header is written before the first row.
+    fn write_row(&mut self, row: &Row) -> io::Result<()> {
+        if self.rows == 0 {
+            let columns: Vec<_> = (0..self.width).map(|i| format!("{i:02x}")).collect();
+            writeln!(self.writer, "offset\t{}\tascii", columns.join("\t"))?;
+        }
+
+        let cells: Vec<_> = (0..self.width)
+            .map(|i| match row.bytes.get(i) {
+                Some(byte) => format!("{byte:02x}"),
+                None => String::new(),
+            })
+            .collect();
+        writeln!(
+            self.writer,
+            "{}\t{}\t{}",
+            self.offset,
+            cells.join("\t"),
+            quote(&row.ascii)
+        )?;
+
+        self.offset += row.bytes.len();
+        self.rows += 1;
+
+        Ok(())
+    }
+}
+
+/// Quote a cell when it contains a `"`, so spreadsheets keep the quotes.
+///
+/// Tabs and line breaks are control characters, which are never in the character table.
+fn quote(s: &str) -> String {
+    if s.contains('"') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tsv(input: &[u8], width: usize) -> String {
+        let mut output = Vec::new();
+        let mut tsv = Tsv::new(&mut output, width, Numeric::Decimal);
+        tsv.write(&mut &input[..]).unwrap();
+        tsv.finish().unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_tsv() {
+        assert_eq!(
+            tsv(b"a1\t\xff\"", 3),
+            "\
+                offset\t00\t01\t02\tascii\n\
+                0\t61\t31\t09\ta1.\n\
+                3\tff\t22\t\t\".\"\"\"\n"
+        );
+        assert_eq!(
+            tsv("€!".as_bytes(), 4),
+            "offset\t00\t01\t02\t03\tascii\n0\te2\t82\tac\t21\t€!\n"
+        );
+        assert_eq!(tsv(b"", 3), "");
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("plain"), "plain");
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,75 @@
+use std::io;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// The size and modification time of a file, which change when it is written.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Stamp {
+    /// File size in bytes.
+    len: u64,
+
+    /// Modification time, when the platform has one.
+    modified: Option<SystemTime>,
+}
+
+impl Stamp {
+    /// Get the stamp of the file at a path.
+    pub(crate) fn of(path: &Path) -> io::Result<Self> {
+        let metadata = std::fs::metadata(path)?;
+
+        Ok(Self {
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// Detects changes to a file from its stamps, polled over time.
+///
+/// A file that is missing (e.g. while it is replaced) is not a change, but the next stamp after it
+/// is. This catches rewrites and truncation as well as appends, since any difference in size or
+/// modification time counts.
+#[derive(Default)]
+pub(crate) struct Watcher {
+    /// The stamp from the last poll.
+    last: Option<Stamp>,
+}
+
+impl Watcher {
+    /// Check if the file changed since the last poll. The first stamp is always a change.
+    pub(crate) fn poll(&mut self, stamp: Option<Stamp>) -> bool {
+        let changed = stamp.is_some() && stamp != self.last;
+        self.last = stamp;
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_watcher() {
+        let stamp = |len, secs| Stamp {
+            len,
+            modified: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+        };
+        let mut watcher = Watcher::default();
+
+        assert!(watcher.poll(Some(stamp(16, 1))));
+        assert!(!watcher.poll(Some(stamp(16, 1))));
+
+        // Rewritten in place with the same size.
+        assert!(watcher.poll(Some(stamp(16, 2))));
+
+        // Truncated.
+        assert!(watcher.poll(Some(stamp(0, 2))));
+
+        // Replaced with an identical file.
+        assert!(!watcher.poll(None));
+        assert!(!watcher.poll(None));
+        assert!(watcher.poll(Some(stamp(0, 2))));
+    }
+}
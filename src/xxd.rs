@@ -0,0 +1,155 @@
+use crate::rows::read_rows;
+use std::io::{self, Read, Write};
+
+/// Writes rows of bytes in the same format as `xxd`, for tools that parse its output.
+///
+/// Each line is an 8-digit lowercase hex offset and a colon, the row's bytes as hex in groups of
+/// two, padded to a full row, then two spaces and the row as ASCII with `.` for bytes outside of
+/// `0x20..=0x7e`:
+///
+/// ```text
+/// 00000000: 6865 6c6c 6f0a                           hello.
+/// ```
+///
+/// The output matches `xxd -c WIDTH`, and `xxd -u` when `uppercase` is set.
+pub(crate) struct Xxd<W: Write> {
+    /// The writer receiving the dump.
+    writer: W,
+
+    /// Number of bytes per row.
+    width: usize,
+
+    /// Write hex bytes in uppercase. Offsets are always lowercase.
+    uppercase: bool,
+
+    /// Offset of the current row.
+    offset: u64,
+
+    /// Bytes in the current row.
+    row: Vec<u8>,
+}
+
+impl<W: Write> Xxd<W> {
+    /// Create an `xxd` writer wrapping a writer, with `width` bytes per row.
+    pub(crate) fn new(writer: W, width: usize, uppercase: bool) -> Self {
+        Self {
+            writer,
+            width,
+            uppercase,
+            offset: 0,
+            row: Vec::with_capacity(width),
+        }
+    }
+
+    /// Set the offset of the next row, like `xxd -s` for an input that starts past offset 0.
+    pub(crate) fn set_offset(&mut self, offset: u64) {
+        self.offset = offset;
+    }
+
+    /// Write rows for all bytes from a reader.
+    ///
+    /// Rows and offsets continue across multiple readers.
+    pub(crate) fn write<R>(&mut self, reader: &mut R) -> io::Result<()>
+    where
+        R: Read,
+    {
+        let mut row = std::mem::take(&mut self.row);
+        let result = read_rows(reader, self.width, &mut row, |row| self.write_row(row));
+        self.row = row;
+
+        result
+    }
+
+    /// Write the last partial row and flush the writer.
+    pub(crate) fn finish(mut self) -> io::Result<()> {
+        let row = std::mem::take(&mut self.row);
+        if !row.is_empty() {
+            self.write_row(&row)?;
+        }
+
+        self.writer.flush()
+    }
+
+    /// Write a row.
+    fn write_row(&mut self, row: &[u8]) -> io::Result<()> {
+        let mut hex = String::with_capacity(self.width * 5 / 2);
+        for (i, byte) in row.iter().enumerate() {
+            if i > 0 && i % 2 == 0 {
+                hex.push(' ');
+            }
+            if self.uppercase {
+                hex.push_str(&format!("{byte:02X}"));
+            } else {
+                hex.push_str(&format!("{byte:02x}"));
+            }
+        }
+        let ascii: String = row
+            .iter()
+            .map(|&byte| match byte {
+                0x20..=0x7e => byte as char,
+                _ => '.',
+            })
+            .collect();
+        let hex_width = self.width * 2 + (self.width - 1) / 2;
+        writeln!(
+            self.writer,
+            "{:08x}: {hex:hex_width$}  {ascii}",
+            self.offset
+        )?;
+
+        // Offsets wrap around like the addresses of the pretty printer.
+        self.offset = self.offset.wrapping_add(row.len() as u64);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xxd(input: &[u8], width: usize, uppercase: bool) -> String {
+        let mut output = Vec::new();
+        let mut xxd = Xxd::new(&mut output, width, uppercase);
+        xxd.write(&mut &input[..]).unwrap();
+        xxd.finish().unwrap();
+
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_xxd() {
+        // Expected output is from `xxd` 2022-01-14 by Juergen Weigert et al.
+        assert_eq!(
+            xxd(b"hello\n", 16, false),
+            "00000000: 6865 6c6c 6f0a                           hello.\n"
+        );
+        assert_eq!(
+            xxd(b"hello world, this is xxd\x00\x01\xff~\x7f", 16, false),
+            "\
+                00000000: 6865 6c6c 6f20 776f 726c 642c 2074 6869  hello world, thi\n\
+                00000010: 7320 6973 2078 7864 0001 ff7e 7f         s is xxd...~.\n"
+        );
+        assert_eq!(xxd(b"abc", 4, false), "00000000: 6162 63    abc\n");
+        assert_eq!(xxd(b"abcde", 5, false), "00000000: 6162 6364 65  abcde\n");
+        assert_eq!(
+            xxd(b"\xab\xcd", 16, true),
+            "00000000: ABCD                                     ..\n"
+        );
+        assert_eq!(xxd(b"", 16, false), "");
+    }
+
+    #[test]
+    fn test_xxd_offset() {
+        let mut output = Vec::new();
+        let mut xxd = Xxd::new(&mut output, 4, false);
+        xxd.set_offset(0x20);
+        xxd.write(&mut &b"abcdef"[..]).unwrap();
+        xxd.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "00000020: 6162 6364  abcd\n00000024: 6566       ef\n"
+        );
+    }
+}
@@ -0,0 +1,1397 @@
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Output, Stdio};
+
+/// A temporary directory for test files, removed when dropped so failed tests clean up too.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Create an empty directory, named for the test and unique to this process.
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("hd-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        Self(dir)
+    }
+
+    /// Write a file in the directory, returning its path.
+    fn write(&self, name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.0.join(name);
+        std::fs::write(&path, contents).unwrap();
+
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Create an `hd` command that ignores the user's environment: the config file, `HD_SKIP`,
+/// `HD_LENGTH`, and `COLUMNS`.
+fn hd_command() -> Command {
+    let config = Path::new(env!("CARGO_TARGET_TMPDIR")).join("empty-config");
+    std::fs::create_dir_all(&config).unwrap();
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_hd"));
+    command
+        .env("XDG_CONFIG_HOME", config)
+        .env_remove("HD_SKIP")
+        .env_remove("HD_LENGTH")
+        .env_remove("COLUMNS");
+
+    command
+}
+
+/// Run `hd` with the given arguments and colors disabled.
+fn hd(args: &[&str]) -> Output {
+    let output = hd_command()
+        .args(args)
+        .env_remove("ALWAYS_COLOR")
+        .env_remove("CLICOLOR_FORCE")
+        .env_remove("FORCE_COLOR")
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    output
+}
+
+/// Run `hd` with the given arguments and colors disabled, for commands that fail. Returns the exit
+/// status and stderr.
+fn hd_err(args: &[&str]) -> (ExitStatus, String) {
+    let output = hd_command()
+        .args(args)
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+
+    (
+        output.status,
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+/// Run `hd` with the given arguments and colors disabled, reading `input` from stdin.
+fn hd_stdin(args: &[&str], input: &[u8]) -> String {
+    let mut command = hd_command();
+    command
+        .env_remove("ALWAYS_COLOR")
+        .env_remove("CLICOLOR_FORCE")
+        .env_remove("FORCE_COLOR")
+        .env("NO_COLOR", "1");
+
+    pipe(command.args(args), input)
+}
+
+/// Run `hd` with the given arguments and colors enabled, reading `input` from stdin.
+fn hd_color(args: &[&str], input: &[u8]) -> String {
+    let mut command = hd_command();
+    command.env_remove("NO_COLOR").env("ALWAYS_COLOR", "1");
+
+    pipe(command.args(args), input)
+}
+
+/// Run a command with `input` on stdin, returning stdout.
+fn pipe(command: &mut Command, input: &[u8]) -> String {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Check that every line of output is a dump row.
+fn is_rows_only(stdout: &[u8]) -> bool {
+    let stdout = String::from_utf8_lossy(stdout);
+
+    stdout.lines().all(|line| line.starts_with("0000_"))
+}
+
+#[test]
+fn test_quiet_multi_file() {
+    let output = hd(&["Cargo.toml", "example.bin"]);
+    assert!(!is_rows_only(&output.stdout));
+
+    let output = hd(&["--quiet", "Cargo.toml", "example.bin"]);
+    assert!(is_rows_only(&output.stdout));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_meta_stderr_multi_file() {
+    let output = hd(&["--meta-stderr", "Cargo.toml", "example.bin"]);
+    assert!(is_rows_only(&output.stdout));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "\n[Cargo.toml]\n\n[example.bin]\n"
+    );
+}
+
+#[test]
+fn test_concat() {
+    let data = std::fs::read("example.bin").unwrap();
+    let concat = [data.as_slice(), data.as_slice()].concat();
+
+    // Rows and addresses continue across the boundary, as if the inputs were one stream.
+    let args = ["--concat", "--quiet", "--width", "10"];
+    let output = hd(&[&args[..], &["example.bin", "example.bin"]].concat());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        hd_stdin(&args, &concat)
+    );
+
+    // The row containing the first byte of the next input is marked.
+    let output = hd(&["--concat", "--width", "10", "example.bin", "example.bin"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let marked: Vec<_> = stdout
+        .lines()
+        .filter(|line| line.ends_with("[example.bin at 0xb0]"))
+        .collect();
+    assert_eq!(marked.len(), 1);
+    assert!(marked[0].starts_with("0000_0000_0000_00aa:"));
+    assert_eq!(stdout.matches("[example.bin]").count(), 1);
+}
+
+#[test]
+fn test_byte_at() {
+    let data = std::fs::read("example.bin").unwrap();
+    let output = hd(&["--byte-at", "0x1a", "example.bin"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("{:02x}\n", data[0x1a])
+    );
+    assert_eq!(hd_stdin(&["--byte-at", "2", "-u"], b"ab\xfe"), "FE\n");
+
+    // Offsets past the end of the input are an error.
+    let (status, stderr) = hd_err(&["--byte-at", &data.len().to_string(), "example.bin"]);
+    assert!(!status.success());
+    assert!(stderr.contains("is past the end of \"example.bin\""));
+}
+
+#[test]
+fn test_find_repeats() {
+    let input = b"0123456789abcdef".repeat(3);
+    let stdout = hd_stdin(&["--find-repeats", "--width", "8"], &input);
+    assert_eq!(stdout, "3x 0x0 0x10 0x20\n3x 0x8 0x18 0x28\n");
+
+    let args = ["--find-repeats", "--width", "8", "--repeats-limit", "1"];
+    assert_eq!(
+        hd_stdin(&args, &input),
+        "3x 0x0 0x10 0x20\nStopped tracking new rows at 0x8 after 1 distinct rows\n"
+    );
+}
+
+#[test]
+fn test_xor() {
+    let stdout = hd_stdin(&["--xor", "20", "--no-addr"], b"HELLO");
+    assert_eq!(stdout, hd_stdin(&["--no-addr"], b"hello"));
+
+    // Multi-byte keys line up with input offsets, including skipped bytes.
+    let stdout = hd_stdin(&["--xor", "0x0020", "--skip", "3"], b"HELLO WORLD");
+    assert_eq!(stdout, hd_stdin(&["--skip", "3"], b"HeLlO\0WoRlD"));
+    let stdout = hd_stdin(
+        &["--xor", "deadbeef", "--output-format", "hex"],
+        b"\0\0\0\0\0",
+    );
+    assert_eq!(stdout, "deadbeefde\n");
+}
+
+#[test]
+fn test_rot13() {
+    let stdout = hd_stdin(&["--rot13", "--no-addr"], b"Hello, World! 123\xff");
+    assert_eq!(stdout, hd_stdin(&["--no-addr"], b"Uryyb, Jbeyq! 123\xff"));
+
+    // Transforms compose in a fixed order: XOR, then add, then ROT13.
+    let stdout = hd_stdin(
+        &[
+            "--rot13",
+            "--add",
+            "1",
+            "--xor",
+            "20",
+            "--output-format",
+            "hex",
+        ],
+        b"A",
+    );
+    assert_eq!(stdout, "6f\n");
+    let stdout = hd_stdin(&["--add", "-1", "--output-format", "hex"], b"\0b");
+    assert_eq!(stdout, "ff61\n");
+}
+
+#[test]
+fn test_line_numbers() {
+    let output = hd(&["--line-numbers", "--no-addr", "example.bin"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    for (i, line) in stdout.lines().enumerate() {
+        assert!(line.starts_with(&format!("{:>6}: ", i + 1)));
+    }
+}
+
+#[test]
+fn test_invert() {
+    const BRIGHT_RED: &str = "\x1b[91m";
+
+    let stdout = hd_color(&["--invert", "--no-addr"], b"abc123\xffdef");
+    assert_eq!(stdout.matches(BRIGHT_RED).count(), 2);
+    assert_eq!(stdout.matches("\x1b[").count(), 4);
+
+    let stdout = hd_color(&["--no-addr"], b"abc123\xffdef");
+    assert_eq!(stdout.matches(BRIGHT_RED).count(), 2);
+    assert_eq!(stdout.matches("\x1b[").count(), 16);
+}
+
+#[test]
+fn test_sparse() {
+    let mut input = b"hello".to_vec();
+    input.extend([0; 20000]);
+    input.extend(b"world");
+
+    let stdout = hd_stdin(&["--sparse"], &input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("0000_0000_0000_0010: [sparse hole: 0x4e10 bytes]"));
+    assert!(lines[2].starts_with("0000_0000_0000_4e20: 0000 0000 0077 6f72 6c64 "));
+
+    // Short runs of zeros are not collapsed.
+    let stdout = hd_stdin(&["--sparse"], &[0; 64]);
+    assert_eq!(stdout.lines().count(), 4);
+}
+
+#[test]
+fn test_table_width() {
+    let input = b"0123456789abcdefghij";
+
+    let stdout = hd_stdin(&["--table-width", "8", "--no-addr"], input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert!(lines[0].ends_with(" | 0123456\u{2026} |"));
+    assert!(lines[1].ends_with(" | ghij     |"));
+
+    let stdout = hd_stdin(&["--table-width", "20", "--no-addr"], input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert!(lines[0].ends_with(" | 0123456789abcdef     |"));
+    assert!(lines[1].ends_with(" | ghij                 |"));
+}
+
+#[test]
+fn test_stdin_name() {
+    let stdout = hd_stdin(&["--stdin-name", "capture.pcap"], b"hi");
+    assert!(stdout.starts_with("\n[capture.pcap]\n0000_"));
+
+    let stdout = hd_stdin(&["--stdin-name", "capture.pcap", "-", "Cargo.toml"], b"hi");
+    assert!(stdout.starts_with("\n[capture.pcap]\n0000_"));
+    assert!(stdout.contains("\n[Cargo.toml]\n"));
+
+    // Unnamed stdin has no header by itself.
+    let stdout = hd_stdin(&[], b"hi");
+    assert!(stdout.starts_with("0000_"));
+}
+
+#[test]
+fn test_squeeze_min_run() {
+    let row = |byte| [byte; 16];
+    let input = [row(b'a'), row(b'b'), row(b'b'), row(b'c')].concat();
+
+    // A run of exactly `--min-run` rows is collapsed.
+    let stdout = hd_stdin(&["--squeeze", "--min-run", "2"], &input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(lines[2], "*");
+    assert!(lines[3].starts_with("0000_0000_0000_0030: 6363 "));
+
+    // A run shorter than `--min-run` is printed in full.
+    let stdout = hd_stdin(&["--squeeze", "--min-run", "3"], &input);
+    assert!(!stdout.contains('*'));
+    assert_eq!(stdout.lines().count(), 4);
+}
+
+#[test]
+fn test_no_final_newline() {
+    let input = [b'a'; 20];
+
+    let stdout = hd_stdin(&[], &input);
+    assert!(stdout.ends_with("|\n"));
+    assert_eq!(stdout.lines().count(), 2);
+
+    let stdout = hd_stdin(&["--no-final-newline"], &input);
+    assert!(stdout.ends_with('|'));
+    assert_eq!(stdout.lines().count(), 2);
+}
+
+#[test]
+fn test_row_hash() {
+    let input = [[b'a'; 16], [b'a'; 16], [b'a'; 16], [b'b'; 16]].concat();
+
+    // Squeezed rows are not hashed; the row before the `*` has the same bytes.
+    let stdout = hd_stdin(&["--row-hash", "crc32", "--squeeze"], &input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with(" | aaaaaaaaaaaaaaaa | cfd668d5"));
+    assert_eq!(lines[1], "*");
+    assert!(lines[2].ends_with(" | bbbbbbbbbbbbbbbb | d68acab2"));
+}
+
+#[test]
+fn test_skip_length() {
+    let input: Vec<u8> = (0..=255).collect();
+
+    let stdout = hd_stdin(&["--skip", "0x20", "--length", "18"], &input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("0000_0000_0000_0020: 2021 2223 "));
+    assert!(lines[1].starts_with("0000_0000_0000_0030: 3031 "));
+
+    let stdout = hd_stdin(&["--skip", "0x20", "--base-addr", "0x1000"], &input);
+    assert!(stdout.starts_with("0000_0000_0000_1000: 2021 2223 "));
+    assert_eq!(stdout.lines().count(), 14);
+}
+
+#[test]
+fn test_dump_range() {
+    let input: Vec<u8> = (0..=255).collect();
+
+    // The same window as `--skip 0x20 --length 18`.
+    let stdout = hd_stdin(&["--dump-range", "0x20:0x32"], &input);
+    assert_eq!(
+        stdout,
+        hd_stdin(&["--skip", "0x20", "--length", "18"], &input)
+    );
+
+    let stdout = hd_stdin(&["--dump-range", "0xf8:"], &input);
+    assert!(stdout.starts_with("0000_0000_0000_00f8: f8f9 fafb fcfd feff "));
+    assert_eq!(stdout.lines().count(), 1);
+
+    let stdout = hd_stdin(&["--dump-range", ":3"], &input);
+    assert!(stdout.starts_with("0000_0000_0000_0000: 0001 02 "));
+    assert_eq!(stdout.lines().count(), 1);
+
+    let (status, _) = hd_err(&["--dump-range", "0x20:", "--skip", "4", "example.bin"]);
+    assert!(!status.success());
+}
+
+#[test]
+fn test_min_string() {
+    // A string in the middle of binary noise, with a short string that is skipped.
+    let noise: Vec<u8> = (0..64_u32).map(|i| (i * 37 % 251) as u8 | 0x80).collect();
+    let input = [
+        &noise[..],
+        b"embedded string",
+        &noise[..],
+        b"ab",
+        &noise[..],
+    ]
+    .concat();
+
+    let stdout = hd_stdin(&["--min-string", "8"], &input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "...",
+            "0000_0000_0000_0040: 656d 6265 6464 6564 2073 7472 696e 6780 | embedded string. |",
+            "...",
+        ]
+    );
+}
+
+#[test]
+fn test_collapse_zeros() {
+    const DIM_ZERO: &str = "\x1b[90m00\x1b[39m";
+
+    let stdout = hd_color(&["--collapse-zeros"], b"ab\x00\x00\x01\x00");
+    assert_eq!(stdout.matches(DIM_ZERO).count(), 3);
+    assert!(stdout.contains("\x1b[93m01\x1b[39m"));
+
+    // Alignment is unchanged.
+    let plain = hd_stdin(&["--collapse-zeros"], b"ab\x00\x00\x01\x00");
+    assert_eq!(plain, hd_stdin(&[], b"ab\x00\x00\x01\x00"));
+}
+
+#[test]
+fn test_separator_colors() {
+    let args = ["--separator-color", "red", "--punct-color", "bright-black"];
+    let stdout = hd_color(&args, b"abc");
+    assert!(stdout.contains("\x1b[90m:\x1b[39m"));
+    assert!(stdout.contains("\x1b[31m | \x1b[39m"));
+    assert!(stdout.contains("\x1b[31m |\x1b[39m\n"));
+
+    // Separators are plain by default, and colors are never added to plain output.
+    assert_eq!(hd_color(&[], b"abc").matches("\x1b[31m").count(), 0);
+    assert_eq!(hd_stdin(&args, b"abc"), hd_stdin(&[], b"abc"));
+}
+
+#[test]
+fn test_mark() {
+    const MARK: &str = "\x1b[4;7m";
+
+    // The mark spans the row boundary, and is highlighted in both columns of each row.
+    let stdout = hd_color(&["--mark", "0xe:0x12"], b"0123456789abcdefghij");
+    let rows: Vec<_> = stdout.lines().collect();
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0].contains(&format!("{MARK}65\x1b[24;27m{MARK}66\x1b[24;27m")));
+    assert!(rows[0].contains(&format!("{MARK}ef\x1b[24;27m")));
+    assert!(rows[1].contains(&format!("{MARK}gh\x1b[24;27m")));
+    assert_eq!(stdout.matches(MARK).count(), 6);
+
+    // Overlapping marks highlight their union.
+    let stdout = hd_color(&["--mark", "0:2", "--mark", "1:3"], b"abcd");
+    assert!(stdout.contains(&format!("{MARK}abc\x1b[24;27m\x1b[92md")));
+
+    // Alignment is unchanged.
+    let plain = hd_stdin(&["--mark", "2:5"], b"abcdef");
+    assert_eq!(plain, hd_stdin(&[], b"abcdef"));
+}
+
+#[test]
+fn test_no_color_addr() {
+    let stdout = hd_color(&["--no-color-addr"], b"ab\x01");
+    assert!(stdout.starts_with("0000_0000_0000_0000:\x1b[92m 6162"));
+    assert!(stdout.contains("\x1b[93m 01\x1b[39m"));
+
+    // Other row prefixes are not colored either.
+    let args = ["--no-color-addr", "--line-numbers", "--record-size", "4"];
+    let stdout = hd_color(&args, b"ab\x01");
+    assert!(stdout.starts_with("     1      0:0x00 0000_0000_0000_0000:\x1b[92m 6162"));
+
+    // Colors are still disabled globally.
+    let plain = hd_stdin(&["--no-color-addr"], b"ab\x01");
+    assert_eq!(plain, hd_stdin(&[], b"ab\x01"));
+}
+
+#[test]
+fn test_dim_addr_repeats() {
+    let input = [b'a'; 32];
+    let stdout = hd_color(&["--dim-addr-repeats"], &input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert!(lines[0].starts_with("\x1b[94m0000_0000_0000_0000\x1b[39m:"));
+    assert!(
+        lines[1].starts_with("\x1b[2m\x1b[94m0000_0000_0000_00\x1b[22m\x1b[39m\x1b[94m10\x1b[39m:")
+    );
+
+    // Without colors, addresses are printed in full.
+    let plain = hd_stdin(&["--dim-addr-repeats"], &input);
+    assert_eq!(plain, hd_stdin(&[], &input));
+}
+
+#[test]
+fn test_size_env() {
+    let env = |vars: &[(&str, &str)], args: &[&str]| {
+        let mut command = hd_command();
+        command.env("NO_COLOR", "1").envs(vars.iter().copied());
+
+        pipe(command.args(args), b"0123456789")
+    };
+
+    let stdout = env(&[("HD_SKIP", "0x2"), ("HD_LENGTH", "3")], &[]);
+    assert_eq!(
+        stdout,
+        hd_stdin(&["--skip", "2", "--length", "3"], b"0123456789")
+    );
+
+    // Options take precedence, and empty variables are ignored.
+    let stdout = env(&[("HD_SKIP", "2"), ("HD_LENGTH", "")], &["--skip", "4"]);
+    assert_eq!(stdout, hd_stdin(&["--skip", "4"], b"0123456789"));
+}
+
+#[test]
+fn test_show_bom() {
+    // The input is written in chunks, pausing between them so each arrives in a separate read.
+    let stderr = |args: &[&str], chunks: &[&[u8]]| {
+        let mut child = hd_command()
+            .args(args)
+            .env("NO_COLOR", "1")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        for (i, chunk) in chunks.iter().enumerate() {
+            if i > 0 {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            stdin.write_all(chunk).unwrap();
+            stdin.flush().unwrap();
+        }
+        drop(stdin);
+        let output = child.wait_with_output().unwrap();
+        assert!(output.status.success());
+
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    let boms: [(&[u8], &str); 5] = [
+        (b"\xef\xbb\xbfa", "UTF-8"),
+        (b"\xff\xfea\0", "UTF-16LE"),
+        (b"\xfe\xff\0a", "UTF-16BE"),
+        (b"\xff\xfe\0\0a\0\0\0", "UTF-32LE"),
+        (b"\0\0\xfe\xffa\0\0\0", "UTF-32BE"),
+    ];
+    for (input, encoding) in boms {
+        assert_eq!(
+            stderr(&["--show-bom"], &[input]),
+            format!("Note: \"stdin\" starts with a {encoding} byte-order mark\n")
+        );
+        assert_eq!(stderr(&[], &[input]), "");
+    }
+
+    // Marks that arrive in short reads are detected from all of their bytes.
+    assert_eq!(
+        stderr(&["--show-bom"], &[b"\xff\xfe", b"\0\0a\0\0\0"]),
+        "Note: \"stdin\" starts with a UTF-32LE byte-order mark\n"
+    );
+    assert_eq!(
+        stderr(&["--show-bom"], &[b"\xef", b"\xbb\xbf"]),
+        "Note: \"stdin\" starts with a UTF-8 byte-order mark\n"
+    );
+
+    // The mark is only detected at the start of the input.
+    assert_eq!(
+        stderr(&["--show-bom", "--skip", "1"], &[b"a\xef\xbb\xbf"]),
+        ""
+    );
+    assert_eq!(stderr(&["--show-bom"], &[b"a\xef\xbb\xbf"]), "");
+}
+
+#[test]
+fn test_dump_struct() {
+    let input = b"RIFF\x24\x00\x00\x00WAVE\x01\x00";
+    let spec = "magic:4,size:u32le,format:4,channels:u16,rate:u32be";
+    let stdout = hd_stdin(&["--dump-struct", spec, "--width", "8"], input);
+    let notes: Vec<_> = stdout
+        .lines()
+        .map(|line| {
+            line.split_once(" | ")
+                .unwrap()
+                .1
+                .split_once(" | ")
+                .unwrap()
+                .1
+        })
+        .collect();
+
+    // Fields are annotated on the row where they end, and `rate` is past the end of the input.
+    assert_eq!(
+        notes,
+        ["magic=\"RIFF\" size=36", "format=\"WAVE\" channels=1"]
+    );
+
+    assert!(hd_color(&["--dump-struct", spec], input).contains("\x1b[4;7mRIFF\x1b[24;27m"));
+}
+
+#[test]
+fn test_offset_separator() {
+    let input = b"hello, world!\n\x00\x01\xff";
+    let dump = hd_stdin(&["--offset-separator", "\u{2502}"], input);
+    assert!(dump.starts_with("0000_0000_0000_0000\u{2502} 6865"));
+
+    // The dump is reversed with the same separator.
+    let args = [
+        "--input-format",
+        "dump",
+        "--output-format",
+        "hex",
+        "--offset-separator",
+        "\u{2502}",
+    ];
+    let hex = hd_stdin(&args, dump.as_bytes());
+    assert_eq!(hex.trim(), "68656c6c6f2c20776f726c64210a0001ff");
+}
+
+#[test]
+fn test_decode_errors() {
+    let dir = TempDir::new("decode-errors");
+    let base64 = dir.write("mixed.b64", "aGVsbG8-/w\n");
+    let hex = dir.write("bad.hex", "de ad bx\n");
+
+    // Decoding errors are reported on their own, with the offset of the bad character.
+    let (status, stderr) = hd_err(&["--input-format", "base64", base64.to_str().unwrap()]);
+    assert!(!status.success());
+    assert!(stderr.starts_with(
+        "Error: Base64 character `/` at offset 8 mixes the standard and URL-safe alphabets\n"
+    ));
+    let (status, stderr) = hd_err(&["--input-format", "hex", hex.to_str().unwrap()]);
+    assert!(!status.success());
+    assert!(stderr.starts_with("Error: Invalid hex character `x` at offset 7\n"));
+}
+
+#[test]
+fn test_raw_offsets() {
+    let input: Vec<u8> = (0..=255).collect();
+    let dump = hd_stdin(&["--raw-offsets"], &input);
+    let lines: Vec<_> = dump.lines().collect();
+    assert!(lines[0].starts_with("00000000: 0001"));
+    assert!(lines[15].starts_with("000000f0: f0f1"));
+    let dump = hd_stdin(&["--raw-offsets", "--uppercase"], &input);
+    assert!(dump.lines().nth(15).unwrap().starts_with("000000F0: F0F1"));
+
+    // Raw offsets are reversed like grouped addresses.
+    let args = ["--input-format", "dump", "--output-format", "hex"];
+    let hex = hd_stdin(&args, dump.as_bytes());
+    assert_eq!(hex, hd_stdin(&["--output-format", "hex"], &input));
+}
+
+#[test]
+fn test_classify_utf8_strict() {
+    // A noncharacter, and a combining mark after a newline are invalid. The mark after `e` is not.
+    let input = "a\u{fffe}\n\u{301}e\u{301}".as_bytes();
+    let stdout = hd_stdin(&["--classify-utf8-strict", "--no-addr"], input);
+    assert_eq!(
+        stdout,
+        " 61ef bfbe 0acc 8165 cc81                | a......e.        |\n"
+    );
+    assert_ne!(stdout, hd_stdin(&["--no-addr"], input));
+}
+
+#[test]
+fn test_no_leading_space() {
+    let input = b"hello, world!\n\x00\x01\xff";
+    let dump = hd_stdin(&["--no-leading-space"], input);
+    assert!(dump.starts_with("0000_0000_0000_0000:6865 6c6c"));
+
+    // The dump can still be reversed.
+    let args = ["--input-format", "dump", "--output-format", "hex"];
+    let hex = hd_stdin(&args, dump.as_bytes());
+    assert_eq!(hex.trim(), "68656c6c6f2c20776f726c64210a0001ff");
+}
+
+#[test]
+fn test_ascii_escape() {
+    let stdout = hd_stdin(&["--no-hex", "--ascii-escape", "--no-addr"], b"hi\r\n\0\t");
+    assert_eq!(stdout, " | hi\\r\\n\\0\\t                       |\n");
+
+    // Escapes are not allowed with the hex column.
+    let (status, stderr) = hd_err(&["--ascii-escape", "example.bin"]);
+    assert!(!status.success());
+    assert!(stderr.contains("requires `--no-hex`"));
+
+    // The help describes the escapes without control characters.
+    let (_, help) = hd_err(&["--help"]);
+    assert!(help.contains("--ascii-escape"));
+    assert!(!help.contains(['\t', '\r']));
+}
+
+#[test]
+fn test_final_offset() {
+    let stdout = hd_stdin(&["--final-offset"], &[b'x'; 100]);
+    assert_eq!(stdout.lines().last(), Some("0000_0000_0000_0064:"));
+    let stdout = hd_stdin(&["--final-offset", "--raw-offsets"], &[0; 32]);
+    assert_eq!(stdout.lines().last(), Some("00000020:"));
+    assert_eq!(hd_stdin(&["--final-offset"], b""), "0000_0000_0000_0000:\n");
+
+    // Each file ends with its own final offset.
+    let dir = TempDir::new("final-offset");
+    let first = dir.write("first.bin", [b'a'; 20]);
+    let second = dir.write("second.bin", b"hi");
+
+    let args = [
+        "--final-offset",
+        first.to_str().unwrap(),
+        second.to_str().unwrap(),
+    ];
+    let stdout = hd_stdin(&args, b"");
+
+    // Each offset is the size of its file, though addresses continue across files.
+    let offsets: Vec<_> = stdout.lines().filter(|line| line.ends_with(':')).collect();
+    assert_eq!(offsets, ["0000_0000_0000_0014:", "0000_0000_0000_0002:"]);
+    assert!(stdout.contains("0000_0000_0000_0020: 6869 "));
+
+    // Concatenated files have one offset with their total size.
+    let stdout = hd_stdin(&[&args[..], &["--concat"]].concat(), b"");
+    assert_eq!(stdout.lines().last(), Some("0000_0000_0000_0016:"));
+
+    // Only the dumped bytes are counted.
+    let stdout = hd_stdin(
+        &["--final-offset", "--skip", "4", "--length", "90"],
+        &[0; 100],
+    );
+    assert_eq!(stdout.lines().last(), Some("0000_0000_0000_005a:"));
+}
+
+#[test]
+fn test_limit_rows_per_file() {
+    let dir = TempDir::new("limit-rows");
+    let long = dir.write("long.bin", [b'a'; 100]);
+    let short = dir.write("short.bin", b"hi");
+
+    let stdout = hd_stdin(
+        &[
+            "--limit-rows-per-file",
+            "2",
+            long.to_str().unwrap(),
+            short.to_str().unwrap(),
+        ],
+        b"",
+    );
+
+    // The long file is truncated after two rows, and the short file is printed in full.
+    let lines: Vec<_> = stdout.lines().filter(|line| !line.is_empty()).collect();
+    assert_eq!(lines.len(), 6);
+    assert!(lines[0].ends_with("long.bin]"));
+    assert!(lines[1].ends_with("| aaaaaaaaaaaaaaaa |"));
+    assert!(lines[2].ends_with("| aaaaaaaaaaaaaaaa |"));
+    assert_eq!(lines[3], "... [0x44 more bytes]");
+    assert!(lines[4].ends_with("short.bin]"));
+    assert!(lines[5].ends_with("| hi               |"));
+
+    // Streams are not counted to the end.
+    let stdout = hd_stdin(&["--limit-rows-per-file", "1", "--no-addr"], &[b'a'; 100]);
+    assert_eq!(
+        stdout,
+        " 6161 6161 6161 6161 6161 6161 6161 6161 | aaaaaaaaaaaaaaaa |\n...\n"
+    );
+    let stdout = hd_stdin(&["--limit-rows-per-file", "1", "--no-addr"], &[b'a'; 16]);
+    assert!(!stdout.contains("..."));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_limit_rows_per_file_endless() {
+    // Endless input stops at the limit.
+    let output = hd(&["--limit-rows-per-file", "1", "/dev/zero"]);
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with(" |\n...\n"));
+}
+
+#[test]
+fn test_color_numeric_by_radix() {
+    const NUMERIC: &str = "\x1b[96m";
+    const WARNING: &str = "\x1b[31m";
+
+    let args = [
+        "--numeric",
+        "octal",
+        "--color-numeric-by-radix",
+        "--no-addr",
+    ];
+    let stdout = hd_color(&args, b"7 8");
+    let table = stdout.split_once(" | ").unwrap().1;
+    assert!(table.starts_with(&format!("{NUMERIC}7\x1b[39m")));
+    assert!(table.contains(&format!("{WARNING}8\x1b[39m")));
+
+    // The warning is opt-in.
+    let stdout = hd_color(&["--numeric", "octal", "--no-addr"], b"7 8");
+    assert!(!stdout.contains(WARNING));
+}
+
+#[test]
+fn test_color_columns() {
+    let args = ["--color-columns", "ascii", "--no-addr"];
+    let stdout = hd_color(&args, b"ab1\x01");
+    let (hex, table) = stdout.split_once(" | ").unwrap();
+    assert_eq!(hex, " 6162 3101                              ");
+    assert!(table.starts_with("\x1b[92mab\x1b[39m\x1b[96m1\x1b[39m"));
+
+    // Per-byte hex colors follow the hex column.
+    let args = ["--color-columns", "ascii", "--color-scale", "--no-addr"];
+    let stdout = hd_color(&args, b"ab1\x01");
+    assert!(stdout.starts_with(" 6162 3101 "));
+
+    let stdout = hd_color(&["--color-columns", "hex", "--no-addr"], b"ab1\x01");
+    let (hex, table) = stdout.split_once(" | ").unwrap();
+    assert!(hex.starts_with("\x1b[92m 6162\x1b[39m"));
+    assert_eq!(table, "ab1.             |\n");
+
+    // Both columns are colored by default.
+    assert_eq!(
+        hd_color(&["--color-columns", "hex,ascii"], b"ab1\x01"),
+        hd_color(&[], b"ab1\x01")
+    );
+}
+
+#[test]
+fn test_numeric_color() {
+    // The digit stays printable green in the hex column, and numeric cyan in the table.
+    let stdout = hd_color(&["--numeric-color", "ascii", "--no-addr"], b"ab1");
+    let (hex, table) = stdout.split_once(" | ").unwrap();
+    assert!(hex.starts_with("\x1b[92m 6162\x1b[39m\x1b[92m 31\x1b[39m"));
+    assert!(table.starts_with("\x1b[92mab\x1b[39m\x1b[96m1\x1b[39m"));
+
+    let stdout = hd_color(&["--numeric-color", "hex", "--no-addr"], b"ab1");
+    let (hex, table) = stdout.split_once(" | ").unwrap();
+    assert!(hex.starts_with("\x1b[92m 6162\x1b[39m\x1b[96m 31\x1b[39m"));
+    assert!(table.starts_with("\x1b[92mab\x1b[39m\x1b[92m1\x1b[39m"));
+
+    assert_eq!(
+        hd_color(&["--numeric-color", "both"], b"ab1"),
+        hd_color(&[], b"ab1")
+    );
+}
+
+#[test]
+fn test_numeric_tokens() {
+    const NUMERIC: &str = "\x1b[96m";
+
+    let stdout = hd_color(&["--numeric-tokens", "--no-addr"], b"x=-12.5");
+    let table = stdout.split_once(" | ").unwrap().1;
+    assert!(table.contains(&format!("{NUMERIC}-12.5\x1b[39m")));
+
+    // Signs and decimal points are printable by default.
+    let stdout = hd_color(&["--no-addr"], b"x=-12.5");
+    let table = stdout.split_once(" | ").unwrap().1;
+    assert!(table.contains(&format!("{NUMERIC}12\x1b[39m")));
+}
+
+#[test]
+fn test_numeric_min_run() {
+    const NUMERIC: &str = "\x1b[96m";
+    const PRINTABLE: &str = "\x1b[92m";
+
+    let stdout = hd_color(&["--numeric-min-run", "2", "--no-addr"], b"abc5def 42");
+    let table = stdout.split_once(" | ").unwrap().1;
+    assert!(table.contains(&format!("{PRINTABLE}abc5def \x1b[39m")));
+    assert!(table.contains(&format!("{NUMERIC}42\x1b[39m")));
+
+    let stdout = hd_color(&["--no-addr"], b"abc5def 42");
+    let table = stdout.split_once(" | ").unwrap().1;
+    assert!(table.contains(&format!("{NUMERIC}5\x1b[39m")));
+}
+
+#[test]
+fn test_ascii_delimiters() {
+    // The last hex byte touches the delimiter in full rows.
+    let input = b"cafe babe\x00";
+    let dump = hd_stdin(&["--ascii-delimiters", "<<,>>", "--width", "10"], input);
+    assert_eq!(
+        dump,
+        "0000_0000_0000_0000: 6361 6665 2062 6162 6500<<cafe babe.>>\n"
+    );
+
+    let args = [
+        "--input-format",
+        "dump",
+        "--output-format",
+        "hex",
+        "--ascii-delimiters",
+        "<<,>>",
+    ];
+    let hex = hd_stdin(&args, dump.as_bytes());
+    assert_eq!(hex.trim(), "636166652062616265 00".replace(' ', ""));
+}
+
+#[test]
+fn test_sample() {
+    let dir = TempDir::new("sample");
+    let data: Vec<u8> = (0..=255).collect();
+    let path = dir.write("sample.bin", data);
+    let path = path.to_str().unwrap();
+
+    let stdout = hd_stdin(&["--sample", "3", "--width", "4", path], b"");
+    let small = hd_stdin(&["--sample", "3", "--width", "100", path], b"");
+    let whole = hd_stdin(&["--width", "100", path], b"");
+
+    assert_eq!(
+        stdout,
+        "\
+            0000_0000_0000_0000: 0001 0203 | .... |\n\
+            ...\n\
+            0000_0000_0000_007e: 7e7f 8081 | ~... |\n\
+            ...\n\
+            0000_0000_0000_00fc: fcfd feff | .... |\n"
+    );
+
+    // Files smaller than the samples are printed in full.
+    assert_eq!(small, whole);
+}
+
+#[test]
+fn test_broken_pipe() {
+    use std::io::{BufRead as _, BufReader};
+
+    // Dump the (large) binary itself, and close the pipe after the first line.
+    let mut child = hd_command()
+        .arg(env!("CARGO_BIN_EXE_hd"))
+        .env("NO_COLOR", "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    assert!(line.starts_with("0000_"));
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn test_debug_layout() {
+    let output = hd(&[
+        "--debug-layout",
+        "-w",
+        "10",
+        "-g",
+        "3",
+        "--group-gap",
+        "2",
+        "example.bin",
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines[0], format!("{:21}000102 030405  060708 09", ""));
+    assert_eq!(lines[1].find(" | "), lines[0].find("09").map(|i| i + 2));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+#[cfg(feature = "gzip")]
+fn test_decompress() {
+    // `printf 'hi' | gzip -n`
+    let gzip = [
+        0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03, 0xcb, 0xc8, 0x04, 0x00, 0xac,
+        0x2a, 0x93, 0xd8, 0x02, 0x00, 0x00, 0x00,
+    ];
+
+    let stdout = hd_stdin(&["--decompress", "--output-format", "hex"], &gzip);
+    assert_eq!(stdout, "6869\n");
+
+    // Without the flag, the compressed bytes are shown.
+    let stdout = hd_stdin(&["--output-format", "hex"], &gzip);
+    assert!(stdout.starts_with("1f8b08"));
+
+    // A forced format decompresses without detecting it, and rejects other input.
+    let stdout = hd_stdin(
+        &["--decompress-format", "gz", "--output-format", "hex"],
+        &gzip,
+    );
+    assert_eq!(stdout, "6869\n");
+    let mut child = hd_command()
+        .args(["--decompress-format", "gzip"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"plain").unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+#[cfg(feature = "zstd")]
+fn test_decompress_zstd() {
+    // `printf 'hi' | zstd --no-check`
+    let zstd = [
+        0x28, 0xb5, 0x2f, 0xfd, 0x00, 0x58, 0x11, 0x00, 0x00, 0x68, 0x69,
+    ];
+
+    let stdout = hd_stdin(&["--decompress", "--output-format", "hex"], &zstd);
+    assert_eq!(stdout, "6869\n");
+    let stdout = hd_stdin(
+        &["--decompress-format", "zst", "--output-format", "hex"],
+        &zstd,
+    );
+    assert_eq!(stdout, "6869\n");
+
+    // Truncated frames are an error.
+    let mut child = hd_command()
+        .arg("--decompress")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(&zstd[..9]).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("Corrupt or truncated zstd stream"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_max_file_size() {
+    let (status, stderr) = hd_err(&["--max-file-size", "100", "example.bin"]);
+    assert!(!status.success());
+    assert!(
+        stderr.contains("\"example.bin\" has 176 bytes to print, exceeding `--max-file-size` 100")
+    );
+
+    // Files under the limit, or limited by `--length`, are printed.
+    let output = hd(&["--max-file-size", "176", "example.bin"]);
+    assert_eq!(output.stdout.iter().filter(|&&b| b == b'\n').count(), 11);
+    let output = hd(&["--max-file-size", "100", "--length", "64", "example.bin"]);
+    assert_eq!(output.stdout.iter().filter(|&&b| b == b'\n').count(), 4);
+
+    // Stdin has no known size.
+    let stdout = hd_stdin(&["--max-file-size", "1"], b"hello");
+    assert_eq!(stdout.lines().count(), 1);
+}
+
+#[test]
+fn test_paginate_not_terminal() {
+    // Output that is not written to a terminal is never paged.
+    let mut command = hd_command();
+    command.env("NO_COLOR", "1").env("PAGER", "false");
+
+    let stdout = pipe(command.arg("--paginate"), b"hello");
+    assert!(stdout.starts_with("0000_0000_0000_0000: 6865 6c6c 6f"));
+}
+
+#[test]
+fn test_multiple_widths() {
+    let stdout = hd_stdin(&["--width", "4,8"], b"0123456789");
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 9);
+    assert_eq!(lines[1], "[width 4]");
+    assert!(lines[2].starts_with("0000_0000_0000_0000: 3031 3233 |"));
+    assert_eq!(lines[6], "[width 8]");
+    assert!(lines[8].starts_with("0000_0000_0000_0008: 3839 "));
+}
+
+#[test]
+fn test_count_only() {
+    let stdout = hd_stdin(&["--count-only"], b"hi 42\r\n\xc3\xa9\xff");
+    assert_eq!(
+        stdout,
+        "printable  3\nnumeric    2\ncontrol    2\ngraphemes  2\ninvalid    1\ntotal     10\n"
+    );
+
+    // Counts are totaled across inputs, and the dump is not printed.
+    let output = hd(&[
+        "--count-only",
+        "--numeric",
+        "none",
+        "example.bin",
+        "example.bin",
+    ]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.ends_with("\ntotal     352\n"));
+    assert!(stdout.contains("\nnumeric     0\n"));
+    assert_eq!(stdout.lines().count(), 6);
+}
+
+#[test]
+fn test_trim_trailing_zeros() {
+    let mut input = b"0123456789abcdef\x00\x01".to_vec();
+    input.extend([0; 100]);
+
+    let stdout = hd_stdin(&["--trim-trailing-zeros"], &input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("0000_0000_0000_0010: 0001 "));
+    assert_eq!(lines[2], "[trimmed 0x64 trailing zero bytes]");
+
+    // Only the rows are printed with `--quiet`.
+    let stdout = hd_stdin(&["--trim-trailing-zeros", "--quiet"], &input);
+    assert_eq!(stdout.lines().count(), 2);
+
+    // Other output formats are trimmed without a note.
+    let stdout = hd_stdin(
+        &["--trim-trailing-zeros", "--output-format", "hex"],
+        b"\x00\x01\x00\x00",
+    );
+    assert_eq!(stdout, "0001\n");
+}
+
+#[test]
+fn test_html() {
+    // HTML is colored even when colors are disabled.
+    let stdout = hd_stdin(&["--output-format", "html", "--no-addr"], b"<a>1");
+    assert_eq!(
+        stdout,
+        "<pre class=\"hd\">\
+         <span style=\"color:#00ff00\"> 3c61 3e</span><span style=\"color:#00ffff\">31</span>\
+         \x20                              | \
+         <span style=\"color:#00ff00\">&lt;a&gt;</span><span style=\"color:#00ffff\">1</span>\
+         \x20            |\n</pre>\n"
+    );
+
+    let stdout = hd_stdin(&["--output-format", "html", "--html-class"], b"\x00");
+    assert!(stdout.starts_with("<pre class=\"hd\"><span class=\"hd-bright-blue\">0000_"));
+    assert!(stdout.contains("<span class=\"hd-bright-yellow\">.</span>"));
+
+    // Messages on stderr are not colored.
+    let (status, stderr) = hd_err(&["--output-format", "html", "missing.bin"]);
+    assert!(!status.success());
+    assert!(stderr.contains("\nError: Unable to read file"));
+    let output = hd(&[
+        "--output-format",
+        "html",
+        "--meta-stderr",
+        "Cargo.toml",
+        "example.bin",
+    ]);
+    assert!(!output.stderr.is_empty());
+    assert!(!output.stderr.contains(&0x1b));
+}
+
+#[test]
+fn test_xxd() {
+    // Expected output is from `xxd`, which does not color its output either.
+    let input = b"hello world, this is xxd\x00\x01\xff~\x7f";
+    assert_eq!(
+        hd_color(&["--output-format", "xxd"], input),
+        "\
+            00000000: 6865 6c6c 6f20 776f 726c 642c 2074 6869  hello world, thi\n\
+            00000010: 7320 6973 2078 7864 0001 ff7e 7f         s is xxd...~.\n"
+    );
+    assert_eq!(
+        hd_stdin(
+            &["--output-format", "xxd", "-w", "4", "-u"],
+            b"\xab\xcdhi\n"
+        ),
+        "00000000: ABCD 6869  ..hi\n00000004: 0A         .\n"
+    );
+
+    // Offsets start at the skipped address, like `xxd -s 0x20`.
+    let input: Vec<u8> = (0..0x28).collect();
+    assert_eq!(
+        hd_stdin(&["--output-format", "xxd", "--skip", "0x20"], &input),
+        "00000020: 2021 2223 2425 2627                       !\"#$%&'\n"
+    );
+    // Offsets near the end of the address space wrap around.
+    assert_eq!(
+        hd_stdin(
+            &[
+                "--output-format",
+                "xxd",
+                "-w",
+                "4",
+                "--base-addr",
+                "0xfffffffffffffffc"
+            ],
+            b"abcdefg"
+        ),
+        "fffffffffffffffc: 6162 6364  abcd\n00000000: 6566 67    efg\n"
+    );
+}
+
+#[test]
+fn test_seek_to() {
+    // The marker straddles the first 4096-byte read.
+    let mut input = vec![b'a'; 4095];
+    input.extend(b"MARKER");
+    input.extend([0; 40]);
+
+    let stdout = hd_stdin(&["--seek-to", "4d41524b4552", "--length", "20"], &input);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("0000_0000_0000_0fff: 4d41 524b 4552 0000 "));
+    assert!(lines[1].starts_with("0000_0000_0000_100f: 0000 0000 "));
+
+    // Nothing is printed without a match.
+    let stdout = hd_stdin(&["--seek-to", "ffd8ff", "--quiet"], &input);
+    assert_eq!(stdout, "");
+}
+
+#[test]
+fn test_config() {
+    let dir = TempDir::new("config");
+    std::fs::create_dir_all(dir.0.join("hd")).unwrap();
+    dir.write(
+        "hd/config.toml",
+        "# Defaults\nwidth = 4\nuppercase = true\nno_addr = true\n",
+    );
+    let run = |args: &[&str]| {
+        let mut command = hd_command();
+        command.env("NO_COLOR", "1").env("XDG_CONFIG_HOME", &dir.0);
+
+        pipe(command.args(args), b"\xab\xcd\xef\x01\x02")
+    };
+
+    // The default config file sets options, and the command line overrides them.
+    assert_eq!(run(&[]), " ABCD EF01 | .... |\n 02        | .    |\n");
+    assert_eq!(run(&["-w", "8"]), " ABCD EF01 02        | .....    |\n");
+
+    // `--config` replaces the default config file.
+    let path = dir.write("other.toml", "group = \"byte\"\n");
+    let stdout = run(&["--config", path.to_str().unwrap()]);
+    assert!(stdout.starts_with("0000_0000_0000_0000: ab cd ef 01 02 "));
+
+    // The environment overrides the config file, and the command line overrides both.
+    let path = dir.write("length.toml", "length = 2\n");
+    let config = ["--config", path.to_str().unwrap(), "--output-format", "hex"];
+    assert_eq!(run(&config), "abcd\n");
+    let mut command = hd_command();
+    command.env("NO_COLOR", "1").env("HD_LENGTH", "3");
+    assert_eq!(
+        pipe(command.args(config), b"\xab\xcd\xef\x01\x02"),
+        "abcdef\n"
+    );
+    let mut command = hd_command();
+    command.env("NO_COLOR", "1").env("HD_LENGTH", "3");
+    let args = command.args(config).args(["--length", "4"]);
+    assert_eq!(pipe(args, b"\xab\xcd\xef\x01\x02"), "abcdef01\n");
+
+    // `--dump-range` and the sizes replace each other from a later source.
+    let args = ["--dump-range", "2:4"];
+    assert_eq!(run(&[&config[..], &args].concat()), "ef01\n");
+    let path = dir.write("range.toml", "dump_range = \"1:3\"\n");
+    let config = ["--config", path.to_str().unwrap(), "--output-format", "hex"];
+    assert_eq!(run(&config), "cdef\n");
+    assert_eq!(run(&[&config[..], &["--length", "1"]].concat()), "ab\n");
+    let mut command = hd_command();
+    command.env("NO_COLOR", "1").env("HD_SKIP", "3");
+    assert_eq!(
+        pipe(command.args(config), b"\xab\xcd\xef\x01\x02"),
+        "0102\n"
+    );
+
+    // They conflict when they come from the same source.
+    let path = dir.write("both.toml", "dump_range = \"1:3\"\nskip = 1\n");
+    let (status, stderr) = hd_err(&["--config", path.to_str().unwrap(), "example.bin"]);
+    assert!(!status.success());
+    assert!(stderr.contains("`--dump-range` cannot be combined"));
+
+    // Unknown keys are reported with their line, instead of as command line arguments.
+    let path = dir.write("bogus.toml", "width = 4\nbogus = 1\n");
+    let (status, stderr) = hd_err(&["--config", path.to_str().unwrap(), "example.bin"]);
+    assert!(!status.success());
+    assert!(stderr.contains("bogus.toml"));
+    assert!(stderr.contains("Unknown option `bogus` on line 2"));
+    assert!(!stderr.contains("Usage:"));
+
+    // Arrays repeat an option, and the command line adds to them.
+    let path = dir.write("mark.toml", "mark = [\"0:1\", \"2:3\"]\n");
+    let mut command = hd_command();
+    command
+        .env("ALWAYS_COLOR", "1")
+        .env("XDG_CONFIG_HOME", &dir.0);
+    let args = ["--config", path.to_str().unwrap(), "--mark", "4:5"];
+    let stdout = pipe(command.args(args), b"abcdef");
+    assert_eq!(stdout.matches("\x1b[4;7m").count(), 6);
+}
+
+#[test]
+fn test_watch_input() {
+    // Only a single file can be watched, so these fail without waiting for changes.
+    for args in [&["--watch"][..], &["--watch", "example.bin", "example.bin"]] {
+        let (status, stderr) = hd_err(args);
+        assert!(!status.success());
+        assert!(stderr.contains("requires a single file"));
+    }
+
+    // Only a dump is printed, checking for changes at least every millisecond.
+    let conflicts = [
+        (
+            &["--output-format", "hex"][..],
+            "`--watch` cannot be combined with `--output-format`",
+        ),
+        (
+            &["--seek-to", "ff"],
+            "`--watch` cannot be combined with `--seek-to`",
+        ),
+        (
+            &["--sample", "2"],
+            "`--watch` cannot be combined with `--sample`",
+        ),
+        (
+            &["--watch-interval", "0"],
+            "`--watch-interval` must be at least 1",
+        ),
+    ];
+    for (args, error) in conflicts {
+        let (status, stderr) = hd_err(&[&["--watch", "example.bin"], args].concat());
+        assert!(!status.success());
+        assert!(stderr.contains(error), "{args:?}");
+    }
+}
+
+#[test]
+#[cfg(feature = "interactive")]
+fn test_interactive_input() {
+    // Only a single raw file can be browsed, so these fail without opening the terminal.
+    let inputs = [
+        &["--interactive"][..],
+        &["--interactive", "example.bin", "example.bin"],
+        &["--interactive", "--decompress", "example.bin"],
+        &["--interactive", "--output-format", "hex", "example.bin"],
+    ];
+    for args in inputs {
+        let (status, stderr) = hd_err(args);
+        assert!(!status.success());
+        assert!(stderr.contains("`--interactive` requires a single uncompressed file"));
+    }
+}
+
+#[test]
+fn test_endian() {
+    let stdout = hd_stdin(&["--endian", "little", "--group", "4"], b"\x01\x02\x03\x04");
+    assert!(stdout.starts_with("0000_0000_0000_0000: 04030201 "));
+
+    // Groups that are not a power of two have no byte order.
+    let (status, stderr) = hd_err(&["--endian", "little", "--group", "3", "example.bin"]);
+    assert!(!status.success());
+    assert!(stderr.contains("`--endian little` requires a group of 2, 4, or 8 bytes, got 3"));
+}
+
+#[test]
+fn test_pad_last_group() {
+    let args = ["--endian", "little", "-g", "4", "-w", "8", "--no-addr"];
+    let input = b"\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a";
+
+    // The last group is reversed in place, unless it is padded to line up with the rows above.
+    assert_eq!(
+        hd_stdin(&args, input),
+        " 04030201 08070605 | ........ |\n 0a09              | ..       |\n"
+    );
+    assert_eq!(
+        hd_stdin(&[&args[..], &["--pad-last-group"]].concat(), input),
+        " 04030201 08070605 | ........ |\n     0a09          | ..       |\n"
+    );
+}
+
+#[test]
+fn test_preset() {
+    let input = b"\xab\xcd\xef\x01\x02";
+    assert_eq!(
+        hd_stdin(&["--preset", "ipv4", "--no-addr"], input),
+        " abcd ef01 | .... |\n 02        | .    |\n"
+    );
+
+    // The command line overrides the preset.
+    assert_eq!(
+        hd_stdin(&["--preset", "ipv4", "--no-addr", "-w", "8"], input),
+        " abcd ef01 02        | .....    |\n"
+    );
+
+    let (status, stderr) = hd_err(&["--preset", "ipv6", "example.bin"]);
+    assert!(!status.success());
+    assert!(stderr.contains("Unknown preset: `ipv6`"));
+}
+
+#[test]
+fn test_detect_stride() {
+    let records: Vec<u8> = (0..64_u8)
+        .flat_map(|i| [b'I', b'D', i, 0, 0, 0, i.wrapping_mul(37), 0xff, 0xee, 0])
+        .collect();
+
+    // The sample read from stdin is still printed.
+    let stdout = hd_stdin(&["--apply-stride", "--quiet"], &records);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 64);
+    assert!(lines[1].starts_with("0000_0000_0000_000a: 4944 0100 0000 25ff ee00 | "));
+
+    let output = hd_command()
+        .args(["--detect-stride", "example.bin"])
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, hd(&["example.bin"]).stdout);
+    assert_eq!(output.stderr, b"No stride detected\n");
+}
+
+#[test]
+fn test_wide_final_column() {
+    // A double-width character starting in the last column wraps to the next row.
+    let stdout = hd_stdin(&["--width", "4", "--no-addr"], "abc\u{4e2d}d".as_bytes());
+    assert_eq!(stdout, " 6162 63e4 | abc  |\n b8ad 64   | \u{4e2d}d  |\n");
+}